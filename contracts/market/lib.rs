@@ -41,6 +41,44 @@ mod marketplace {
         }
     }
 
+    /// Representa el perfil público de un usuario registrado.
+    ///
+    /// Se crea vacío al registrarse y puede completarse con `actualizar_perfil`.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Perfil {
+        /// Nombre visible del usuario, único en el marketplace (vacío si no se definió).
+        pub nombre_visible: String,
+        /// URI del avatar o de los metadatos extendidos del perfil.
+        pub uri_metadata: String,
+        /// Hash del contenido referenciado por `uri_metadata`, para verificar su integridad.
+        pub hash_contenido: Option<Hash>,
+        /// Hash de los datos de contacto del usuario, compartidos fuera de la cadena.
+        pub hash_contacto: Option<Hash>,
+        /// Bloque en el que el usuario se registró.
+        pub bloque_registro: BlockNumber,
+    }
+
+    /// Representa una categoría del registro administrado por el admin.
+    ///
+    /// Las categorías forman un árbol: cada una puede tener una categoría padre,
+    /// que debe existir al momento de crearla. Publicar con `publicar` en una categoría
+    /// sin registrar la agrega como categoría raíz.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Categoria {
+        /// Nombre canónico de la categoría.
+        pub nombre: String,
+        /// ID de la categoría padre, o `None` si es una categoría raíz.
+        pub padre: Option<u32>,
+    }
+
     /// Define el estado de una orden de compra.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
     #[cfg_attr(
@@ -58,6 +96,37 @@ mod marketplace {
         Cancelada,
     }
 
+    /// Tipo de producto, que determina cómo se entrega al comprador.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum TipoProducto {
+        /// Producto físico, entregado con `marcar_enviado` y `marcar_recibido`.
+        Fisico,
+        /// Producto descargable. El contenido está cifrado y el vendedor entrega a cada
+        /// comprador la clave para descifrarlo con `entregar_clave`.
+        Digital {
+            /// Referencia al contenido cifrado (por ejemplo, un CID de IPFS).
+            referencia_cifrada: String,
+        },
+    }
+
+    /// Datos de lanzamiento de un producto publicado en preventa.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Lanzamiento {
+        /// Momento (timestamp del bloque, en milisegundos) a partir del cual el
+        /// vendedor puede enviar las órdenes.
+        pub fecha_lanzamiento: Timestamp,
+        /// Indica si el vendedor canceló el lanzamiento.
+        pub cancelado: bool,
+    }
+
     /// Representa un producto en venta en el marketplace.
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
     #[cfg_attr(
@@ -75,10 +144,193 @@ mod marketplace {
         pub precio: Balance,
         /// La cantidad de unidades disponibles del producto.
         pub stock: u32,
-        /// Categoría del producto.
-        pub categoria: String,
+        /// ID de la categoría del producto en el registro de categorías.
+        pub id_categoria: u32,
+        /// Reputación mínima que debe tener un comprador para poder comprar el producto.
+        pub requisito_reputacion: Option<RequisitoReputacion>,
+        /// Indica si el producto está publicado y puede comprarse.
+        pub activo: bool,
+        /// Pausas generales del vendedor registradas cuando el producto se publicó o se
+        /// reactivó. Si el vendedor vuelve a pausar todas sus publicaciones (al darse de baja
+        /// o dejar de ser vendedor), el producto queda inactivo aunque `activo` siga en `true`.
+        pub pausas_vendedor: u32,
+        /// Porcentaje del monto de cada venta referida que recibe el referido (0 si el
+        /// producto no ofrece comisión).
+        pub porcentaje_referido: u8,
+        /// Tipo de producto (físico o digital).
+        pub tipo: TipoProducto,
+        /// Plan de suscripción que ofrece el producto, si corresponde.
+        pub plan_suscripcion: Option<PlanSuscripcion>,
+        /// Datos de lanzamiento si el producto se publicó en preventa.
+        pub lanzamiento: Option<Lanzamiento>,
+        /// Timestamp del bloque (en milisegundos) en el que se publicó el producto.
+        pub creado_en: Timestamp,
+        /// Timestamp del bloque (en milisegundos) de la última actividad del producto: una
+        /// modificación hecha por el vendedor, una venta, una reserva, una suscripción o una
+        /// depuración de publicaciones inactivas.
+        pub actualizado_en: Timestamp,
+    }
+
+    /// Reputación mínima como comprador exigida por un vendedor para uno de sus productos.
+    ///
+    /// Se evalúa contra `ReputacionUsuario::como_comprador` al momento de la compra.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RequisitoReputacion {
+        /// Promedio mínimo como comprador, multiplicado por 100 (ej: 400 = 4.00 estrellas).
+        pub promedio_minimo_x100: u32,
+        /// Cantidad mínima de calificaciones recibidas como comprador.
+        pub calificaciones_minimas: u32,
+    }
+
+    /// Plan de suscripción ofrecido por un producto: un cobro de `precio_periodo`
+    /// cada `periodo_bloques` bloques.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PlanSuscripcion {
+        /// Duración de cada período, en bloques.
+        pub periodo_bloques: BlockNumber,
+        /// Precio de cada período.
+        pub precio_periodo: Balance,
+    }
+
+    /// Representa la suscripción de un comprador a un producto.
+    ///
+    /// El comprador paga por adelantado `periodos_pagados` períodos, que quedan retenidos
+    /// en el contrato. Cada período iniciado genera su propia `Orden` en estado `Recibido`
+    /// cuando el vendedor lo cobra con `cobrar_suscripcion`.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Suscripcion {
+        /// La cuenta del comprador suscrito.
+        pub comprador: AccountId,
+        /// La cuenta del vendedor del producto.
+        pub vendedor: AccountId,
+        /// El ID del producto al que se suscribió.
+        pub id_prod: u32,
+        /// Duración de cada período, en bloques (fijada al suscribirse).
+        pub periodo_bloques: BlockNumber,
+        /// Precio de cada período (fijado al suscribirse).
+        pub precio_periodo: Balance,
+        /// Cantidad de períodos pagados por adelantado (al cancelar, se reduce a los ya
+        /// iniciados).
+        pub periodos_pagados: u32,
+        /// Cantidad de períodos ya convertidos en órdenes y cobrados por el vendedor.
+        pub periodos_cobrados: u32,
+        /// Bloque en el que comenzó el primer período.
+        pub bloque_inicio: BlockNumber,
+        /// Indica si la suscripción sigue vigente.
+        pub activa: bool,
+    }
+
+    /// Criterios de filtrado para `buscar_productos`.
+    ///
+    /// Todos los campos son opcionales; un filtro vacío incluye a todos los productos activos.
+    #[derive(Debug, Clone, PartialEq, Eq, Default, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FiltroBusqueda {
+        /// Texto que debe aparecer en el nombre del producto (sin distinguir mayúsculas).
+        pub texto: Option<String>,
+        /// Categoría del producto; incluye sus subcategorías si `incluir_subcategorias` es `true`.
+        pub id_categoria: Option<u32>,
+        /// Indica si la búsqueda por categoría incluye a todas sus descendientes.
+        pub incluir_subcategorias: bool,
+        /// Precio mínimo (inclusive).
+        pub precio_min: Option<Balance>,
+        /// Precio máximo (inclusive).
+        pub precio_max: Option<Balance>,
+        /// Vendedor del producto.
+        pub vendedor: Option<AccountId>,
+        /// Promedio mínimo del vendedor como tal, multiplicado por 100.
+        pub reputacion_minima_x100: Option<u32>,
+        /// Indica si solo se incluyen productos con stock disponible.
+        pub solo_con_stock: bool,
+    }
+
+    /// Criterio de ordenamiento de los resultados de `buscar_productos`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum OrdenBusqueda {
+        /// Del precio más bajo al más alto.
+        PrecioAscendente,
+        /// Del precio más alto al más bajo.
+        PrecioDescendente,
+        /// De la publicación más reciente a la más antigua (según `creado_en`).
+        MasRecientes,
+        /// Del vendedor con mejor promedio al de peor promedio.
+        MejorCalificados,
+    }
+
+    /// Punto desde el que continúa `buscar_productos`: la página siguiente empieza después de él.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum CursorBusqueda {
+        /// Último producto recorrido de un índice ordenado y su clave (precio o `creado_en`).
+        Indice { clave: u128, id_producto: u32 },
+        /// Próxima posición a leer al recorrer los vendedores por promedio: el promedio (x100),
+        /// la posición del vendedor entre los de ese promedio y la posición en su índice de
+        /// productos.
+        Vendedores {
+            promedio_x100: u32,
+            posicion_vendedor: u32,
+            posicion_producto: u32,
+        },
+    }
+
+    /// Una página de resultados de `buscar_productos`.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PaginaBusqueda {
+        /// Los productos de la página, como tuplas `(id_producto, Producto)`.
+        pub productos: Vec<(u32, Producto)>,
+        /// Cursor para pedir la página siguiente, o `None` si no quedan productos por recorrer.
+        pub siguiente: Option<CursorBusqueda>,
+    }
+
+    /// Criterio por el que se ordena un índice de búsqueda.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    enum CriterioIndice {
+        /// Por precio y, a igual precio, por ID de producto.
+        Precio,
+        /// Por `creado_en` y, a igual fecha, por ID de producto.
+        Creacion,
+    }
+
+    /// Productos que cubre un índice de búsqueda.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    enum AlcanceIndice {
+        /// Todos los productos publicados.
+        Todos,
+        /// Los productos de una categoría y de todas sus descendientes.
+        Categoria(u32),
+        /// Los productos de un vendedor.
+        Vendedor(AccountId),
     }
 
+    /// Índice ordenado de búsqueda: su criterio de orden y los productos que cubre.
+    type IndiceBusqueda = (CriterioIndice, AlcanceIndice);
+
+    /// Bloque de un índice de búsqueda: su criterio, su alcance y el ID del bloque.
+    type BloqueBusqueda = (CriterioIndice, AlcanceIndice, u32);
+
+    /// Par `(clave, id_producto)` por el que se ordena un producto en un índice de búsqueda.
+    type ParBusqueda = (u128, u32);
+
+    /// Cabecera de un bloque de un índice de búsqueda: el primer par del bloque y su ID, como
+    /// `(clave, id_producto, id_bloque)`.
+    type CabeceraBusqueda = (u128, u32, u32);
+
     /// Representa una orden de compra de un producto.
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
     #[cfg_attr(
@@ -99,6 +351,150 @@ mod marketplace {
         /// El monto total pagado por la orden (precio × cantidad).
         /// Este monto queda retenido en escrow hasta que se complete o cancele la orden.
         pub monto_total: Balance,
+        /// La cuenta que recibe la orden: el comprador, salvo en las compras para regalo.
+        /// Es quien confirma la recepción y califica al vendedor.
+        pub destinatario: AccountId,
+    }
+
+    /// Estado de la entrega de una orden de un producto digital.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EntregaDigital {
+        /// Bloque en el que se realizó la compra.
+        pub bloque_compra: BlockNumber,
+        /// Clave del contenido cifrada para el destinatario de la orden, una vez entregada.
+        pub clave_cifrada: Option<Vec<u8>>,
+        /// Bloque en el que el vendedor entregó la clave.
+        pub bloque_entrega: Option<BlockNumber>,
+        /// Indica si el destinatario disputó la entrega; mientras tanto los fondos quedan
+        /// retenidos.
+        pub disputada: bool,
+    }
+
+    /// Mensaje intercambiado entre el comprador y el vendedor de una orden.
+    ///
+    /// El contenido se guarda tal como lo envía el remitente; se espera que sea el texto
+    /// cifrado para el otro participante (y el árbitro) o un hash de un mensaje externo.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct MensajeOrden {
+        /// La cuenta que envió el mensaje.
+        pub remitente: AccountId,
+        /// El contenido cifrado o hash del mensaje.
+        pub contenido: Vec<u8>,
+        /// Timestamp del bloque (en milisegundos) en el que se envió.
+        pub momento: Timestamp,
+    }
+
+    /// Referido que acompañó la compra de una orden.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ReferidoOrden {
+        /// Cuenta del referido.
+        pub cuenta: AccountId,
+        /// Porcentaje de comisión vigente en el producto al momento de la compra.
+        pub porcentaje: u8,
+        /// Comisión pagada al referido, una vez completada la orden.
+        pub comision: Option<Balance>,
+    }
+
+    /// Rendimiento acumulado de una cuenta como referido.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EstadisticasReferido {
+        /// Cantidad de órdenes compradas con este referido.
+        pub ordenes_referidas: u32,
+        /// Cantidad de órdenes referidas que ya se completaron y pagaron comisión.
+        pub ordenes_completadas: u32,
+        /// Total de comisiones cobradas.
+        pub ganancias: Balance,
+    }
+
+    /// Fianza mínima que debe tener depositada un vendedor para publicar productos caros.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RequisitoFianza {
+        /// Precio a partir del cual se exige la fianza.
+        pub precio_minimo: Balance,
+        /// Fianza mínima exigida.
+        pub fianza_minima: Balance,
+    }
+
+    /// Resultado de la verificación de conservación de fondos del contrato.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EstadoFondos {
+        /// Suma de los fondos retenidos en escrow de todas las órdenes.
+        pub fondos_retenidos: Balance,
+        /// Suma de los fondos pagados por adelantado en suscripciones activas.
+        pub fondos_suscripciones: Balance,
+        /// Suma de los depósitos de reservas abiertas.
+        pub depositos_reservas: Balance,
+        /// Suma de las fianzas de los vendedores.
+        pub fianzas: Balance,
+        /// Suma de los saldos retirables de todos los usuarios.
+        pub saldos_retirables: Balance,
+        /// Balance actual del contrato.
+        pub balance: Balance,
+        /// Indica si la suma de todos los fondos comprometidos no supera el balance.
+        pub cumple: bool,
+    }
+
+    /// Reserva temporal de stock de un producto para un comprador.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Reserva {
+        /// Cantidad de unidades reservadas.
+        pub cantidad: u32,
+        /// Depósito pagado al reservar.
+        pub deposito: Balance,
+        /// Bloque a partir del cual la reserva está vencida.
+        pub bloque_vencimiento: BlockNumber,
+    }
+
+    /// Progreso de una migración de identidad aceptada cuyas referencias todavía se
+    /// están trasladando a la nueva cuenta.
+    ///
+    /// Cada campo es la posición del próximo elemento a trasladar en el índice
+    /// correspondiente de la cuenta anterior.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct MigracionEnCurso {
+        /// La cuenta que inició la migración.
+        pub anterior: AccountId,
+        /// Posición en los compradores bloqueados por la cuenta anterior.
+        pub bloqueos: u32,
+        /// Posición en los vendedores que bloquearon a la cuenta anterior.
+        pub bloqueos_recibidos: u32,
+        /// Posición en los productos publicados por la cuenta anterior.
+        pub productos: u32,
+        /// Posición en las órdenes de la cuenta anterior.
+        pub ordenes: u32,
+        /// Posición en las suscripciones de la cuenta anterior.
+        pub suscripciones: u32,
+        /// Posición en las reservas de la cuenta anterior.
+        pub reservas: u32,
     }
 
     /// Representa una solicitud de cancelación pendiente para una orden.
@@ -112,6 +508,47 @@ mod marketplace {
         pub solicitante: AccountId,
     }
 
+    /// Cambio registrado en el historial de una orden.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum EventoOrden {
+        /// La orden fue creada en estado `Pendiente`.
+        Creada,
+        /// La orden pasó a `Enviado`.
+        Enviada,
+        /// La orden pasó a `Recibido`.
+        Recibida,
+        /// Un participante solicitó cancelar la orden.
+        CancelacionSolicitada {
+            /// El participante que solicitó la cancelación.
+            solicitante: AccountId,
+        },
+        /// El otro participante rechazó la solicitud de cancelación.
+        CancelacionRechazada,
+        /// La orden pasó a `Cancelada`.
+        Cancelada,
+        /// El comprador disputó la entrega de una orden digital.
+        Disputada,
+    }
+
+    /// Entrada del historial de una orden.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RegistroOrden {
+        /// El cambio ocurrido.
+        pub evento: EventoOrden,
+        /// Timestamp del bloque (en milisegundos) en el que ocurrió.
+        pub momento: Timestamp,
+        /// Bloque en el que ocurrió.
+        pub bloque: BlockNumber,
+    }
+
     /// Representa la reputación de un usuario en el marketplace.
     ///
     /// Los valores por defecto son `(0, 0)` para ambos roles, indicando
@@ -132,6 +569,177 @@ mod marketplace {
         pub como_vendedor: (u32, u32),
     }
 
+    /// Puntaje de reputación ponderado por monto y antigüedad para uno de los roles de un usuario.
+    ///
+    /// Cada calificación pesa según el `monto_total` de la orden calificada, y ese peso decae
+    /// con la antigüedad de modo que las calificaciones recientes y de órdenes grandes dominan
+    /// el promedio. Con `h = VIDA_MEDIA_REPUTACION_BLOQUES`, una calificación de hace `t`
+    /// bloques pesa `h / (h + t % h) / 2^(t / h)` de su monto: se reduce exactamente a la
+    /// mitad en cada vida media completa y, entre medio, decae de forma hiperbólica (a las
+    /// `h / 2` pesa 2/3, no 1/√2).
+    ///
+    /// En lugar de decaer las sumas existentes, cada calificación nueva entra escalada por
+    /// `escala(bloque - bloque_base)`: el resultado es el mismo promedio, y el aporte de cada
+    /// calificación queda guardado exactamente para poder reemplazarlo al editarla.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PuntajePonderado {
+        /// Suma de `puntos × peso` de las calificaciones recibidas, escalada a `bloque_base`.
+        pub suma_ponderada: Balance,
+        /// Suma de los pesos de las calificaciones recibidas, escalada a `bloque_base`.
+        pub peso_total: Balance,
+        /// Bloque de referencia de la escala; avanza por vidas medias completas.
+        pub bloque_base: BlockNumber,
+    }
+
+    /// Peso escalado con el que una calificación entró a un `PuntajePonderado`.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct AportePonderado {
+        /// Monto de la orden multiplicado por la escala del bloque de la calificación.
+        pub peso: Balance,
+        /// `bloque_base` del puntaje en el momento de la calificación.
+        pub bloque_base: BlockNumber,
+    }
+
+    impl PuntajePonderado {
+        /// Obtiene el promedio ponderado multiplicado por 100 para evitar decimales.
+        ///
+        /// Ejemplo: 450 = 4.50 estrellas. Devuelve 0 si no hay calificaciones con peso.
+        pub fn promedio_x100(&self) -> u32 {
+            if self.peso_total == 0 {
+                return 0;
+            }
+            let promedio = self
+                .suma_ponderada
+                .saturating_mul(100)
+                .saturating_div(self.peso_total);
+            u32::try_from(promedio).unwrap_or(u32::MAX)
+        }
+
+        /// Obtiene la suma de los montos calificados con el decaimiento aplicado hasta `bloque`.
+        #[cfg(test)]
+        fn peso_en(&self, bloque: BlockNumber) -> Balance {
+            let transcurridos = bloque.saturating_sub(self.bloque_base);
+            let valor = self
+                .peso_total
+                .checked_shr(transcurridos / VIDA_MEDIA_REPUTACION_BLOQUES)
+                .unwrap_or(0);
+            escala(transcurridos % VIDA_MEDIA_REPUTACION_BLOQUES)
+                .map(|divisor| valor / divisor)
+                .unwrap_or(0)
+        }
+
+        /// Avanza `bloque_base` por vidas medias completas cuando la escala se vuelve grande.
+        ///
+        /// Ambas sumas se dividen por la misma potencia de dos, y `reemplazar` aplica el mismo
+        /// desplazamiento a los aportes anteriores, así que nunca descuenta más de lo que quedó.
+        fn rebasar_hasta(&mut self, bloque: BlockNumber) {
+            let vidas_medias =
+                bloque.saturating_sub(self.bloque_base) / VIDA_MEDIA_REPUTACION_BLOQUES;
+            if vidas_medias < VIDAS_MEDIAS_POR_BASE {
+                return;
+            }
+            self.suma_ponderada = self.suma_ponderada.checked_shr(vidas_medias).unwrap_or(0);
+            self.peso_total = self.peso_total.checked_shr(vidas_medias).unwrap_or(0);
+            self.bloque_base = self
+                .bloque_base
+                .saturating_add(vidas_medias.saturating_mul(VIDA_MEDIA_REPUTACION_BLOQUES));
+        }
+
+        /// Incorpora una nueva calificación con el monto de la orden como peso.
+        ///
+        /// Devuelve el aporte que hay que guardar con la calificación para poder editarla.
+        fn agregar(
+            &mut self,
+            puntos: u8,
+            monto: Balance,
+            bloque: BlockNumber,
+        ) -> Result<AportePonderado, Error> {
+            self.rebasar_hasta(bloque);
+            let peso = escala(bloque.saturating_sub(self.bloque_base))
+                .and_then(|factor| monto.checked_mul(factor))
+                .ok_or(Error::OverflowAritmetico)?;
+            let aporte = peso
+                .checked_mul(puntos as Balance)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.suma_ponderada = self
+                .suma_ponderada
+                .checked_add(aporte)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.peso_total = self
+                .peso_total
+                .checked_add(peso)
+                .ok_or(Error::OverflowAritmetico)?;
+            Ok(AportePonderado {
+                peso,
+                bloque_base: self.bloque_base,
+            })
+        }
+
+        /// Reemplaza los puntos de una calificación ya incorporada con `aporte`.
+        ///
+        /// El peso de la calificación se mantiene; se descuenta exactamente lo que sumó con
+        /// los puntos anteriores y se suma lo que corresponde a los nuevos.
+        fn reemplazar(
+            &mut self,
+            aporte: &AportePonderado,
+            puntos_anteriores: u8,
+            puntos_nuevos: u8,
+        ) -> Result<(), Error> {
+            let desplazamiento =
+                self.bloque_base.saturating_sub(aporte.bloque_base) / VIDA_MEDIA_REPUTACION_BLOQUES;
+            let convertir = |puntos: u8| {
+                aporte
+                    .peso
+                    .checked_mul(puntos as Balance)
+                    .map(|v| v.checked_shr(desplazamiento).unwrap_or(0))
+                    .ok_or(Error::OverflowAritmetico)
+            };
+            let anterior = convertir(puntos_anteriores)?;
+            let nuevo = convertir(puntos_nuevos)?;
+            self.suma_ponderada = self
+                .suma_ponderada
+                .checked_sub(anterior)
+                .and_then(|suma| suma.checked_add(nuevo))
+                .ok_or(Error::OverflowAritmetico)?;
+            Ok(())
+        }
+    }
+
+    /// Obtiene el factor por el que se multiplica el peso de una calificación hecha
+    /// `transcurridos` bloques después de la base de su puntaje.
+    ///
+    /// El factor se duplica por cada vida media completa y crece linealmente dentro de la
+    /// vida media en curso, por lo que las calificaciones anteriores pierden peso relativo.
+    fn escala(transcurridos: BlockNumber) -> Option<Balance> {
+        let resto = (transcurridos % VIDA_MEDIA_REPUTACION_BLOQUES) as Balance;
+        (1 as Balance)
+            .checked_shl(transcurridos / VIDA_MEDIA_REPUTACION_BLOQUES)?
+            .checked_mul((VIDA_MEDIA_REPUTACION_BLOQUES as Balance).checked_add(resto)?)
+    }
+
+    /// Representa la reputación ponderada de un usuario en el marketplace.
+    ///
+    /// Es una alternativa a `ReputacionUsuario`, que se mantiene sin cambios por compatibilidad.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ReputacionPonderada {
+        /// Puntaje ponderado recibido como comprador.
+        pub como_comprador: PuntajePonderado,
+        /// Puntaje ponderado recibido como vendedor.
+        pub como_vendedor: PuntajePonderado,
+    }
+
     /// Representa el estado de calificaciones para una orden.
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Default)]
     #[cfg_attr(
@@ -143,12 +751,158 @@ mod marketplace {
         pub comprador_califico: bool,
         /// Indica si el vendedor ya ha calificado al comprador.
         pub vendedor_califico: bool,
+        /// Puntos (1-5) otorgados por el comprador al vendedor, o 0 si aún no calificó.
+        pub puntos_al_vendedor: u8,
+        /// Puntos (1-5) otorgados por el vendedor al comprador, o 0 si aún no calificó.
+        pub puntos_al_comprador: u8,
+        /// Respuesta pública del vendedor a la calificación que recibió del comprador.
+        pub respuesta_vendedor: Option<String>,
+        /// Respuesta pública del comprador a la calificación que recibió del vendedor.
+        pub respuesta_comprador: Option<String>,
+        /// Bloque en el que el comprador calificó al vendedor.
+        pub bloque_calificacion_comprador: BlockNumber,
+        /// Bloque en el que el vendedor calificó al comprador.
+        pub bloque_calificacion_vendedor: BlockNumber,
+        /// Aporte de la calificación del comprador al puntaje ponderado del vendedor.
+        pub aporte_al_vendedor: AportePonderado,
+        /// Aporte de la calificación del vendedor al puntaje ponderado del comprador.
+        pub aporte_al_comprador: AportePonderado,
+        /// Historial de ediciones realizadas sobre las calificaciones de la orden.
+        pub historial: Vec<EdicionCalificacion>,
+    }
+
+    /// Representa una edición de una calificación dentro del período de gracia.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EdicionCalificacion {
+        /// La cuenta que editó su calificación.
+        pub autor: AccountId,
+        /// Los puntos que tenía la calificación antes de la edición.
+        pub puntos_anteriores: u8,
+        /// Los puntos asignados en la edición.
+        pub puntos_nuevos: u8,
+        /// Bloque en el que se realizó la edición.
+        pub bloque: BlockNumber,
+    }
+
+    /// Evento emitido cuando un participante responde a la calificación que recibió.
+    ///
+    /// Permite que la contraparte (autor de la calificación) sea notificada de la respuesta.
+    #[ink(event)]
+    pub struct RespuestaPublicada {
+        /// El ID de la orden calificada.
+        #[ink(topic)]
+        pub oid: u32,
+        /// La cuenta que publicó la respuesta.
+        #[ink(topic)]
+        pub autor: AccountId,
+        /// La cuenta autora de la calificación respondida.
+        #[ink(topic)]
+        pub destinatario: AccountId,
     }
 
     /// Límites de longitud para strings en el contrato.
     const MAX_NOMBRE_LEN: usize = 64;
     const MAX_DESCRIPCION_LEN: usize = 256;
     const MAX_CATEGORIA_LEN: usize = 32;
+    const MAX_RESPUESTA_LEN: usize = 256;
+    const MAX_URI_LEN: usize = 128;
+
+    /// Cantidad máxima de productos que un usuario puede guardar como favoritos.
+    const MAX_FAVORITOS: usize = 100;
+
+    /// Cantidad máxima de IDs de producto que recorre una página de `listar_conteo_favoritos`.
+    const MAX_CONTEO_FAVORITOS_POR_PAGINA: u32 = 100;
+
+    /// Cantidad máxima de resultados que devuelve una página de `buscar_productos`.
+    const MAX_RESULTADOS_BUSQUEDA: u32 = 50;
+
+    /// Cantidad máxima de productos que recorre una llamada a `buscar_productos`, cumplan o no
+    /// el filtro.
+    const MAX_RECORRIDOS_BUSQUEDA: u32 = 500;
+
+    /// Cantidad máxima de pares `(clave, id_producto)` en un bloque de un índice de búsqueda;
+    /// al superarla, el bloque se divide en dos. Como cada división deja al menos la mitad
+    /// del bloque libre, un bloque se divide a lo sumo una vez cada `MAX_PARES_POR_BLOQUE / 2`
+    /// inserciones.
+    const MAX_PARES_POR_BLOQUE: usize = 64;
+
+    /// Cantidad máxima de usuarios devueltos por página en `listar_usuarios`.
+    const MAX_USUARIOS_POR_PAGINA: u32 = 100;
+
+    /// Cantidad máxima de registros devueltos por página en `historial_orden`.
+    const MAX_REGISTROS_POR_PAGINA: u32 = 50;
+
+    /// Cantidad máxima de posiciones recorridas por página en `listar_ordenes_de_destinatario`.
+    const MAX_ORDENES_POR_PAGINA: u32 = 50;
+
+    /// Cantidad máxima de períodos de una suscripción que se convierten en órdenes por llamada
+    /// a `cobrar_suscripcion` o `cancelar_suscripcion`.
+    const MAX_PERIODOS_POR_COBRO: u32 = 20;
+
+    /// Cantidad de bloques en los que el peso de una calificación se reduce a la mitad
+    /// (aproximadamente una semana con bloques de 6 segundos).
+    const VIDA_MEDIA_REPUTACION_BLOQUES: BlockNumber = 100_800;
+
+    /// Cantidad de vidas medias tras las que un `PuntajePonderado` avanza su bloque base,
+    /// para que la escala de las calificaciones nuevas no desborde.
+    const VIDAS_MEDIAS_POR_BASE: BlockNumber = 16;
+
+    /// Ventana por defecto, en bloques, para editar una calificación
+    /// (aproximadamente un día con bloques de 6 segundos).
+    const VENTANA_EDICION_BLOQUES_DEFAULT: BlockNumber = 14_400;
+
+    /// Plazo, en bloques desde la compra, para que el vendedor entregue la clave de un
+    /// producto digital (aproximadamente una hora con bloques de 6 segundos).
+    const PLAZO_ENTREGA_DIGITAL_BLOQUES: BlockNumber = 600;
+
+    /// Ventana, en bloques desde la entrega de la clave, durante la cual el comprador
+    /// puede disputar una orden digital antes de que se complete automáticamente
+    /// (aproximadamente un día con bloques de 6 segundos).
+    const VENTANA_DISPUTA_DIGITAL_BLOQUES: BlockNumber = 14_400;
+
+    /// Longitud máxima, en bytes, de la clave cifrada de un producto digital.
+    const MAX_CLAVE_LEN: usize = 512;
+
+    /// Longitud máxima, en bytes, del contenido de un mensaje de una orden.
+    const MAX_MENSAJE_LEN: usize = 512;
+
+    /// Cantidad máxima de mensajes que cada participante puede enviar en una orden.
+    const MAX_MENSAJES_POR_PARTICIPANTE: u32 = 50;
+
+    /// Cantidad máxima de mensajes devueltos por página en `obtener_mensajes`.
+    const MAX_MENSAJES_POR_PAGINA: u32 = 20;
+
+    /// Cantidad máxima de órdenes de una preventa cancelada que se reembolsan por llamada.
+    const MAX_REEMBOLSOS_POR_LLAMADA: u32 = 50;
+
+    /// Cantidad máxima de referencias (bloqueos, productos, órdenes y suscripciones) que
+    /// se trasladan por llamada en una migración de identidad.
+    const MAX_MIGRACION_POR_LLAMADA: u32 = 50;
+
+    /// Cantidad máxima de órdenes devueltas por página en `listar_referidos_orden`.
+    const MAX_REFERIDOS_POR_PAGINA: u32 = 50;
+
+    /// Cantidad de referidos con más comisiones cobradas que se mantienen en
+    /// `listar_mejores_referidos`.
+    const MAX_MEJORES_REFERIDOS: usize = 20;
+
+    /// Porcentaje máximo de comisión que un vendedor puede ofrecer a los referidos.
+    const MAX_PORCENTAJE_REFERIDO: u8 = 50;
+
+    /// Duración, en bloques, de una reserva de stock (aproximadamente diez minutos con
+    /// bloques de 6 segundos).
+    const DURACION_RESERVA_BLOQUES: BlockNumber = 100;
+
+    /// Depósito exigido para reservar, como porcentaje del precio de las unidades reservadas.
+    const DEPOSITO_RESERVA_PORCENTAJE: Balance = 5;
+
+    /// Porcentaje del depósito que se retiene como comisión anti-spam cuando la reserva
+    /// vence sin concretarse la compra. Se transfiere al vendedor.
+    const COMISION_RESERVA_PORCENTAJE: Balance = 20;
 
     /// Enumera los posibles errores que pueden ocurrir en el contrato.
     #[derive(Debug, PartialEq, Eq, Encode, Decode)]
@@ -196,8 +950,89 @@ mod marketplace {
         PagoInsuficiente,
         /// El monto enviado excede el precio de la compra.
         PagoExcesivo,
-        /// La transferencia de fondos al vendedor o comprador falló.
+        /// La transferencia de fondos al llamante falló.
         TransferenciaFallida,
+        /// No existe una calificación a la cual responder.
+        CalificacionInexistente,
+        /// Ya se ha respondido a esta calificación.
+        YaRespondido,
+        /// El período para editar la calificación ha vencido.
+        VentanaEdicionVencida,
+        /// La calificación ya fue editada una vez y no puede volver a modificarse.
+        YaEditado,
+        /// El vendedor bloqueó al comprador y no acepta operaciones suyas.
+        CompradorBloqueado,
+        /// El comprador no cumple la reputación mínima exigida por el producto.
+        ReputacionInsuficiente,
+        /// El nombre visible ya está en uso por otro usuario.
+        NombreEnUso,
+        /// El producto no está publicado y no puede comprarse.
+        ProductoInactivo,
+        /// El usuario tiene órdenes abiertas (Pendiente o Enviado), cancelaciones pendientes
+        /// o suscripciones activas.
+        OrdenesAbiertas,
+        /// El vendedor no puede dejar de serlo mientras tenga ventas sin completar o cancelar.
+        VentasPendientes,
+        /// No existe una migración pendiente desde la cuenta indicada hacia el llamante.
+        MigracionInexistente,
+        /// La cuenta destino de una migración es la misma, ya está registrada o ya tiene reputación.
+        CuentaDestinoInvalida,
+        /// El producto ya está en la lista de favoritos del usuario.
+        YaEsFavorito,
+        /// La lista de favoritos del usuario alcanzó el máximo permitido.
+        FavoritosLlenos,
+        /// La categoría especificada no existe en el registro.
+        CategoriaInexistente,
+        /// Ya existe una categoría con un nombre equivalente.
+        CategoriaDuplicada,
+        /// La operación no aplica a productos digitales (la entrega se hace con `entregar_clave`).
+        ProductoDigital,
+        /// La operación solo aplica a órdenes de productos digitales.
+        ProductoNoDigital,
+        /// El plazo para realizar la operación ya venció.
+        PlazoVencido,
+        /// El plazo o la ventana requeridos para realizar la operación aún no vencieron.
+        PlazoNoVencido,
+        /// El producto no ofrece un plan de suscripción.
+        SinPlanSuscripcion,
+        /// La suscripción especificada no existe.
+        SuscripcionInexistente,
+        /// La suscripción ya fue cancelada o todos sus períodos fueron cobrados.
+        SuscripcionFinalizada,
+        /// El producto no se publicó en preventa.
+        SinLanzamiento,
+        /// La orden no puede enviarse antes de la fecha de lanzamiento del producto.
+        LanzamientoPendiente,
+        /// El vendedor canceló el lanzamiento del producto.
+        LanzamientoCancelado,
+        /// El comprador ya tiene una reserva vigente para el producto.
+        ReservaExistente,
+        /// No existe una reserva del comprador para el producto.
+        ReservaInexistente,
+        /// La fianza del vendedor no alcanza para la operación.
+        FianzaInsuficiente,
+        /// El usuario tiene una fianza depositada que debe retirar antes de darse de baja.
+        FianzaDepositada,
+        /// El usuario no tiene saldo para retirar.
+        SinSaldoRetirable,
+        /// El monto a retirar supera el saldo retirable del usuario.
+        SaldoInsuficiente,
+        /// Se intentó ejecutar un mensaje que mueve fondos mientras otro estaba en curso.
+        Reentrada,
+        /// El administrador no configuró un plazo de inactividad para las publicaciones.
+        SinPlazoInactividad,
+        /// El participante alcanzó la cantidad máxima de mensajes en la orden.
+        MensajesLlenos,
+        /// La cuenta tiene una migración de identidad cuyas referencias todavía se trasladan.
+        MigracionEnCurso,
+        /// La orden digital tiene una disputa abierta.
+        OrdenEnDisputa,
+        /// La orden digital no tiene una disputa abierta.
+        DisputaInexistente,
+        /// La orden ya fue compensada con la fianza del vendedor.
+        OrdenYaCompensada,
+        /// La cuenta migró su identidad a otra cuenta.
+        CuentaMigrada,
     }
 
     /// La estructura de almacenamiento principal del contrato.
@@ -210,6 +1045,10 @@ mod marketplace {
     pub struct Marketplace {
         /// Asigna un rol a cada cuenta de usuario.
         roles: Mapping<AccountId, Rol>,
+        /// Almacena el perfil público de cada usuario registrado.
+        perfiles: Mapping<AccountId, Perfil>,
+        /// Asocia cada nombre visible (normalizado en minúsculas) a la cuenta que lo usa.
+        nombres_visibles: Mapping<String, AccountId>,
         /// Almacena los productos publicados, mapeados por su ID.
         productos: Mapping<u32, Producto>,
         /// Almacena las órdenes de compra, mapeadas por su ID.
@@ -218,20 +1057,176 @@ mod marketplace {
         cancelaciones_pendientes: Mapping<u32, CancelacionPendiente>,
         /// Almacena la reputación de cada usuario.
         reputaciones: Mapping<AccountId, ReputacionUsuario>,
+        /// Almacena la reputación ponderada por monto y antigüedad de cada usuario.
+        reputaciones_ponderadas: Mapping<AccountId, ReputacionPonderada>,
         /// Almacena el estado de calificaciones para cada orden.
         calificaciones: Mapping<u32, CalificacionOrden>,
-        /// Suma y cantidad de calificaciones de vendedores por categoría (promedio = suma / cantidad).
-        calificaciones_por_categoria: Mapping<String, (u32, u32)>,
+        /// Suma y cantidad de calificaciones de vendedores por ID de categoría (promedio = suma / cantidad).
+        calificaciones_por_categoria: Mapping<u32, (u32, u32)>,
+        /// Compradores bloqueados por cada vendedor ((vendedor, comprador) -> bloqueado).
+        compradores_bloqueados: Mapping<(AccountId, AccountId), bool>,
+        /// Entregas de las órdenes de productos digitales (orden_id -> entrega).
+        entregas_digitales: Mapping<u32, EntregaDigital>,
+        /// Historial de cambios de cada orden ((orden_id, posición) -> registro), en orden
+        /// cronológico.
+        historial_ordenes: Mapping<(u32, u32), RegistroOrden>,
+        /// Cantidad de registros en el historial de cada orden (orden_id -> cantidad).
+        total_registros_orden: Mapping<u32, u32>,
+        /// Mensajes entre los participantes de cada orden ((orden_id, posición) -> mensaje),
+        /// en orden cronológico.
+        mensajes_orden: Mapping<(u32, u32), MensajeOrden>,
+        /// Cantidad de mensajes de cada orden (orden_id -> cantidad).
+        total_mensajes_orden: Mapping<u32, u32>,
+        /// Cantidad de mensajes enviados por cada participante de una orden
+        /// ((orden_id, remitente) -> cantidad).
+        mensajes_enviados: Mapping<(u32, AccountId), u32>,
+        /// Árbitro asignado por el administrador a cada orden (orden_id -> árbitro).
+        arbitros_orden: Mapping<u32, AccountId>,
+        /// Referidos de las órdenes compradas con referido (orden_id -> referido).
+        referidos_orden: Mapping<u32, ReferidoOrden>,
+        /// Órdenes compradas con referido (posición -> orden_id).
+        ordenes_con_referido: Mapping<u32, u32>,
+        /// Cantidad de órdenes compradas con referido.
+        total_ordenes_con_referido: u32,
+        /// Rendimiento acumulado de cada referido (órdenes y comisiones cobradas).
+        estadisticas_referidos: Mapping<AccountId, EstadisticasReferido>,
+        /// Referidos con más comisiones cobradas, de mayor a menor
+        /// (hasta `MAX_MEJORES_REFERIDOS`).
+        mejores_referidos: Vec<AccountId>,
+        /// Fianza depositada por cada vendedor.
+        fianzas: Mapping<AccountId, Balance>,
+        /// Órdenes completadas cuyo comprador ya fue compensado con la fianza del vendedor.
+        ordenes_compensadas: Mapping<u32, bool>,
+        /// Fianza mínima exigida para publicar productos caros, si el administrador la configuró.
+        requisito_fianza: Option<RequisitoFianza>,
+        /// Tiempo (en milisegundos) sin actividad tras el cual una publicación activa
+        /// puede despublicarse con `depurar_publicaciones`, si el administrador lo configuró.
+        plazo_inactividad: Option<Timestamp>,
+        /// Órdenes de cada producto en preventa ((producto_id, posición) -> orden_id).
+        ordenes_preventa: Mapping<(u32, u32), u32>,
+        /// Cantidad de órdenes de cada producto en preventa (producto_id -> cantidad).
+        total_ordenes_preventa: Mapping<u32, u32>,
+        /// Posición de la próxima orden a reembolsar de cada preventa cancelada
+        /// (producto_id -> posición).
+        reembolsos_preventa: Mapping<u32, u32>,
+        /// Reservas de stock ((comprador, producto) -> reserva).
+        reservas: Mapping<(AccountId, u32), Reserva>,
+        /// Productos reservados por cada cuenta ((cuenta, posición) -> producto_id).
+        reservas_de_cuenta: Mapping<(AccountId, u32), u32>,
+        /// Cantidad de reservas indexadas para cada cuenta.
+        total_reservas_de_cuenta: Mapping<AccountId, u32>,
+        /// Fondos acreditados a cada usuario (pagos de ventas, reembolsos, comisiones)
+        /// pendientes de retirar con `retirar` o `retirar_monto`.
+        saldos_retirables: Mapping<AccountId, Balance>,
         /// Fondos retenidos en escrow por cada orden (orden_id -> balance).
         /// Los fondos se liberan al vendedor cuando la orden se marca como recibida,
         /// o se devuelven al comprador si la orden se cancela.
         fondos_retenidos: Mapping<u32, Balance>,
+        /// Registro de categorías administrado por el admin (id -> categoría).
+        categorias: Mapping<u32, Categoria>,
+        /// Índice de categorías por nombre normalizado, para evitar duplicados.
+        nombres_categoria: Mapping<String, u32>,
+        /// El ID que se asignará a la próxima categoría creada.
+        next_categoria_id: u32,
+        /// Subcategorías directas de cada categoría ((id, posición) -> id de la hija).
+        subcategorias: Mapping<(u32, u32), u32>,
+        /// Cantidad de subcategorías directas de cada categoría.
+        total_subcategorias: Mapping<u32, u32>,
+        /// Índice de productos por categoría ((id_categoria, posición) -> id de producto).
+        productos_por_categoria: Mapping<(u32, u32), u32>,
+        /// Cantidad de productos indexados en cada categoría.
+        total_productos_por_categoria: Mapping<u32, u32>,
+        /// Índice de productos por vendedor ((vendedor, posición) -> id de producto).
+        productos_por_vendedor: Mapping<(AccountId, u32), u32>,
+        /// Cantidad de productos indexados para cada vendedor.
+        total_productos_por_vendedor: Mapping<AccountId, u32>,
+        /// Bloques de los índices ordenados de búsqueda ((criterio, alcance, id de bloque) ->
+        /// pares `(clave, id_producto)` en orden).
+        bloques_busqueda: Mapping<BloqueBusqueda, Vec<ParBusqueda>>,
+        /// Cabeceras de los bloques de cada índice de búsqueda, en orden
+        /// ((índice, posición) -> cabecera).
+        cabeceras_busqueda: Mapping<(IndiceBusqueda, u32), CabeceraBusqueda>,
+        /// Cantidad de bloques de cada índice de búsqueda y el próximo ID de bloque libre.
+        total_cabeceras_busqueda: Mapping<IndiceBusqueda, (u32, u32)>,
+        /// Vendedores que alcanzaron cada promedio como vendedor ((promedio x100, posición) ->
+        /// vendedor). Conserva posiciones vencidas; la vigente está en `posicion_por_promedio`.
+        vendedores_por_promedio: Mapping<(u32, u32), AccountId>,
+        /// Cantidad de posiciones usadas en `vendedores_por_promedio` para cada promedio.
+        total_vendedores_por_promedio: Mapping<u32, u32>,
+        /// Posición vigente de cada vendedor en `vendedores_por_promedio`.
+        posicion_por_promedio: Mapping<AccountId, (u32, u32)>,
+        /// Promedios (x100) con vendedores en `vendedores_por_promedio`, de mayor a menor
+        /// (posición -> promedio).
+        promedios_con_vendedores: Mapping<u32, u32>,
+        /// Cantidad de promedios en `promedios_con_vendedores`.
+        total_promedios_con_vendedores: u32,
+        /// Cantidad de veces que cada vendedor pausó todas sus publicaciones a la vez.
+        pausas_vendedor: Mapping<AccountId, u32>,
         /// El ID que se asignará al próximo producto publicado.
         next_prod_id: u32,
         /// El ID que se asignará a la próxima orden creada.
         next_order_id: u32,
-        /// Lista de todos los usuarios registrados (para iterar en reportes)
-        usuarios_registrados: Vec<AccountId>,
+        /// Suscripciones de compradores a productos (suscripcion_id -> suscripción).
+        suscripciones: Mapping<u32, Suscripcion>,
+        /// Fondos prepagados aún no cobrados de cada suscripción (suscripcion_id -> balance).
+        fondos_suscripciones: Mapping<u32, Balance>,
+        /// El ID que se asignará a la próxima suscripción.
+        next_suscripcion_id: u32,
+        /// Usuarios registrados por posición (para iterar en reportes sin cargar la lista
+        /// completa en cada llamada).
+        usuarios: Mapping<u32, AccountId>,
+        /// Cantidad de usuarios registrados en `usuarios`.
+        total_usuarios: u32,
+        /// Posición de cada usuario en `usuarios`, para quitarlo en O(1).
+        indice_usuarios: Mapping<AccountId, u32>,
+        /// Cantidad de órdenes abiertas (Pendiente o Enviado) de cada usuario:
+        /// (como comprador, como vendedor).
+        ordenes_abiertas: Mapping<AccountId, (u32, u32)>,
+        /// Migraciones de identidad iniciadas y aún no aceptadas (cuenta anterior -> cuenta nueva).
+        migraciones_pendientes: Mapping<AccountId, AccountId>,
+        /// Migraciones aceptadas que todavía trasladan referencias (cuenta nueva -> progreso).
+        migraciones_en_curso: Mapping<AccountId, MigracionEnCurso>,
+        /// Cuentas anteriores de las migraciones en curso (cuenta anterior -> cuenta nueva).
+        origenes_migracion: Mapping<AccountId, AccountId>,
+        /// Cuenta nueva de cada migración aceptada (cuenta anterior -> cuenta nueva), para
+        /// resolver las órdenes cerradas que conservan la cuenta anterior.
+        cuentas_migradas: Mapping<AccountId, AccountId>,
+        /// Órdenes en las que participa cada cuenta ((cuenta, posición) -> id de orden).
+        ordenes_de_cuenta: Mapping<(AccountId, u32), u32>,
+        /// Cantidad de órdenes indexadas para cada cuenta.
+        total_ordenes_de_cuenta: Mapping<AccountId, u32>,
+        /// Suscripciones en las que participa cada cuenta ((cuenta, posición) -> id de suscripción).
+        suscripciones_de_cuenta: Mapping<(AccountId, u32), u32>,
+        /// Cantidad de suscripciones indexadas para cada cuenta.
+        total_suscripciones_de_cuenta: Mapping<AccountId, u32>,
+        /// Compradores bloqueados por cada vendedor ((vendedor, posición) -> comprador).
+        bloqueados_por_vendedor: Mapping<(AccountId, u32), AccountId>,
+        /// Cantidad de bloqueos indexados para cada vendedor.
+        total_bloqueados_por_vendedor: Mapping<AccountId, u32>,
+        /// Vendedores que bloquearon a cada comprador ((comprador, posición) -> vendedor).
+        bloqueadores_de_comprador: Mapping<(AccountId, u32), AccountId>,
+        /// Cantidad de bloqueos indexados para cada comprador.
+        total_bloqueadores_de_comprador: Mapping<AccountId, u32>,
+        /// Productos guardados como favoritos por cada usuario, en orden de agregado.
+        favoritos: Mapping<AccountId, Vec<u32>>,
+        /// Cantidad de usuarios que tienen cada producto en favoritos.
+        favoritos_por_producto: Mapping<u32, u32>,
+        /// La cuenta administradora del contrato (quien lo desplegó).
+        admin: AccountId,
+        /// Cantidad de bloques durante los cuales el autor de una calificación puede editarla.
+        ventana_edicion: BlockNumber,
+        /// Indica si hay un mensaje que mueve fondos en curso (protección contra reentrada).
+        bloqueado: bool,
+        /// Suma de los fondos en escrow de todas las órdenes.
+        total_fondos_retenidos: Balance,
+        /// Suma de los fondos pendientes de todas las suscripciones.
+        total_fondos_suscripciones: Balance,
+        /// Suma de los depósitos de reservas abiertas.
+        total_depositos_reservas: Balance,
+        /// Suma de las fianzas de todos los vendedores.
+        total_fianzas: Balance,
+        /// Suma de los saldos retirables de todos los usuarios.
+        total_saldos_retirables: Balance,
     }
 
     impl Default for Marketplace {
@@ -252,16 +1247,89 @@ mod marketplace {
         pub fn new() -> Self {
             Self {
                 roles: Mapping::default(),
+                perfiles: Mapping::default(),
+                nombres_visibles: Mapping::default(),
                 productos: Mapping::default(),
                 ordenes: Mapping::default(),
                 cancelaciones_pendientes: Mapping::default(),
                 reputaciones: Mapping::default(),
+                reputaciones_ponderadas: Mapping::default(),
                 calificaciones: Mapping::default(),
                 calificaciones_por_categoria: Mapping::default(),
+                compradores_bloqueados: Mapping::default(),
+                entregas_digitales: Mapping::default(),
+                historial_ordenes: Mapping::default(),
+                total_registros_orden: Mapping::default(),
+                mensajes_orden: Mapping::default(),
+                total_mensajes_orden: Mapping::default(),
+                mensajes_enviados: Mapping::default(),
+                arbitros_orden: Mapping::default(),
+                referidos_orden: Mapping::default(),
+                ordenes_con_referido: Mapping::default(),
+                total_ordenes_con_referido: 0,
+                estadisticas_referidos: Mapping::default(),
+                mejores_referidos: Vec::new(),
+                fianzas: Mapping::default(),
+                ordenes_compensadas: Mapping::default(),
+                requisito_fianza: None,
+                plazo_inactividad: None,
+                ordenes_preventa: Mapping::default(),
+                total_ordenes_preventa: Mapping::default(),
+                reembolsos_preventa: Mapping::default(),
+                reservas: Mapping::default(),
+                reservas_de_cuenta: Mapping::default(),
+                total_reservas_de_cuenta: Mapping::default(),
+                saldos_retirables: Mapping::default(),
                 fondos_retenidos: Mapping::default(),
+                categorias: Mapping::default(),
+                nombres_categoria: Mapping::default(),
+                next_categoria_id: 1,
+                subcategorias: Mapping::default(),
+                total_subcategorias: Mapping::default(),
+                productos_por_categoria: Mapping::default(),
+                total_productos_por_categoria: Mapping::default(),
+                productos_por_vendedor: Mapping::default(),
+                total_productos_por_vendedor: Mapping::default(),
+                bloques_busqueda: Mapping::default(),
+                cabeceras_busqueda: Mapping::default(),
+                total_cabeceras_busqueda: Mapping::default(),
+                vendedores_por_promedio: Mapping::default(),
+                total_vendedores_por_promedio: Mapping::default(),
+                posicion_por_promedio: Mapping::default(),
+                promedios_con_vendedores: Mapping::default(),
+                total_promedios_con_vendedores: 0,
+                pausas_vendedor: Mapping::default(),
                 next_prod_id: 1,
                 next_order_id: 1,
-                usuarios_registrados: Vec::new(),
+                suscripciones: Mapping::default(),
+                fondos_suscripciones: Mapping::default(),
+                next_suscripcion_id: 1,
+                usuarios: Mapping::default(),
+                total_usuarios: 0,
+                indice_usuarios: Mapping::default(),
+                ordenes_abiertas: Mapping::default(),
+                migraciones_pendientes: Mapping::default(),
+                migraciones_en_curso: Mapping::default(),
+                origenes_migracion: Mapping::default(),
+                cuentas_migradas: Mapping::default(),
+                ordenes_de_cuenta: Mapping::default(),
+                total_ordenes_de_cuenta: Mapping::default(),
+                suscripciones_de_cuenta: Mapping::default(),
+                total_suscripciones_de_cuenta: Mapping::default(),
+                bloqueados_por_vendedor: Mapping::default(),
+                total_bloqueados_por_vendedor: Mapping::default(),
+                bloqueadores_de_comprador: Mapping::default(),
+                total_bloqueadores_de_comprador: Mapping::default(),
+                favoritos: Mapping::default(),
+                favoritos_por_producto: Mapping::default(),
+                admin: Self::env().caller(),
+                ventana_edicion: VENTANA_EDICION_BLOQUES_DEFAULT,
+                bloqueado: false,
+                total_fondos_retenidos: 0,
+                total_fondos_suscripciones: 0,
+                total_depositos_reservas: 0,
+                total_fianzas: 0,
+                total_saldos_retirables: 0,
             }
         }
 
@@ -273,46 +1341,306 @@ mod marketplace {
         ///
         /// # Errores
         ///
-        /// Devuelve `Error::YaRegistrado` si el llamante ya tiene un rol asignado.
+        /// - `Error::YaRegistrado` si el llamante ya tiene un rol asignado.
+        /// - `Error::MigracionEnCurso` si el llamante es la cuenta anterior de una migración
+        ///   en curso.
+        /// - `Error::CuentaMigrada` si el llamante migró su identidad a otra cuenta.
         #[ink(message)]
         pub fn registrar(&mut self, rol: Rol) -> Result<(), Error> {
             let caller = self.env().caller();
             self._registrar(caller, rol)
         }
 
-        /// Obtiene el rol de un usuario específico.
+        /// Registra al llamante con un rol y completa su perfil público en la misma operación.
         ///
         /// # Argumentos
         ///
-        /// * `usuario` - La `AccountId` del usuario a consultar.
+        /// * `rol` - El `Rol` a asignar al llamante.
+        /// * `nombre_visible` - Nombre visible único (máximo 64 caracteres, vacío para omitirlo).
+        /// * `uri_metadata` - URI del avatar o metadatos (máximo 128 caracteres, puede estar vacía).
+        /// * `hash_contenido` - Hash opcional del contenido referenciado por la URI.
+        /// * `hash_contacto` - Hash opcional de los datos de contacto.
         ///
-        /// # Retorno
+        /// # Errores
         ///
-        /// Devuelve `Some(Rol)` si el usuario está registrado, o `None` en caso contrario.
+        /// - `Error::YaRegistrado` si el llamante ya tiene un rol asignado.
+        /// - `Error::ParamInvalido` si el nombre o la URI exceden la longitud máxima,
+        ///   o si el nombre tiene espacios al inicio o al final.
+        /// - `Error::NombreEnUso` si otro usuario ya usa el nombre visible.
         #[ink(message)]
-        pub fn obtener_rol(&self, usuario: AccountId) -> Option<Rol> {
-            self.roles.get(usuario)
-        }
-
+        pub fn registrar_con_perfil(
+            &mut self,
+            rol: Rol,
+            nombre_visible: String,
+            uri_metadata: String,
+            hash_contenido: Option<Hash>,
+            hash_contacto: Option<Hash>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._registrar_con_perfil(
+                caller,
+                rol,
+                nombre_visible,
+                uri_metadata,
+                hash_contenido,
+                hash_contacto,
+            )
+        }
+
+        /// Actualiza el perfil público del llamante.
+        ///
+        /// Si el nombre visible cambia, el nombre anterior queda libre para otros usuarios.
+        ///
+        /// # Argumentos
+        ///
+        /// * `nombre_visible` - Nombre visible único (máximo 64 caracteres, vacío para omitirlo).
+        /// * `uri_metadata` - URI del avatar o metadatos (máximo 128 caracteres, puede estar vacía).
+        /// * `hash_contenido` - Hash opcional del contenido referenciado por la URI.
+        /// * `hash_contacto` - Hash opcional de los datos de contacto.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::ParamInvalido` si el nombre o la URI exceden la longitud máxima,
+        ///   o si el nombre tiene espacios al inicio o al final.
+        /// - `Error::NombreEnUso` si otro usuario ya usa el nombre visible.
+        #[ink(message)]
+        pub fn actualizar_perfil(
+            &mut self,
+            nombre_visible: String,
+            uri_metadata: String,
+            hash_contenido: Option<Hash>,
+            hash_contacto: Option<Hash>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._actualizar_perfil(
+                caller,
+                nombre_visible,
+                uri_metadata,
+                hash_contenido,
+                hash_contacto,
+            )
+        }
+
+        /// Obtiene el perfil público de un usuario.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(Perfil)` si el usuario está registrado, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_perfil(&self, usuario: AccountId) -> Option<Perfil> {
+            self.perfiles.get(usuario)
+        }
+
+        /// Busca al usuario que usa un nombre visible (sin distinguir mayúsculas de minúsculas).
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(AccountId)` si el nombre está en uso, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn buscar_usuario_por_nombre(&self, nombre_visible: String) -> Option<AccountId> {
+            self.nombres_visibles
+                .get(Self::normalizar_nombre(&nombre_visible))
+        }
+
+        /// Da de baja al llamante del marketplace.
+        ///
+        /// Quita su rol, su perfil, su nombre visible, sus favoritos y su migración de
+        /// identidad pendiente, despublica todos sus productos y lo elimina de la lista de
+        /// usuarios registrados. Las órdenes históricas y la reputación se conservan para que
+        /// sus contrapartes sigan viéndolas. El usuario puede volver a registrarse más adelante.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::OrdenesAbiertas` si el llamante tiene órdenes en estado `Pendiente`
+        ///   o `Enviado` (y, por lo tanto, posibles cancelaciones pendientes).
+        /// - `Error::FianzaDepositada` si el llamante tiene una fianza sin retirar.
+        #[ink(message)]
+        pub fn darse_de_baja(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._darse_de_baja(caller)
+        }
+
+        /// Inicia la migración de la identidad del llamante hacia una nueva cuenta.
+        ///
+        /// La migración se completa cuando la nueva cuenta llama a `aceptar_migracion`,
+        /// de modo que ambas claves firman la operación. Iniciar una nueva migración
+        /// reemplaza a la anterior que estuviera pendiente.
+        ///
+        /// # Argumentos
+        ///
+        /// * `nueva_cuenta` - La `AccountId` que recibirá la identidad del llamante.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::CuentaDestinoInvalida` si la nueva cuenta es la misma, ya está
+        ///   registrada, ya tiene reputación o es la cuenta anterior de otra migración en curso.
+        /// - `Error::MigracionEnCurso` si el llamante todavía recibe una migración anterior.
+        #[ink(message)]
+        pub fn iniciar_migracion(&mut self, nueva_cuenta: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._iniciar_migracion(caller, nueva_cuenta)
+        }
+
+        /// Cancela la migración de identidad pendiente del llamante.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::MigracionInexistente` si el llamante no tiene una migración pendiente.
+        #[ink(message)]
+        pub fn cancelar_migracion(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._cancelar_migracion(caller)
+        }
+
+        /// Acepta una migración de identidad iniciada por `cuenta_anterior` hacia el llamante.
+        ///
+        /// Transfiere a la nueva cuenta el rol, el perfil, la reputación, la fianza, el saldo
+        /// retirable, las estadísticas de referido y los favoritos. Los bloqueos (tanto los
+        /// que puso como los que recibió la cuenta anterior), los productos publicados, las
+        /// reservas y la participación en las órdenes abiertas (`Pendiente` o `Enviado`, con
+        /// sus contadores de mensajes) y en las suscripciones activas se trasladan por lotes de
+        /// hasta `MAX_MIGRACION_POR_LLAMADA`: si quedan pendientes, la nueva cuenta debe
+        /// completarlos con `continuar_migracion`.
+        /// Las órdenes ya cerradas conservan la cuenta anterior como registro histórico: las
+        /// calificaciones, los créditos y las compensaciones de esas órdenes se aplican a la
+        /// nueva cuenta, que también puede calificarlas en lugar de la anterior. La cuenta
+        /// anterior ya no puede volver a registrarse.
+        ///
+        /// # Argumentos
+        ///
+        /// * `cuenta_anterior` - La `AccountId` que inició la migración.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::MigracionInexistente` si `cuenta_anterior` no inició una migración hacia
+        ///   el llamante.
+        /// - `Error::SinRegistro` si la cuenta anterior ya no está registrada.
+        /// - `Error::CuentaDestinoInvalida` si el llamante se registró o recibió reputación
+        ///   después de iniciada la migración.
+        #[ink(message)]
+        pub fn aceptar_migracion(&mut self, cuenta_anterior: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._aceptar_migracion(caller, cuenta_anterior)
+        }
+
+        /// Traslada al llamante el siguiente lote de referencias de la migración que aceptó.
+        ///
+        /// Mientras la migración está en curso, la nueva cuenta no puede darse de baja,
+        /// cambiar de rol ni iniciar otra migración, y la cuenta anterior no puede volver
+        /// a registrarse.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::MigracionInexistente` si el llamante no tiene una migración en curso.
+        ///
+        /// # Retorno
+        ///
+        /// `true` si la migración terminó.
+        #[ink(message)]
+        pub fn continuar_migracion(&mut self) -> Result<bool, Error> {
+            let caller = self.env().caller();
+            self._continuar_migracion(caller)
+        }
+
+        /// Obtiene el progreso de la migración en curso hacia una cuenta, si existe.
+        #[ink(message)]
+        pub fn obtener_migracion_en_curso(&self, cuenta: AccountId) -> Option<MigracionEnCurso> {
+            self.migraciones_en_curso.get(cuenta)
+        }
+
+        /// Obtiene la cuenta destino de la migración pendiente de un usuario, si existe.
+        #[ink(message)]
+        pub fn obtener_migracion_pendiente(&self, usuario: AccountId) -> Option<AccountId> {
+            self.migraciones_pendientes.get(usuario)
+        }
+
+        /// Obtiene la cantidad de órdenes abiertas (`Pendiente` o `Enviado`) de un usuario.
+        ///
+        /// # Retorno
+        ///
+        /// Tupla `(como_comprador, como_vendedor)`.
+        #[ink(message)]
+        pub fn obtener_ordenes_abiertas(&self, usuario: AccountId) -> (u32, u32) {
+            self.ordenes_abiertas.get(usuario).unwrap_or((0, 0))
+        }
+
+        /// Obtiene el rol de un usuario específico.
+        ///
+        /// # Argumentos
+        ///
+        /// * `usuario` - La `AccountId` del usuario a consultar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(Rol)` si el usuario está registrado, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_rol(&self, usuario: AccountId) -> Option<Rol> {
+            self.roles.get(usuario)
+        }
+
         /// Modifica el rol de un usuario ya registrado.
         ///
         /// El usuario debe estar previamente registrado para poder modificar su rol.
         /// Esta función permite que un usuario cambie de `Comprador` a `Vendedor`,
         /// de `Vendedor` a `Comprador`, o que cualquiera de ellos cambie a `Ambos`.
         ///
+        /// Al dejar de ser vendedor, todos sus productos activos se pausan
+        /// automáticamente para que no puedan seguir comprándose.
+        ///
         /// # Argumentos
         ///
         /// * `nuevo_rol` - El nuevo `Rol` a asignar al llamante.
         ///
         /// # Errores
         ///
-        /// Devuelve `Error::SinRegistro` si el llamante no está registrado previamente.
+        /// - `Error::SinRegistro` si el llamante no está registrado previamente.
+        /// - `Error::VentasPendientes` si el llamante deja de ser vendedor teniendo
+        ///   órdenes de venta en estado `Pendiente` o `Enviado`.
         #[ink(message)]
         pub fn modificar_rol(&mut self, nuevo_rol: Rol) -> Result<(), Error> {
             let caller = self.env().caller();
             self._modificar_rol(caller, nuevo_rol)
         }
 
+        /// Pausa la publicación de un producto para que no pueda comprarse.
+        ///
+        /// # Argumentos
+        ///
+        /// * `id_prod` - El ID del producto a pausar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto.
+        #[ink(message)]
+        pub fn pausar_producto(&mut self, id_prod: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._cambiar_estado_producto(caller, id_prod, false)
+        }
+
+        /// Reactiva la publicación de un producto pausado.
+        ///
+        /// El llamante debe seguir registrado como `Vendedor` o `Ambos`.
+        ///
+        /// # Argumentos
+        ///
+        /// * `id_prod` - El ID del producto a reactivar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto o ya no es vendedor.
+        /// - `Error::LanzamientoCancelado` si el producto es una preventa cancelada.
+        #[ink(message)]
+        pub fn reactivar_producto(&mut self, id_prod: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._reactivar_producto(caller, id_prod)
+        }
+
         /// Obtiene todos los productos disponibles en el marketplace de una sola vez.
         ///
         /// # Complejidad
@@ -371,13 +1699,17 @@ mod marketplace {
         /// * `descripcion` - Descripción del producto (máximo 256 caracteres).
         /// * `precio` - El precio del producto (debe ser mayor que 0).
         /// * `stock` - La cantidad de unidades disponibles (debe ser mayor que 0).
-        /// * `categoria` - Categoría del producto (máximo 32 caracteres).
+        /// * `categoria` - Nombre de la categoría del producto (máximo 32 caracteres). Se busca
+        ///   en el registro sin distinguir mayúsculas, tildes ni espacios al inicio o al final;
+        ///   si no está registrada, se crea como categoría raíz.
         ///
         /// # Errores
         ///
         /// - `Error::SinPermiso` si el llamante no es un vendedor.
         /// - `Error::ParamInvalido` si el precio, stock, nombre, descripción o categoría no son válidos.
-        /// - `Error::IdOverflow` si se ha alcanzado el número máximo de productos.
+        /// - `Error::FianzaInsuficiente` si el precio alcanza el umbral configurado y el
+        ///   vendedor no tiene depositada la fianza mínima.
+        /// - `Error::IdOverflow` si se ha alcanzado el número máximo de productos o de categorías.
         ///
         /// # Retorno
         ///
@@ -395,791 +1727,4939 @@ mod marketplace {
             self._publicar(vendedor, nombre, descripcion, precio, stock, categoria)
         }
 
-        /// Obtiene la información de un producto por su ID.
+        /// Publica un nuevo producto en una categoría del registro, indicada por su ID.
         ///
         /// # Argumentos
         ///
-        /// * `id` - El ID del producto a consultar.
+        /// Los mismos que `publicar`, salvo la categoría:
         ///
-        /// # Retorno
+        /// * `id_categoria` - El ID de la categoría del producto en el registro de categorías.
         ///
-        /// Devuelve `Some(Producto)` si el producto existe, o `None` en caso contrario.
+        /// # Errores
+        ///
+        /// Los mismos que `publicar`, y `Error::CategoriaInexistente` si la categoría no
+        /// está registrada.
         #[ink(message)]
-        pub fn obtener_producto(&self, id: u32) -> Option<Producto> {
-            self.productos.get(id)
+        pub fn publicar_en_categoria(
+            &mut self,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            stock: u32,
+            id_categoria: u32,
+        ) -> Result<u32, Error> {
+            let vendedor = self.env().caller();
+            self._publicar_en_categoria(vendedor, nombre, descripcion, precio, stock, id_categoria)
         }
 
-        /// Permite a un comprador crear una orden para un producto.
+        /// Publica un producto digital, cuyo contenido cifrado está en `referencia_cifrada`.
         ///
-        /// El llamante debe estar registrado como `Comprador` o `Ambos`.
-        /// El comprador debe enviar el monto exacto (precio × cantidad) junto con la transacción.
-        /// Los fondos quedan retenidos en el contrato (escrow) hasta que la orden se complete
-        /// o se cancele por acuerdo mutuo.
+        /// Las órdenes de productos digitales no usan `marcar_enviado`: el vendedor entrega
+        /// a cada comprador la clave del contenido con `entregar_clave`.
         ///
-        /// ## Sistema de Pagos (Escrow)
+        /// # Argumentos
         ///
-        /// - Los fondos enviados se validan contra el precio del producto.
-        /// - Si el monto es correcto, se retienen en el contrato.
-        /// - Al marcar como `Recibido`, los fondos se transfieren al vendedor.
-        /// - Al `Cancelar`, los fondos se devuelven al comprador.
+        /// Los mismos que `publicar_en_categoria`, más:
+        ///
+        /// * `referencia_cifrada` - Referencia al contenido cifrado (máximo 128 caracteres).
+        ///
+        /// # Errores
+        ///
+        /// Los mismos que `publicar_en_categoria`, y `Error::ParamInvalido` si la referencia
+        /// está vacía o es demasiado larga.
+        #[ink(message)]
+        pub fn publicar_digital(
+            &mut self,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            stock: u32,
+            id_categoria: u32,
+            referencia_cifrada: String,
+        ) -> Result<u32, Error> {
+            let vendedor = self.env().caller();
+            let producto =
+                self.nuevo_producto(vendedor, nombre, descripcion, precio, stock, id_categoria)?;
+            self._publicar_digital(producto, referencia_cifrada)
+        }
+
+        /// Publica un producto en preventa, que puede comprarse antes de su lanzamiento.
+        ///
+        /// Los compradores pagan al reservar y los fondos quedan en escrow. El vendedor
+        /// no puede marcar las órdenes como enviadas hasta la fecha de lanzamiento.
         ///
         /// # Argumentos
         ///
-        /// * `id_prod` - El ID del producto a comprar.
-        /// * `cant` - La cantidad de unidades a comprar (debe ser mayor que 0).
+        /// Los mismos que `publicar_en_categoria`, salvo el stock, más:
+        ///
+        /// * `fecha_lanzamiento` - Timestamp (en milisegundos) del lanzamiento; debe ser futuro.
+        /// * `cupo_reservas` - Cantidad máxima de unidades que pueden reservarse (se usa como stock).
+        ///
+        /// # Errores
+        ///
+        /// Los mismos que `publicar_en_categoria`, y `Error::ParamInvalido` si la fecha de
+        /// lanzamiento no es futura o el cupo es 0.
+        #[ink(message)]
+        pub fn publicar_preventa(
+            &mut self,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            id_categoria: u32,
+            fecha_lanzamiento: Timestamp,
+            cupo_reservas: u32,
+        ) -> Result<u32, Error> {
+            let vendedor = self.env().caller();
+            let producto = self.nuevo_producto(
+                vendedor,
+                nombre,
+                descripcion,
+                precio,
+                cupo_reservas,
+                id_categoria,
+            )?;
+            self._publicar_preventa(producto, fecha_lanzamiento)
+        }
+
+        /// Cancela el lanzamiento de un producto en preventa.
+        ///
+        /// El producto queda despublicado y sus órdenes pendientes se cancelan; el monto
+        /// pagado por cada comprador se acredita a su saldo retirable. Se procesan hasta
+        /// `MAX_REEMBOLSOS_POR_LLAMADA` órdenes; las restantes se reembolsan con
+        /// `reembolsar_preventas`.
         ///
         /// # Errores
         ///
-        /// - `Error::SinPermiso` si el llamante no es un comprador.
-        /// - `Error::ParamInvalido` si la cantidad es 0.
         /// - `Error::ProdInexistente` si el producto no existe.
-        /// - `Error::StockInsuf` si no hay suficiente stock para la cantidad solicitada.
-        /// - `Error::AutoCompraProhibida` si el vendedor intenta comprar su propio producto.
-        /// - `Error::PagoInsuficiente` si el monto enviado es menor al requerido.
-        /// - `Error::PagoExcesivo` si el monto enviado es mayor al requerido.
-        /// - `Error::IdOverflow` si se ha alcanzado el número máximo de órdenes.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto.
+        /// - `Error::SinLanzamiento` si el producto no se publicó en preventa.
+        /// - `Error::LanzamientoCancelado` si el lanzamiento ya fue cancelado.
+        /// - `Error::PlazoVencido` si la fecha de lanzamiento ya pasó.
         ///
         /// # Retorno
         ///
-        /// Devuelve el `id` de la nueva orden creada.
-        #[ink(message, payable)]
-        pub fn comprar(&mut self, id_prod: u32, cant: u32) -> Result<u32, Error> {
-            let comprador = self.env().caller();
-            let monto_enviado = self.env().transferred_value();
-            self._comprar(comprador, id_prod, cant, monto_enviado)
+        /// Devuelve la cantidad de órdenes canceladas en esta llamada.
+        #[ink(message)]
+        pub fn cancelar_lanzamiento(&mut self, id_prod: u32) -> Result<u32, Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._cancelar_lanzamiento(caller, id_prod))
         }
 
-        /// Marca una orden como enviada.
+        /// Continúa el reembolso de las órdenes de una preventa cancelada.
         ///
-        /// Solo el vendedor de la orden puede llamar a esta función.
-        /// La orden debe estar en estado `Pendiente`.
+        /// Cualquier cuenta puede llamarla. Procesa hasta `MAX_REEMBOLSOS_POR_LLAMADA`
+        /// órdenes por llamada, en el orden en que se crearon.
         ///
-        /// # Argumentos
+        /// # Errores
         ///
-        /// * `oid` - El ID de la orden a marcar como enviada.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinLanzamiento` si el producto no se publicó en preventa.
+        /// - `Error::EstadoInvalido` si el lanzamiento no fue cancelado.
         ///
-        /// # Errores
+        /// # Retorno
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
-        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente`.
+        /// Devuelve la cantidad de órdenes canceladas en esta llamada.
         #[ink(message)]
-        pub fn marcar_enviado(&mut self, oid: u32) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self._marcar_enviado(caller, oid)
+        pub fn reembolsar_preventas(&mut self, id_prod: u32) -> Result<u32, Error> {
+            self.sin_reentrada(|mp| mp._reembolsar_preventas(id_prod))
         }
 
-        /// Marca una orden como recibida.
+        /// Obtiene la cantidad de órdenes de una preventa que aún no se procesaron para
+        /// reembolso.
+        #[ink(message)]
+        pub fn obtener_preventas_sin_reembolsar(&self, id_prod: u32) -> u32 {
+            self.total_ordenes_preventa
+                .get(id_prod)
+                .unwrap_or(0)
+                .saturating_sub(self.reembolsos_preventa.get(id_prod).unwrap_or(0))
+        }
+
+        /// Transfiere al llamante todo su saldo retirable.
         ///
-        /// Solo el comprador de la orden puede llamar a esta función.
-        /// La orden debe estar en estado `Enviado`.
+        /// Los pagos del marketplace (ventas completadas, reembolsos, comisiones de
+        /// referidos, etc.) no se transfieren en el momento sino que se acreditan a un
+        /// saldo interno, para que ninguna transición de estado dependa de que el
+        /// destinatario pueda recibir fondos.
         ///
-        /// ## Liberación de Fondos
+        /// # Errores
         ///
-        /// Al marcar como recibida, los fondos retenidos en escrow se transfieren
-        /// automáticamente al vendedor. Esta operación es atómica: si la transferencia
-        /// falla, la orden no cambia de estado.
+        /// - `Error::SinSaldoRetirable` si el llamante no tiene saldo.
+        /// - `Error::TransferenciaFallida` si la transferencia falla.
         ///
-        /// # Argumentos
+        /// # Retorno
         ///
-        /// * `oid` - El ID de la orden a marcar como recibida.
+        /// Devuelve el monto retirado.
+        #[ink(message)]
+        pub fn retirar(&mut self) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._retirar(caller))
+        }
+
+        /// Transfiere al llamante una parte de su saldo retirable.
         ///
         /// # Errores
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el comprador de la orden.
-        /// - `Error::EstadoInvalido` si la orden no está en estado `Enviado`.
-        /// - `Error::TransferenciaFallida` si no se pueden transferir los fondos al vendedor.
+        /// - `Error::ParamInvalido` si el monto es 0.
+        /// - `Error::SaldoInsuficiente` si el monto supera el saldo retirable.
+        /// - `Error::TransferenciaFallida` si la transferencia falla.
         #[ink(message)]
-        pub fn marcar_recibido(&mut self, oid: u32) -> Result<(), Error> {
+        pub fn retirar_monto(&mut self, monto: Balance) -> Result<(), Error> {
             let caller = self.env().caller();
-            self._marcar_recibido(caller, oid)
+            self.sin_reentrada(|mp| mp._retirar_monto(caller, monto))
         }
 
-        /// Obtiene la información de una orden por su ID.
+        /// Obtiene el saldo retirable de un usuario.
+        #[ink(message)]
+        pub fn obtener_saldo_retirable(&self, usuario: AccountId) -> Balance {
+            self.saldos_retirables.get(usuario).unwrap_or(0)
+        }
+
+        /// Busca productos activos que cumplan un filtro, ordenados y paginados.
         ///
-        /// Solo el comprador o el vendedor de la orden pueden acceder a esta información.
+        /// Los productos se recorren en un índice ordenado por precio o por `creado_en`: el
+        /// del vendedor del filtro, el de su categoría (que incluye a sus subcategorías, que se
+        /// descartan si `incluir_subcategorias` es `false`) o el de todos los productos. Con
+        /// `OrdenBusqueda::MejorCalificados` y sin vendedor, se recorren los vendedores de
+        /// mayor a menor promedio y los productos de cada uno en el orden de su índice.
+        /// Los productos pausados, con el lanzamiento cancelado o despublicados por
+        /// inactividad salen de los índices, y vuelven a ellos al reactivarse.
+        ///
+        /// Cada llamada recorre a lo sumo `MAX_RECORRIDOS_BUSQUEDA` productos. Si se detiene
+        /// antes del final, `PaginaBusqueda::siguiente` indica desde dónde continuar, aunque la
+        /// página tenga menos de `cantidad` resultados.
         ///
         /// # Argumentos
         ///
-        /// * `id` - El ID de la orden a consultar.
+        /// * `filtro` - Los criterios que deben cumplir los productos.
+        /// * `orden` - El criterio de ordenamiento de los resultados.
+        /// * `despues_de` - El cursor de la página anterior, o `None` para la primera.
+        /// * `cantidad` - Cantidad máxima de resultados (se limita a `MAX_RESULTADOS_BUSQUEDA`).
         ///
         /// # Errores
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el comprador ni el vendedor de la orden.
+        /// - `Error::ParamInvalido` si `precio_min` es mayor que `precio_max`, si `cantidad`
+        ///   es 0 o si el cursor corresponde a otro orden.
+        /// - `Error::CategoriaInexistente` si la categoría del filtro no está registrada.
         ///
         /// # Retorno
         ///
-        /// Devuelve la `Orden` si existe y el llamante tiene permisos.
+        /// Una `PaginaBusqueda`. A igual precio o fecha, los productos se ordenan por ID en el
+        /// mismo sentido.
         #[ink(message)]
-        pub fn obtener_orden(&self, id: u32) -> Result<Orden, Error> {
-            let caller = self.env().caller();
-            let orden = self.ordenes.get(id).ok_or(Error::OrdenInexistente)?;
-            self.ensure(
-                orden.comprador == caller || orden.vendedor == caller,
-                Error::SinPermiso,
-            )?;
-            Ok(orden)
+        pub fn buscar_productos(
+            &self,
+            filtro: FiltroBusqueda,
+            orden: OrdenBusqueda,
+            despues_de: Option<CursorBusqueda>,
+            cantidad: u32,
+        ) -> Result<PaginaBusqueda, Error> {
+            self._buscar_productos(filtro, orden, despues_de, cantidad)
         }
 
-        /// Lista todos los productos publicados por un vendedor específico.
+        /// Configura la reputación mínima que debe tener un comprador para comprar un producto.
+        ///
+        /// Solo el vendedor que publicó el producto puede configurar el requisito.
+        /// Pasar `None` elimina el requisito.
         ///
         /// # Argumentos
         ///
-        /// * `vendedor` - La `AccountId` del vendedor cuyos productos se desean listar.
+        /// * `id_prod` - El ID del producto.
+        /// * `requisito` - El `RequisitoReputacion` a exigir, o `None` para no exigir ninguno.
         ///
-        /// # Retorno
+        /// # Errores
         ///
-        /// Devuelve un `Vec<Producto>` con todos los productos del vendedor.
-        /// Si el vendedor no tiene productos, devuelve un vector vacío.
-        ///
-        /// # Nota
-        ///
-        /// Esta función itera sobre todos los IDs de productos, por lo que su costo
-        /// aumenta linealmente con el número total de productos en el marketplace.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto.
+        /// - `Error::ParamInvalido` si el promedio mínimo excede las 5 estrellas (500).
         #[ink(message)]
-        pub fn listar_productos_de_vendedor(&self, vendedor: AccountId) -> Vec<Producto> {
-            self._listar_productos_de_vendedor(vendedor)
+        pub fn configurar_requisito_reputacion(
+            &mut self,
+            id_prod: u32,
+            requisito: Option<RequisitoReputacion>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._configurar_requisito_reputacion(caller, id_prod, requisito)
         }
 
-        /// Lista todas las órdenes realizadas por el usuario que llama esta función.
-        ///
-        /// Por motivos de seguridad y privacidad, un comprador solo puede ver sus propias órdenes.
-        ///
-        /// # Retorno
+        /// Configura el porcentaje de comisión que recibe un referido por cada venta del
+        /// producto que él haya referido.
         ///
-        /// Devuelve un `Vec<Orden>` con todas las órdenes del caller.
-        /// Si el caller no tiene órdenes, devuelve un vector vacío.
+        /// El porcentaje se fija en cada orden al momento de la compra, por lo que los
+        /// cambios solo afectan a compras futuras.
         ///
-        /// # Nota
+        /// # Errores
         ///
-        /// Esta función itera sobre todos los IDs de órdenes, por lo que su costo
-        /// aumenta linealmente con el número total de órdenes en el marketplace.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto.
+        /// - `Error::ParamInvalido` si el porcentaje supera `MAX_PORCENTAJE_REFERIDO`.
         #[ink(message)]
-        pub fn listar_ordenes_de_comprador(&self, comprador: AccountId) -> Vec<Orden> {
-            self._listar_ordenes_de_comprador(comprador)
+        pub fn configurar_referidos(&mut self, id_prod: u32, porcentaje: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._configurar_referidos(caller, id_prod, porcentaje)
         }
 
-        /// Solicita la cancelación de una orden.
-        ///
-        /// El llamante debe ser el comprador o el vendedor de la orden.
-        /// La orden debe estar en estado `Pendiente` o `Enviado`.
-        ///
-        /// Se registra una solicitud que debe ser aceptada o rechazada por la otra
-        /// parte. Solo puede haber una solicitud pendiente por orden.
+        /// Obtiene la información de un producto por su ID.
         ///
         /// # Argumentos
         ///
-        /// * `oid` - El ID de la orden a cancelar.
+        /// * `id` - El ID del producto a consultar.
         ///
-        /// # Errores
+        /// # Retorno
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el comprador ni el vendedor.
-        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente` o `Enviado`.
-        /// - `Error::CancelacionYaPendiente` si ya existe una solicitud de cancelación.
+        /// Devuelve `Some(Producto)` si el producto existe, o `None` en caso contrario.
         #[ink(message)]
-        pub fn solicitar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self._solicitar_cancelacion(caller, oid)
+        pub fn obtener_producto(&self, id: u32) -> Option<Producto> {
+            self.productos.get(id).map(|p| self.con_estado_vigente(p))
         }
 
-        /// Acepta una solicitud de cancelación de una orden.
+        /// Permite a un comprador crear una orden para un producto.
         ///
-        /// El llamante debe ser el otro participante (comprador si vendedor solicita, o viceversa).
-        /// Al aceptar, la orden pasa a estado `Cancelada` y el stock se restaura.
+        /// El llamante debe estar registrado como `Comprador` o `Ambos`.
+        /// El comprador debe enviar el monto exacto (precio × cantidad) junto con la transacción.
+        /// Los fondos quedan retenidos en el contrato (escrow) hasta que la orden se complete
+        /// o se cancele por acuerdo mutuo.
         ///
-        /// ## Devolución de Fondos
+        /// ## Sistema de Pagos (Escrow)
         ///
-        /// Al cancelar, los fondos retenidos en escrow se devuelven automáticamente
-        /// al comprador. Esta operación es atómica: si la transferencia falla,
-        /// la orden no cambia de estado.
+        /// - Los fondos enviados se validan contra el precio del producto.
+        /// - Si el monto es correcto, se retienen en el contrato.
+        /// - Al marcar como `Recibido`, los fondos se acreditan al saldo retirable del vendedor.
+        /// - Al `Cancelar`, los fondos se acreditan al saldo retirable del comprador.
+        ///
+        /// Si el comprador tiene una reserva del producto, las unidades reservadas se suman
+        /// al stock disponible para esta compra y la reserva se cierra: si está vigente el
+        /// depósito se devuelve completo, y si venció se devuelve descontando la comisión.
         ///
         /// # Argumentos
         ///
-        /// * `oid` - El ID de la orden cuya cancelación se desea aceptar.
+        /// * `id_prod` - El ID del producto a comprar.
+        /// * `cant` - La cantidad de unidades a comprar (debe ser mayor que 0).
         ///
         /// # Errores
         ///
-        /// - `Error::CancelacionInexistente` si no existe solicitud de cancelación.
-        /// - `Error::SinPermiso` si el llamante no es el otro participante.
-        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es un comprador.
+        /// - `Error::ParamInvalido` si la cantidad es 0.
         /// - `Error::ProdInexistente` si el producto no existe.
-        /// - `Error::TransferenciaFallida` si no se pueden devolver los fondos al comprador.
-        #[ink(message)]
-        pub fn aceptar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self._aceptar_cancelacion(caller, oid)
+        /// - `Error::ProductoInactivo` si el producto fue despublicado.
+        /// - `Error::StockInsuf` si no hay suficiente stock para la cantidad solicitada.
+        /// - `Error::AutoCompraProhibida` si el vendedor intenta comprar su propio producto.
+        /// - `Error::CompradorBloqueado` si el vendedor bloqueó al comprador.
+        /// - `Error::ReputacionInsuficiente` si el comprador no cumple la reputación mínima del producto.
+        /// - `Error::PagoInsuficiente` si el monto enviado es menor al requerido.
+        /// - `Error::PagoExcesivo` si el monto enviado es mayor al requerido.
+        /// - `Error::IdOverflow` si se ha alcanzado el número máximo de órdenes.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el `id` de la nueva orden creada.
+        #[ink(message, payable)]
+        pub fn comprar(&mut self, id_prod: u32, cant: u32) -> Result<u32, Error> {
+            let comprador = self.env().caller();
+            let monto_enviado = self.env().transferred_value();
+            self.sin_reentrada(|mp| mp._comprar(comprador, id_prod, cant, monto_enviado))
         }
 
-        /// Rechaza una solicitud de cancelación de una orden.
+        /// Crea una orden igual que `comprar`, registrando la cuenta que refirió la compra.
         ///
-        /// El llamante debe ser el otro participante (comprador si vendedor solicita, o viceversa).
-        /// Solo elimina la solicitud de cancelación, la orden mantiene su estado anterior.
+        /// Al completarse la orden, el referido recibe el porcentaje del escrow que el
+        /// producto ofrecía al momento de la compra, y el vendedor recibe el resto.
         ///
-        /// # Argumentos
+        /// # Errores
         ///
-        /// * `oid` - El ID de la orden cuya cancelación se desea rechazar.
+        /// - Los mismos que `comprar`.
+        /// - `Error::ParamInvalido` si el referido es el comprador o el vendedor del producto.
+        #[ink(message, payable)]
+        pub fn comprar_con_referido(
+            &mut self,
+            id_prod: u32,
+            cant: u32,
+            referido: AccountId,
+        ) -> Result<u32, Error> {
+            let comprador = self.env().caller();
+            let monto_enviado = self.env().transferred_value();
+            self.sin_reentrada(|mp| {
+                mp._comprar_con_referido(comprador, id_prod, cant, monto_enviado, referido)
+            })
+        }
+
+        /// Crea una orden igual que `comprar`, para que la reciba otra cuenta (compra para regalo).
+        ///
+        /// El destinatario confirma la recepción con `marcar_recibido` y califica al
+        /// vendedor; el comprador sigue siendo quien paga, quien puede solicitar la
+        /// cancelación y quien recibe los reembolsos.
         ///
         /// # Errores
         ///
-        /// - `Error::CancelacionInexistente` si no existe solicitud de cancelación.
-        /// - `Error::SinPermiso` si el llamante no es el otro participante.
+        /// - Los mismos que `comprar`.
+        /// - `Error::ParamInvalido` si el destinatario es el comprador o el vendedor del producto.
+        #[ink(message, payable)]
+        pub fn comprar_para(
+            &mut self,
+            id_prod: u32,
+            cant: u32,
+            destinatario: AccountId,
+        ) -> Result<u32, Error> {
+            let comprador = self.env().caller();
+            let monto_enviado = self.env().transferred_value();
+            self.sin_reentrada(|mp| {
+                mp._comprar_para(comprador, id_prod, cant, monto_enviado, destinatario)
+            })
+        }
+
+        /// Obtiene el referido de una orden, si la compra se hizo con referido.
         #[ink(message)]
-        pub fn rechazar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
-            let caller = self.env().caller();
-            self._rechazar_cancelacion(caller, oid)
+        pub fn obtener_referido_orden(&self, oid: u32) -> Option<ReferidoOrden> {
+            self.referidos_orden.get(oid)
         }
 
-        /// Obtiene la reputación de un usuario específico.
+        /// Obtiene las comisiones acumuladas cobradas por un referido.
+        #[ink(message)]
+        pub fn obtener_ganancias_referido(&self, referido: AccountId) -> Balance {
+            self.obtener_estadisticas_referido(referido).ganancias
+        }
+
+        /// Obtiene el rendimiento acumulado de una cuenta como referido.
+        #[ink(message)]
+        pub fn obtener_estadisticas_referido(&self, referido: AccountId) -> EstadisticasReferido {
+            self.estadisticas_referidos
+                .get(referido)
+                .unwrap_or_default()
+        }
+
+        /// Lista una página de las órdenes compradas con referido, en orden de compra.
         ///
         /// # Argumentos
         ///
-        /// * `usuario` - La `AccountId` del usuario cuya reputación se desea consultar.
+        /// * `desde` - Posición (desde 0) de la primera orden con referido a devolver.
+        /// * `cantidad` - Cantidad máxima de órdenes (se limita a `MAX_REFERIDOS_POR_PAGINA`).
         ///
         /// # Retorno
         ///
-        /// Devuelve `Some(ReputacionUsuario)` si el usuario tiene reputación registrada, o `None` en caso contrario.
+        /// Devuelve una lista de tuplas (orden_id, referido).
         #[ink(message)]
-        pub fn obtener_reputacion(&self, usuario: AccountId) -> Option<ReputacionUsuario> {
-            self.reputaciones.get(usuario)
+        pub fn listar_referidos_orden(
+            &self,
+            desde: u32,
+            cantidad: u32,
+        ) -> Vec<(u32, ReferidoOrden)> {
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_REFERIDOS_POR_PAGINA))
+                .min(self.total_ordenes_con_referido);
+            (desde..hasta)
+                .filter_map(|i| self.ordenes_con_referido.get(i))
+                .filter_map(|oid| {
+                    self.referidos_orden
+                        .get(oid)
+                        .map(|referido| (oid, referido))
+                })
+                .collect()
         }
 
-        /// Obtiene la suma y cantidad de calificaciones de vendedores para una categoría.
-        /// Retorna `Some((suma, cantidad))` o `None` si aún no hay calificaciones registradas.
+        /// Lista los referidos con más comisiones cobradas, de mayor a menor.
+        ///
+        /// # Retorno
+        ///
+        /// Hasta `MAX_MEJORES_REFERIDOS` tuplas (referido, estadísticas).
         #[ink(message)]
-        pub fn obtener_calificacion_categoria(&self, categoria: String) -> Option<(u32, u32)> {
-            self.calificaciones_por_categoria.get(categoria)
+        pub fn listar_mejores_referidos(&self) -> Vec<(AccountId, EstadisticasReferido)> {
+            self.mejores_referidos
+                .iter()
+                .map(|&cuenta| (cuenta, self.obtener_estadisticas_referido(cuenta)))
+                .collect()
         }
 
-        /// Permite al comprador calificar al vendedor de una orden.
+        /// Reserva stock de un producto durante `DURACION_RESERVA_BLOQUES` bloques.
         ///
-        /// Solo el comprador de la orden puede calificar al vendedor.
-        /// La orden debe estar en estado `Recibido`.
-        /// Solo se puede calificar una vez por orden.
-        /// La calificación debe estar entre 1 y 5.
+        /// El comprador debe enviar un depósito igual al `DEPOSITO_RESERVA_PORCENTAJE`% del
+        /// precio de las unidades reservadas (como mínimo 1). Mientras la reserva está vigente,
+        /// el comprador puede completarla con `comprar` y recupera el depósito completo. Si
+        /// vence, cualquiera puede liberarla con `liberar_reserva`.
         ///
-        /// # Argumentos
+        /// # Errores
         ///
-        /// * `oid` - El ID de la orden a calificar.
-        /// * `puntos` - La calificación (1-5).
+        /// - Los mismos que `comprar` para el rol, el producto y el stock.
+        /// - `Error::ReservaExistente` si el comprador ya tiene una reserva vigente del producto.
+        /// - `Error::PagoInsuficiente` / `Error::PagoExcesivo` si el depósito no es exacto.
+        #[ink(message, payable)]
+        pub fn reservar(&mut self, id_prod: u32, cant: u32) -> Result<(), Error> {
+            let comprador = self.env().caller();
+            let monto_enviado = self.env().transferred_value();
+            self.sin_reentrada(|mp| mp._reservar(comprador, id_prod, cant, monto_enviado))
+        }
+
+        /// Libera una reserva vencida: devuelve el stock al producto y acredita el depósito
+        /// al comprador, descontando la comisión anti-spam que se acredita al vendedor.
+        ///
+        /// Cualquier cuenta puede llamarla una vez vencida la reserva.
         ///
         /// # Errores
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el comprador de la orden.
-        /// - `Error::OrdenNoRecibida` si la orden no está en estado Recibido.
-        /// - `Error::YaCalificado` si ya se ha calificado en esta orden.
-        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        /// - `Error::ReservaInexistente` si no existe la reserva.
+        /// - `Error::PlazoNoVencido` si la reserva aún está vigente.
         #[ink(message)]
-        pub fn calificar_vendedor(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+        pub fn liberar_reserva(&mut self, comprador: AccountId, id_prod: u32) -> Result<(), Error> {
+            self.sin_reentrada(|mp| mp._liberar_reserva(comprador, id_prod))
+        }
+
+        /// Obtiene la reserva de un comprador para un producto, si existe.
+        #[ink(message)]
+        pub fn obtener_reserva(&self, comprador: AccountId, id_prod: u32) -> Option<Reserva> {
+            self.reservas.get((comprador, id_prod))
+        }
+
+        /// Configura el plan de suscripción que ofrece un producto.
+        ///
+        /// Solo el vendedor del producto puede configurarlo. Pasar `None` deja de ofrecer
+        /// suscripciones; las suscripciones existentes conservan su plan original.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor del producto.
+        /// - `Error::ParamInvalido` si el período o el precio son 0.
+        #[ink(message)]
+        pub fn configurar_suscripcion(
+            &mut self,
+            id_prod: u32,
+            plan: Option<PlanSuscripcion>,
+        ) -> Result<(), Error> {
             let caller = self.env().caller();
-            self._calificar_vendedor(caller, oid, puntos)
+            self._configurar_suscripcion(caller, id_prod, plan)
         }
 
-        /// Permite al vendedor calificar al comprador de una orden.
+        /// Suscribe al llamante a un producto, pagando por adelantado `periodos` períodos.
         ///
-        /// Solo el vendedor de la orden puede calificar al comprador.
-        /// La orden debe estar en estado `Recibido`.
-        /// Solo se puede calificar una vez por orden.
-        /// La calificación debe estar entre 1 y 5.
+        /// El primer período comienza en el bloque actual. Los fondos quedan retenidos
+        /// en el contrato y el vendedor cobra cada período a medida que comienza.
+        /// La suscripción no descuenta stock del producto.
         ///
         /// # Argumentos
         ///
-        /// * `oid` - El ID de la orden a calificar.
-        /// * `puntos` - La calificación (1-5).
+        /// * `id_prod` - El ID del producto.
+        /// * `periodos` - Cantidad de períodos a pagar (mayor que 0).
         ///
         /// # Errores
         ///
-        /// - `Error::OrdenInexistente` si la orden no existe.
-        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
-        /// - `Error::OrdenNoRecibida` si la orden no está en estado Recibido.
-        /// - `Error::YaCalificado` si ya se ha calificado en esta orden.
-        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        /// Los mismos que `comprar`, y `Error::SinPlanSuscripcion` si el producto no
+        /// ofrece suscripción.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el `id` de la nueva suscripción.
+        #[ink(message, payable)]
+        pub fn suscribirse(&mut self, id_prod: u32, periodos: u32) -> Result<u32, Error> {
+            let comprador = self.env().caller();
+            let monto_enviado = self.env().transferred_value();
+            self.sin_reentrada(|mp| mp._suscribirse(comprador, id_prod, periodos, monto_enviado))
+        }
+
+        /// Cobra los períodos ya iniciados de una suscripción.
+        ///
+        /// Genera una `Orden` en estado `Recibido` por cada período iniciado y no cobrado, hasta
+        /// `MAX_PERIODOS_POR_COBRO` por llamada, la indexa para el comprador y el vendedor y
+        /// acredita su precio al vendedor. Cuando se cobran todos los períodos pagados, la
+        /// suscripción finaliza.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SuscripcionInexistente` si la suscripción no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor.
+        /// - `Error::SuscripcionFinalizada` si la suscripción ya no está activa.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve la cantidad de períodos cobrados.
         #[ink(message)]
-        pub fn calificar_comprador(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+        pub fn cobrar_suscripcion(&mut self, sid: u32) -> Result<u32, Error> {
             let caller = self.env().caller();
-            self._calificar_comprador(caller, oid, puntos)
+            self.sin_reentrada(|mp| mp._cobrar_suscripcion(caller, sid))
         }
 
-        /// Obtiene el total de productos publicados.
-        /// Útil para que ReportesView pueda iterar sobre todos los productos.
+        /// Cancela los períodos futuros de una suscripción.
+        ///
+        /// El precio de los períodos que aún no comenzaron se devuelve al comprador y los ya
+        /// iniciados se cobran al vendedor como en `cobrar_suscripcion`. Si quedan más de
+        /// `MAX_PERIODOS_POR_COBRO` períodos iniciados sin cobrar, la suscripción sigue activa
+        /// hasta que el vendedor cobre el resto.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SuscripcionInexistente` si la suscripción no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador.
+        /// - `Error::SuscripcionFinalizada` si la suscripción ya no está activa.
         #[ink(message)]
-        pub fn get_total_productos(&self) -> u32 {
-            self.next_prod_id.saturating_sub(1)
+        pub fn cancelar_suscripcion(&mut self, sid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._cancelar_suscripcion(caller, sid))
         }
 
-        /// Obtiene el total de órdenes creadas.
-        /// Útil para que ReportesView pueda iterar sobre todas las órdenes.
+        /// Obtiene una suscripción por su ID.
         #[ink(message)]
-        pub fn get_total_ordenes(&self) -> u32 {
-            self.next_order_id.saturating_sub(1)
+        pub fn obtener_suscripcion(&self, sid: u32) -> Option<Suscripcion> {
+            self.suscripciones.get(sid)
         }
 
-        /// Obtiene una orden por su ID sin restricción de permisos.
-        /// Esta función es pública para permitir reportes y análisis.
+        /// Bloquea a un comprador para que no pueda operar con el vendedor que llama.
+        ///
+        /// Un comprador bloqueado no puede comprar productos del vendedor ni solicitar
+        /// la cancelación de sus órdenes con él.
         ///
         /// # Argumentos
-        /// * `id` - El ID de la orden a consultar.
         ///
-        /// # Retorno
-        /// Devuelve `Some(Orden)` si existe, `None` en caso contrario.
-        #[ink(message)]
-        pub fn obtener_orden_publica(&self, id: u32) -> Option<Orden> {
-            self.ordenes.get(id)
-        }
-
-        /// Obtiene la lista de todos los usuarios registrados.
-        /// Útil para calcular rankings de reputación.
+        /// * `comprador` - La `AccountId` del comprador a bloquear.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::SinPermiso` si el llamante no es un vendedor.
+        /// - `Error::ParamInvalido` si el vendedor intenta bloquearse a sí mismo.
         #[ink(message)]
-        pub fn listar_usuarios(&self) -> Vec<AccountId> {
-            self.usuarios_registrados.clone()
+        pub fn bloquear_comprador(&mut self, comprador: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._bloquear_comprador(caller, comprador)
         }
 
-        /// Obtiene los fondos retenidos en escrow para una orden específica.
+        /// Quita a un comprador de la lista de bloqueados del vendedor que llama.
         ///
         /// # Argumentos
         ///
-        /// * `oid` - El ID de la orden a consultar.
+        /// * `comprador` - La `AccountId` del comprador a desbloquear.
         ///
-        /// # Retorno
+        /// # Errores
         ///
-        /// Devuelve el monto retenido para la orden, o 0 si no hay fondos (orden completada/cancelada).
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::SinPermiso` si el llamante no es un vendedor.
         #[ink(message)]
-        pub fn obtener_fondos_retenidos(&self, oid: u32) -> Balance {
-            self.fondos_retenidos.get(oid).unwrap_or(0)
+        pub fn desbloquear_comprador(&mut self, comprador: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._desbloquear_comprador(caller, comprador)
         }
 
-        /// Obtiene el balance total del contrato (fondos en escrow).
-        ///
-        /// Este balance representa la suma de todos los pagos retenidos
-        /// para órdenes pendientes o enviadas.
-        ///
-        /// # Retorno
+        /// Indica si un vendedor bloqueó a un comprador.
         ///
-        /// El balance actual del contrato en la moneda nativa.
+        /// Mientras el comprador recibe una migración en curso, también se consideran los
+        /// bloqueos de la cuenta anterior que todavía no se trasladaron.
         #[ink(message)]
-        pub fn balance_contrato(&self) -> Balance {
-            self.env().balance()
+        pub fn esta_bloqueado(&self, vendedor: AccountId, comprador: AccountId) -> bool {
+            self.compradores_bloqueados.contains((vendedor, comprador))
+                || self
+                    .migraciones_en_curso
+                    .get(comprador)
+                    .is_some_and(|m| self.compradores_bloqueados.contains((vendedor, m.anterior)))
         }
 
-        /// Lógica interna para listar todos los productos.
+        /// Agrega un producto a la lista de favoritos del llamante.
         ///
-        /// Itera sobre todos los IDs de productos desde 1 hasta `next_prod_id`.
+        /// # Argumentos
         ///
-        /// # Complejidad
+        /// * `id_prod` - El ID del producto a guardar.
         ///
-        /// O(n) donde n es el número total de productos publicados.
-        fn _listar_todos_productos(&self) -> Vec<(u32, Producto)> {
-            let mut lista = Vec::new();
-            for i in 1..self.next_prod_id {
-                if let Some(p) = self.productos.get(i) {
-                    lista.push((i, p));
+        /// # Errores
+        ///
+        /// - `Error::SinRegistro` si el llamante no está registrado.
+        /// - `Error::SinPermiso` si el llamante no es un comprador.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        /// - `Error::YaEsFavorito` si el producto ya está en la lista.
+        /// - `Error::FavoritosLlenos` si la lista ya tiene `MAX_FAVORITOS` productos.
+        #[ink(message)]
+        pub fn agregar_favorito(&mut self, id_prod: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._agregar_favorito(caller, id_prod)
+        }
+
+        /// Quita un producto de la lista de favoritos del llamante.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ParamInvalido` si el producto no está en la lista.
+        #[ink(message)]
+        pub fn quitar_favorito(&mut self, id_prod: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._quitar_favorito(caller, id_prod)
+        }
+
+        /// Lista una página de los productos favoritos de un usuario.
+        ///
+        /// # Argumentos
+        ///
+        /// * `usuario` - La cuenta cuyos favoritos se consultan.
+        /// * `desde` - Posición (desde 0) del primer favorito a devolver.
+        /// * `cantidad` - Cantidad máxima de IDs a devolver.
+        ///
+        /// # Retorno
+        ///
+        /// Los IDs de producto en el orden en que fueron agregados. Si `desde` supera
+        /// la cantidad de favoritos, devuelve un vector vacío.
+        #[ink(message)]
+        pub fn listar_favoritos(&self, usuario: AccountId, desde: u32, cantidad: u32) -> Vec<u32> {
+            self.favoritos
+                .get(usuario)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(desde as usize)
+                .take(cantidad as usize)
+                .collect()
+        }
+
+        /// Obtiene la cantidad de usuarios que tienen un producto en favoritos.
+        #[ink(message)]
+        pub fn obtener_cantidad_favoritos(&self, id_prod: u32) -> u32 {
+            self.favoritos_por_producto.get(id_prod).unwrap_or(0)
+        }
+
+        /// Obtiene la cantidad de favoritos de una página de productos, omitiendo los que
+        /// no tienen ninguno.
+        ///
+        /// # Argumentos
+        ///
+        /// * `desde` - El primer ID de producto a recorrer (los IDs comienzan en 1).
+        /// * `cantidad` - Cantidad de IDs a recorrer (se limita a
+        ///   `MAX_CONTEO_FAVORITOS_POR_PAGINA`).
+        ///
+        /// # Retorno
+        ///
+        /// Un vector de tuplas `(id_producto, cantidad_favoritos)`. Para recorrer todos los
+        /// productos, avanzar `desde` de a `cantidad` hasta superar `get_total_productos`.
+        #[ink(message)]
+        pub fn listar_conteo_favoritos(&self, desde: u32, cantidad: u32) -> Vec<(u32, u32)> {
+            let desde = desde.max(1);
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_CONTEO_FAVORITOS_POR_PAGINA))
+                .min(self.next_prod_id);
+            (desde..hasta)
+                .filter_map(|i| self.favoritos_por_producto.get(i).map(|c| (i, c)))
+                .collect()
+        }
+
+        /// Marca una orden como enviada.
+        ///
+        /// Solo el vendedor de la orden puede llamar a esta función.
+        /// La orden debe estar en estado `Pendiente`.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a marcar como enviada.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
+        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente`.
+        /// - `Error::ProductoDigital` si la orden es de un producto digital.
+        /// - `Error::LanzamientoCancelado` si el lanzamiento del producto fue cancelado.
+        /// - `Error::LanzamientoPendiente` si el producto está en preventa y aún no se lanzó.
+        #[ink(message)]
+        pub fn marcar_enviado(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._marcar_enviado(caller, oid)
+        }
+
+        /// Marca una orden como recibida.
+        ///
+        /// Solo el destinatario de la orden (el comprador, salvo en las compras para
+        /// regalo) puede llamar a esta función.
+        /// La orden debe estar en estado `Enviado`.
+        ///
+        /// ## Liberación de Fondos
+        ///
+        /// Al marcar como recibida, los fondos retenidos en escrow se acreditan al saldo
+        /// retirable del vendedor, que los retira con `retirar`.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a marcar como recibida.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el destinatario de la orden.
+        /// - `Error::EstadoInvalido` si la orden no está en estado `Enviado`.
+        #[ink(message)]
+        pub fn marcar_recibido(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._marcar_recibido(caller, oid))
+        }
+
+        /// Entrega al destinatario la clave del contenido de una orden digital.
+        ///
+        /// La clave debe estar cifrada con la clave pública del destinatario (el comprador,
+        /// salvo que la orden sea un regalo). Al entregarla,
+        /// la orden pasa a `Enviado` y comienza la ventana de disputa, tras la cual
+        /// cualquiera puede completarla con `completar_orden_digital`.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden.
+        /// * `clave_cifrada` - La clave del contenido, cifrada para el destinatario (máximo 512 bytes).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente`.
+        /// - `Error::LanzamientoCancelado` si el lanzamiento del producto fue cancelado.
+        /// - `Error::ParamInvalido` si la clave está vacía o es demasiado larga.
+        /// - `Error::PlazoVencido` si pasó el plazo de entrega desde la compra.
+        #[ink(message)]
+        pub fn entregar_clave(&mut self, oid: u32, clave_cifrada: Vec<u8>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._entregar_clave(caller, oid, clave_cifrada)
+        }
+
+        /// Completa una orden digital una vez vencida la ventana de disputa y libera
+        /// los fondos al vendedor.
+        ///
+        /// Cualquier cuenta puede llamar a esta función. Si hay una solicitud de cancelación
+        /// pendiente o el destinatario disputó la orden, la orden no se completa hasta que se
+        /// resuelvan.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        /// - `Error::EstadoInvalido` si la clave no fue entregada o la orden ya se cerró.
+        /// - `Error::CancelacionYaPendiente` si hay una solicitud de cancelación pendiente.
+        /// - `Error::OrdenEnDisputa` si el destinatario disputó la orden.
+        /// - `Error::PlazoNoVencido` si la ventana de disputa aún no terminó.
+        #[ink(message)]
+        pub fn completar_orden_digital(&mut self, oid: u32) -> Result<(), Error> {
+            self.sin_reentrada(|mp| mp._completar_orden_digital(oid))
+        }
+
+        /// Permite al destinatario disputar una orden digital dentro de la ventana de disputa.
+        ///
+        /// En un regalo disputa el destinatario, que es quien recibe la clave; si la disputa
+        /// termina en reembolso, los fondos vuelven igualmente al comprador. Mientras la
+        /// disputa está abierta los fondos quedan retenidos: la orden no se completa
+        /// automáticamente, y rechazar una solicitud de cancelación no cierra la disputa.
+        /// Se resuelve con `resolver_disputa_digital`.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el destinatario de la orden.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        /// - `Error::EstadoInvalido` si la clave no fue entregada o la orden ya se cerró.
+        /// - `Error::OrdenEnDisputa` si la orden ya está disputada.
+        /// - `Error::PlazoVencido` si la ventana de disputa ya terminó.
+        #[ink(message)]
+        pub fn disputar_orden_digital(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._disputar_orden_digital(caller, oid)
+        }
+
+        /// Resuelve la disputa de una orden digital, reembolsando al comprador o liberando
+        /// los fondos al vendedor.
+        ///
+        /// El vendedor solo puede resolverla a favor del comprador y el destinatario solo
+        /// puede retirarla. El administrador, si no es el vendedor de la orden, puede
+        /// resolverla en cualquier sentido.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden disputada.
+        /// * `reembolsar` - `true` para cancelar la orden y devolver los fondos al comprador;
+        ///   `false` para completarla y pagar al vendedor.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        /// - `Error::EstadoInvalido` si la orden ya se cerró.
+        /// - `Error::DisputaInexistente` si la orden no está disputada.
+        /// - `Error::SinPermiso` si el llamante no puede resolver la disputa en ese sentido.
+        #[ink(message)]
+        pub fn resolver_disputa_digital(
+            &mut self,
+            oid: u32,
+            reembolsar: bool,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._resolver_disputa_digital(caller, oid, reembolsar))
+        }
+
+        /// Permite al comprador recuperar sus fondos si el vendedor no entregó la clave
+        /// de una orden digital dentro del plazo.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador de la orden.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente`.
+        /// - `Error::PlazoNoVencido` si el plazo de entrega aún no venció.
+        #[ink(message)]
+        pub fn reclamar_reembolso_digital(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._reclamar_reembolso_digital(caller, oid))
+        }
+
+        /// Obtiene el estado de la entrega de una orden digital.
+        ///
+        /// Solo el comprador o el vendedor de la orden pueden consultarlo.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no participa de la orden.
+        /// - `Error::ProductoNoDigital` si la orden no es de un producto digital.
+        #[ink(message)]
+        pub fn obtener_entrega_digital(&self, oid: u32) -> Result<EntregaDigital, Error> {
+            self.obtener_orden(oid)?;
+            self.entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)
+        }
+
+        /// Obtiene la información de una orden por su ID.
+        ///
+        /// Solo el comprador o el vendedor de la orden pueden acceder a esta información.
+        ///
+        /// # Argumentos
+        ///
+        /// * `id` - El ID de la orden a consultar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador, el vendedor ni el
+        ///   destinatario de la orden.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve la `Orden` si existe y el llamante tiene permisos.
+        #[ink(message)]
+        pub fn obtener_orden(&self, id: u32) -> Result<Orden, Error> {
+            let caller = self.env().caller();
+            let orden = self.ordenes.get(id).ok_or(Error::OrdenInexistente)?;
+            self.ensure(
+                orden.comprador == caller
+                    || orden.vendedor == caller
+                    || orden.destinatario == caller,
+                Error::SinPermiso,
+            )?;
+            Ok(orden)
+        }
+
+        /// Lista todos los productos publicados por un vendedor específico.
+        ///
+        /// # Argumentos
+        ///
+        /// * `vendedor` - La `AccountId` del vendedor cuyos productos se desean listar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve un `Vec<Producto>` con todos los productos del vendedor.
+        /// Si el vendedor no tiene productos, devuelve un vector vacío.
+        ///
+        /// # Nota
+        ///
+        /// Esta función itera sobre todos los IDs de productos, por lo que su costo
+        /// aumenta linealmente con el número total de productos en el marketplace.
+        #[ink(message)]
+        pub fn listar_productos_de_vendedor(&self, vendedor: AccountId) -> Vec<Producto> {
+            self._listar_productos_de_vendedor(vendedor)
+        }
+
+        /// Lista todas las órdenes realizadas por el usuario que llama esta función.
+        ///
+        /// Por motivos de seguridad y privacidad, un comprador solo puede ver sus propias órdenes.
+        /// Incluye las órdenes que pagó (también sus compras para regalo) y las que recibe
+        /// como destinatario de un regalo.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve un `Vec<Orden>` con todas las órdenes del caller.
+        /// Si el caller no tiene órdenes, devuelve un vector vacío.
+        ///
+        /// # Nota
+        ///
+        /// Esta función itera sobre todos los IDs de órdenes, por lo que su costo
+        /// aumenta linealmente con el número total de órdenes en el marketplace.
+        /// `listar_ordenes_de_comprador_paginadas` devuelve las mismas órdenes por páginas.
+        #[ink(message)]
+        pub fn listar_ordenes_de_comprador(&self, comprador: AccountId) -> Vec<Orden> {
+            self._listar_ordenes_de_comprador(comprador)
+        }
+
+        /// Lista una página de las órdenes que pagó o recibe una cuenta.
+        ///
+        /// Recorre una página del índice de órdenes de la cuenta, que también contiene
+        /// sus ventas; solo se devuelven las órdenes que la cuenta pagó o recibe.
+        ///
+        /// # Argumentos
+        ///
+        /// * `comprador` - La cuenta que pagó o recibe las órdenes.
+        /// * `desde` - Posición (desde 0) del índice de órdenes de la cuenta.
+        /// * `cantidad` - Cantidad de posiciones a recorrer (se limita a `MAX_ORDENES_POR_PAGINA`).
+        #[ink(message)]
+        pub fn listar_ordenes_de_comprador_paginadas(
+            &self,
+            comprador: AccountId,
+            desde: u32,
+            cantidad: u32,
+        ) -> Vec<Orden> {
+            self._listar_ordenes_de_comprador_paginadas(comprador, desde, cantidad)
+        }
+
+        /// Lista las órdenes que tienen como destinatario a una cuenta, incluidas las
+        /// compras para regalo hechas por otros compradores.
+        ///
+        /// Recorre una página del índice de órdenes de la cuenta, que también contiene
+        /// sus compras y ventas; solo se devuelven las órdenes que la cuenta recibe.
+        ///
+        /// # Argumentos
+        ///
+        /// * `destinatario` - La cuenta que recibe las órdenes.
+        /// * `desde` - Posición (desde 0) del índice de órdenes de la cuenta.
+        /// * `cantidad` - Cantidad de posiciones a recorrer (se limita a `MAX_ORDENES_POR_PAGINA`).
+        #[ink(message)]
+        pub fn listar_ordenes_de_destinatario(
+            &self,
+            destinatario: AccountId,
+            desde: u32,
+            cantidad: u32,
+        ) -> Vec<Orden> {
+            self._listar_ordenes_de_destinatario(destinatario, desde, cantidad)
+        }
+
+        /// Obtiene la cantidad de posiciones del índice de órdenes de una cuenta.
+        #[ink(message)]
+        pub fn get_total_ordenes_de_cuenta(&self, cuenta: AccountId) -> u32 {
+            self.total_ordenes_de_cuenta.get(cuenta).unwrap_or(0)
+        }
+
+        /// Solicita la cancelación de una orden.
+        ///
+        /// El llamante debe ser el comprador o el vendedor de la orden.
+        /// La orden debe estar en estado `Pendiente` o `Enviado`.
+        ///
+        /// Se registra una solicitud que debe ser aceptada o rechazada por la otra
+        /// parte. Solo puede haber una solicitud pendiente por orden.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a cancelar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador ni el vendedor.
+        /// - `Error::EstadoInvalido` si la orden no está en estado `Pendiente` o `Enviado`.
+        /// - `Error::CancelacionYaPendiente` si ya existe una solicitud de cancelación.
+        /// - `Error::CompradorBloqueado` si el comprador solicitante fue bloqueado por el vendedor.
+        #[ink(message)]
+        pub fn solicitar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._solicitar_cancelacion(caller, oid)
+        }
+
+        /// Acepta una solicitud de cancelación de una orden.
+        ///
+        /// El llamante debe ser el otro participante (comprador si vendedor solicita, o viceversa).
+        /// Al aceptar, la orden pasa a estado `Cancelada` y el stock se restaura.
+        ///
+        /// ## Devolución de Fondos
+        ///
+        /// Al cancelar, los fondos retenidos en escrow se acreditan al saldo retirable
+        /// del comprador, que los retira con `retirar`.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden cuya cancelación se desea aceptar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::CancelacionInexistente` si no existe solicitud de cancelación.
+        /// - `Error::SinPermiso` si el llamante no es el otro participante.
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::ProdInexistente` si el producto no existe.
+        #[ink(message)]
+        pub fn aceptar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._aceptar_cancelacion(caller, oid))
+        }
+
+        /// Rechaza una solicitud de cancelación de una orden.
+        ///
+        /// El llamante debe ser el otro participante (comprador si vendedor solicita, o viceversa).
+        /// Solo elimina la solicitud de cancelación, la orden mantiene su estado anterior.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden cuya cancelación se desea rechazar.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::CancelacionInexistente` si no existe solicitud de cancelación.
+        /// - `Error::SinPermiso` si el llamante no es el otro participante.
+        #[ink(message)]
+        pub fn rechazar_cancelacion(&mut self, oid: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._rechazar_cancelacion(caller, oid)
+        }
+
+        /// Obtiene la reputación de un usuario específico.
+        ///
+        /// # Argumentos
+        ///
+        /// * `usuario` - La `AccountId` del usuario cuya reputación se desea consultar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(ReputacionUsuario)` si el usuario tiene reputación registrada, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_reputacion(&self, usuario: AccountId) -> Option<ReputacionUsuario> {
+            self.reputaciones.get(usuario)
+        }
+
+        /// Obtiene la reputación ponderada por monto y antigüedad de un usuario.
+        ///
+        /// A diferencia de `obtener_reputacion`, cada calificación pesa según el monto de la
+        /// orden calificada y las calificaciones antiguas pierden peso con el paso de los bloques.
+        ///
+        /// # Argumentos
+        ///
+        /// * `usuario` - La `AccountId` del usuario cuya reputación se desea consultar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(ReputacionPonderada)` si el usuario tiene calificaciones registradas, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_reputacion_ponderada(
+            &self,
+            usuario: AccountId,
+        ) -> Option<ReputacionPonderada> {
+            self.reputaciones_ponderadas.get(usuario)
+        }
+
+        /// Obtiene la suma y cantidad de calificaciones de vendedores para una categoría.
+        /// La categoría se busca en el registro por su nombre, como en `buscar_categoria`.
+        /// Retorna `Some((suma, cantidad))` o `None` si aún no hay calificaciones registradas.
+        #[ink(message)]
+        pub fn obtener_calificacion_categoria(&self, categoria: String) -> Option<(u32, u32)> {
+            self.buscar_categoria(categoria)
+                .and_then(|id| self.calificaciones_por_categoria.get(id))
+        }
+
+        /// Obtiene la suma y cantidad de calificaciones de vendedores para una categoría
+        /// del registro, indicada por su ID.
+        #[ink(message)]
+        pub fn obtener_calificacion_categoria_por_id(
+            &self,
+            id_categoria: u32,
+        ) -> Option<(u32, u32)> {
+            self.calificaciones_por_categoria.get(id_categoria)
+        }
+
+        /// Lista la suma y cantidad de calificaciones de vendedores de cada categoría
+        /// registrada que ya tiene calificaciones.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número de categorías creadas.
+        #[ink(message)]
+        pub fn listar_calificaciones_categorias(&self) -> Vec<(u32, (u32, u32))> {
+            (1..self.next_categoria_id)
+                .filter_map(|id| {
+                    self.calificaciones_por_categoria
+                        .get(id)
+                        .map(|calif| (id, calif))
+                })
+                .collect()
+        }
+
+        /// Permite al comprador calificar al vendedor de una orden.
+        ///
+        /// Solo el destinatario de la orden (el comprador, salvo en las compras para
+        /// regalo) puede calificar al vendedor.
+        /// La orden debe estar en estado `Recibido`.
+        /// Solo se puede calificar una vez por orden.
+        /// La calificación debe estar entre 1 y 5.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a calificar.
+        /// * `puntos` - La calificación (1-5).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el destinatario de la orden.
+        /// - `Error::OrdenNoRecibida` si la orden no está en estado Recibido.
+        /// - `Error::YaCalificado` si ya se ha calificado en esta orden.
+        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        #[ink(message)]
+        pub fn calificar_vendedor(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._calificar_vendedor(caller, oid, puntos)
+        }
+
+        /// Permite al vendedor calificar al comprador de una orden.
+        ///
+        /// Solo el vendedor de la orden puede calificar al comprador.
+        /// La orden debe estar en estado `Recibido`.
+        /// Solo se puede calificar una vez por orden.
+        /// La calificación debe estar entre 1 y 5.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a calificar.
+        /// * `puntos` - La calificación (1-5).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
+        /// - `Error::OrdenNoRecibida` si la orden no está en estado Recibido.
+        /// - `Error::YaCalificado` si ya se ha calificado en esta orden.
+        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        #[ink(message)]
+        pub fn calificar_comprador(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._calificar_comprador(caller, oid, puntos)
+        }
+
+        /// Permite responder públicamente a la calificación recibida en una orden.
+        ///
+        /// El vendedor de la orden responde a la calificación que le dio el comprador,
+        /// y el comprador responde a la calificación que le dio el vendedor.
+        /// Solo se admite una respuesta por calificación.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden calificada.
+        /// * `respuesta` - El texto de la respuesta (máximo 256 caracteres).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador ni el vendedor de la orden.
+        /// - `Error::ParamInvalido` si la respuesta está vacía o excede la longitud máxima.
+        /// - `Error::CalificacionInexistente` si la contraparte aún no calificó al llamante.
+        /// - `Error::YaRespondido` si el llamante ya respondió a esta calificación.
+        #[ink(message)]
+        pub fn responder_calificacion(&mut self, oid: u32, respuesta: String) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._responder_calificacion(caller, oid, respuesta)
+        }
+
+        /// Obtiene las calificaciones y respuestas registradas para una orden.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a consultar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Some(CalificacionOrden)` si la orden tiene alguna calificación, o `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_calificacion(&self, oid: u32) -> Option<CalificacionOrden> {
+            self.calificaciones.get(oid)
+        }
+
+        /// Permite al comprador (o al destinatario, en las compras para regalo) editar la
+        /// calificación que le dio al vendedor.
+        ///
+        /// La edición solo puede realizarse una vez y dentro de la ventana de edición
+        /// contada desde el bloque de la calificación original. La reputación del vendedor
+        /// y las calificaciones de la categoría se ajustan restando los puntos anteriores
+        /// y sumando los nuevos.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden calificada.
+        /// * `puntos` - La nueva calificación (1-5).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el destinatario de la orden.
+        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        /// - `Error::CalificacionInexistente` si el comprador aún no calificó al vendedor.
+        /// - `Error::YaEditado` si la calificación ya fue editada.
+        /// - `Error::VentanaEdicionVencida` si venció el período de edición.
+        #[ink(message)]
+        pub fn editar_calificacion_vendedor(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._editar_calificacion_vendedor(caller, oid, puntos)
+        }
+
+        /// Permite al vendedor editar la calificación que le dio al comprador.
+        ///
+        /// La edición solo puede realizarse una vez y dentro de la ventana de edición
+        /// contada desde el bloque de la calificación original.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden calificada.
+        /// * `puntos` - La nueva calificación (1-5).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el vendedor de la orden.
+        /// - `Error::CalificacionInvalida` si los puntos no están entre 1 y 5.
+        /// - `Error::CalificacionInexistente` si el vendedor aún no calificó al comprador.
+        /// - `Error::YaEditado` si la calificación ya fue editada.
+        /// - `Error::VentanaEdicionVencida` si venció el período de edición.
+        #[ink(message)]
+        pub fn editar_calificacion_comprador(&mut self, oid: u32, puntos: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._editar_calificacion_comprador(caller, oid, puntos)
+        }
+
+        /// Configura la cantidad de bloques durante los cuales se puede editar una calificación.
+        ///
+        /// Solo el administrador del contrato puede modificar este valor.
+        ///
+        /// # Argumentos
+        ///
+        /// * `bloques` - La nueva duración de la ventana de edición, en bloques.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::SinPermiso` si el llamante no es el administrador.
+        #[ink(message)]
+        pub fn configurar_ventana_edicion(&mut self, bloques: BlockNumber) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            self.ventana_edicion = bloques;
+            Ok(())
+        }
+
+        /// Obtiene la duración actual, en bloques, de la ventana de edición de calificaciones.
+        #[ink(message)]
+        pub fn obtener_ventana_edicion(&self) -> BlockNumber {
+            self.ventana_edicion
+        }
+
+        /// Configura la fianza mínima que deben tener los vendedores para publicar productos
+        /// cuyo precio alcance un umbral. Pasar `None` deja de exigirla.
+        ///
+        /// Se aplica a las publicaciones nuevas.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::SinPermiso` si el llamante no es el administrador.
+        #[ink(message)]
+        pub fn configurar_requisito_fianza(
+            &mut self,
+            requisito: Option<RequisitoFianza>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            self.requisito_fianza = requisito;
+            Ok(())
+        }
+
+        /// Obtiene la fianza mínima exigida para publicar, si está configurada.
+        #[ink(message)]
+        pub fn obtener_requisito_fianza(&self) -> Option<RequisitoFianza> {
+            self.requisito_fianza
+        }
+
+        /// Configura el tiempo sin actividad (modificaciones ni ventas) tras el cual una
+        /// publicación activa puede despublicarse con `depurar_publicaciones`. Pasar `None`
+        /// deshabilita la depuración.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es el administrador.
+        /// - `Error::ParamInvalido` si el plazo es 0.
+        #[ink(message)]
+        pub fn configurar_plazo_inactividad(
+            &mut self,
+            plazo: Option<Timestamp>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            self.ensure(plazo != Some(0), Error::ParamInvalido)?;
+            self.plazo_inactividad = plazo;
+            Ok(())
+        }
+
+        /// Obtiene el plazo de inactividad de las publicaciones, en milisegundos, si está configurado.
+        #[ink(message)]
+        pub fn obtener_plazo_inactividad(&self) -> Option<Timestamp> {
+            self.plazo_inactividad
+        }
+
+        /// Despublica los productos indicados que sigan activos y no hayan tenido actividad
+        /// (modificaciones ni ventas) durante el plazo de inactividad configurado.
+        ///
+        /// Cualquier cuenta puede llamarlo; los productos que no cumplan la condición se
+        /// ignoran. El vendedor puede volver a publicarlos con `reactivar_producto`.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::SinPlazoInactividad` si el administrador no configuró el plazo.
+        ///
+        /// # Retorno
+        ///
+        /// La cantidad de productos despublicados.
+        #[ink(message)]
+        pub fn depurar_publicaciones(&mut self, ids: Vec<u32>) -> Result<u32, Error> {
+            self._depurar_publicaciones(ids)
+        }
+
+        /// Deposita el valor transferido como fianza del vendedor llamante.
+        ///
+        /// La fianza respalda las compensaciones que el administrador otorga con
+        /// `compensar_con_fianza` a compradores de órdenes cuyo escrow ya se liberó.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es vendedor.
+        /// - `Error::ParamInvalido` si no se transfiere ningún monto.
+        #[ink(message, payable)]
+        pub fn depositar_fianza(&mut self) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            let monto = self.env().transferred_value();
+            self.sin_reentrada(|mp| mp._depositar_fianza(caller, monto))
+        }
+
+        /// Libera parte o toda la fianza del llamante.
+        ///
+        /// El monto liberado se acredita al saldo retirable del llamante, que luego lo
+        /// transfiere con `retirar` o `retirar_monto`.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ParamInvalido` si el monto es 0.
+        /// - `Error::FianzaInsuficiente` si el monto supera la fianza depositada.
+        /// - `Error::OrdenesAbiertas` si el llamante tiene ventas abiertas.
+        #[ink(message)]
+        pub fn retirar_fianza(&mut self, monto: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._retirar_fianza(caller, monto))
+        }
+
+        /// Obtiene la fianza depositada por un vendedor.
+        #[ink(message)]
+        pub fn obtener_fianza(&self, vendedor: AccountId) -> Balance {
+            self.fianzas.get(vendedor).unwrap_or(0)
+        }
+
+        /// Compensa al comprador de una orden ya completada con fondos de la fianza del
+        /// vendedor, como resolución de una disputa posterior a la liberación del escrow.
+        ///
+        /// Solo el administrador puede llamarla, una vez por orden. Se paga hasta `monto`,
+        /// limitado al `monto_total` de la orden y a la fianza disponible.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es el administrador.
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::EstadoInvalido` si la orden no está `Recibido` (si sigue abierta, su
+        ///   escrow se reembolsa con una cancelación).
+        /// - `Error::ParamInvalido` si el monto es 0.
+        /// - `Error::OrdenYaCompensada` si la orden ya fue compensada.
+        /// - `Error::FianzaInsuficiente` si el vendedor no tiene fianza.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el monto efectivamente pagado al comprador.
+        #[ink(message)]
+        pub fn compensar_con_fianza(&mut self, oid: u32, monto: Balance) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            self.sin_reentrada(|mp| mp._compensar_con_fianza(caller, oid, monto))
+        }
+
+        /// Verifica que los fondos comprometidos del contrato no superen su balance.
+        ///
+        /// Suma los fondos en escrow de todas las órdenes, los fondos de suscripciones,
+        /// los depósitos de reservas, las fianzas y los saldos retirables (todos llevados
+        /// como totales acumulados), y los compara con `self.env().balance()`. Solo el
+        /// administrador puede consultarla.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es el administrador.
+        /// - `Error::OverflowAritmetico` si la suma desborda.
+        #[ink(message)]
+        pub fn verificar_invariantes(&self) -> Result<EstadoFondos, Error> {
+            self._verificar_invariantes(self.env().caller())
+        }
+
+        /// Crea una categoría en el registro de categorías.
+        ///
+        /// Solo el administrador puede crear categorías. Los nombres se comparan sin
+        /// distinguir mayúsculas ni tildes, de modo que "Electrónica" y "electronica"
+        /// se consideran la misma categoría.
+        ///
+        /// # Argumentos
+        ///
+        /// * `nombre` - Nombre canónico de la categoría (máximo 32 caracteres, sin espacios al inicio ni al final).
+        /// * `padre` - El ID de la categoría padre, o `None` para crear una categoría raíz.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es el administrador.
+        /// - `Error::ParamInvalido` si el nombre está vacío, es demasiado largo o tiene espacios sobrantes.
+        /// - `Error::CategoriaInexistente` si la categoría padre no existe.
+        /// - `Error::CategoriaDuplicada` si ya existe una categoría con un nombre equivalente.
+        /// - `Error::IdOverflow` si se ha alcanzado el número máximo de categorías.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el `id` de la nueva categoría.
+        #[ink(message)]
+        pub fn crear_categoria(
+            &mut self,
+            nombre: String,
+            padre: Option<u32>,
+        ) -> Result<u32, Error> {
+            let caller = self.env().caller();
+            self._crear_categoria(caller, nombre, padre)
+        }
+
+        /// Obtiene una categoría del registro por su ID.
+        #[ink(message)]
+        pub fn obtener_categoria(&self, id: u32) -> Option<Categoria> {
+            self.categorias.get(id)
+        }
+
+        /// Busca el ID de una categoría por su nombre, sin distinguir mayúsculas, tildes ni
+        /// espacios al inicio o al final.
+        #[ink(message)]
+        pub fn buscar_categoria(&self, nombre: String) -> Option<u32> {
+            self.nombres_categoria
+                .get(Self::normalizar_categoria(&nombre))
+        }
+
+        /// Obtiene todas las categorías del registro.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número de categorías creadas.
+        ///
+        /// # Retorno
+        ///
+        /// Un vector de tuplas `(id_categoria, Categoria)` ordenado por ID.
+        #[ink(message)]
+        pub fn listar_categorias_registradas(&self) -> Vec<(u32, Categoria)> {
+            let mut lista = Vec::new();
+            for i in 1..self.next_categoria_id {
+                if let Some(c) = self.categorias.get(i) {
+                    lista.push((i, c));
+                }
+            }
+            lista
+        }
+
+        /// Obtiene la cuenta administradora del contrato.
+        #[ink(message)]
+        pub fn obtener_admin(&self) -> AccountId {
+            self.admin
+        }
+
+        /// Obtiene el total de productos publicados.
+        /// Útil para que ReportesView pueda iterar sobre todos los productos.
+        #[ink(message)]
+        pub fn get_total_productos(&self) -> u32 {
+            self.next_prod_id.saturating_sub(1)
+        }
+
+        /// Obtiene el total de órdenes creadas.
+        /// Útil para que ReportesView pueda iterar sobre todas las órdenes.
+        #[ink(message)]
+        pub fn get_total_ordenes(&self) -> u32 {
+            self.next_order_id.saturating_sub(1)
+        }
+
+        /// Obtiene una orden por su ID sin restricción de permisos.
+        /// Esta función es pública para permitir reportes y análisis.
+        ///
+        /// # Argumentos
+        /// * `id` - El ID de la orden a consultar.
+        ///
+        /// # Retorno
+        /// Devuelve `Some(Orden)` si existe, `None` en caso contrario.
+        #[ink(message)]
+        pub fn obtener_orden_publica(&self, id: u32) -> Option<Orden> {
+            self.ordenes.get(id)
+        }
+
+        /// Obtiene una página del historial de cambios de una orden, con el timestamp y el
+        /// bloque de su creación y de cada transición (incluidas las solicitudes de
+        /// cancelación y su resolución).
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden.
+        /// * `desde` - Posición (desde 0) del primer registro a devolver.
+        /// * `cantidad` - Cantidad máxima de registros (se limita a `MAX_REGISTROS_POR_PAGINA`).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador, el vendedor ni el
+        ///   destinatario de la orden.
+        ///
+        /// # Retorno
+        ///
+        /// Los registros de la página en orden cronológico.
+        #[ink(message)]
+        pub fn historial_orden(
+            &self,
+            oid: u32,
+            desde: u32,
+            cantidad: u32,
+        ) -> Result<Vec<RegistroOrden>, Error> {
+            let caller = self.env().caller();
+            self._historial_orden(caller, oid, desde, cantidad)
+        }
+
+        /// Obtiene la cantidad de registros en el historial de una orden.
+        #[ink(message)]
+        pub fn get_total_registros_orden(&self, oid: u32) -> u32 {
+            self.total_registros_orden.get(oid).unwrap_or(0)
+        }
+
+        /// Envía un mensaje a los demás participantes de una orden.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden.
+        /// * `contenido` - El mensaje cifrado o su hash (máximo 512 bytes).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador, el vendedor ni el
+        ///   destinatario de la orden.
+        /// - `Error::ParamInvalido` si el contenido está vacío o es demasiado largo.
+        /// - `Error::MensajesLlenos` si el llamante ya envió `MAX_MENSAJES_POR_PARTICIPANTE`
+        ///   mensajes en la orden.
+        #[ink(message)]
+        pub fn enviar_mensaje(&mut self, oid: u32, contenido: Vec<u8>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._enviar_mensaje(caller, oid, contenido)
+        }
+
+        /// Obtiene una página de los mensajes de una orden en orden cronológico.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden.
+        /// * `desde` - Posición (desde 0) del primer mensaje a devolver.
+        /// * `cantidad` - Cantidad máxima de mensajes (se limita a `MAX_MENSAJES_POR_PAGINA`).
+        ///
+        /// # Errores
+        ///
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::SinPermiso` si el llamante no es el comprador, el vendedor, el
+        ///   destinatario ni el árbitro asignado a la orden.
+        #[ink(message)]
+        pub fn obtener_mensajes(
+            &self,
+            oid: u32,
+            desde: u32,
+            cantidad: u32,
+        ) -> Result<Vec<MensajeOrden>, Error> {
+            let caller = self.env().caller();
+            self._obtener_mensajes(caller, oid, desde, cantidad)
+        }
+
+        /// Obtiene la cantidad de mensajes de una orden.
+        #[ink(message)]
+        pub fn get_total_mensajes_orden(&self, oid: u32) -> u32 {
+            self.total_mensajes_orden.get(oid).unwrap_or(0)
+        }
+
+        /// Asigna (o quita, con `None`) el árbitro que puede leer los mensajes de una orden.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::SinPermiso` si el llamante no es el administrador.
+        /// - `Error::OrdenInexistente` si la orden no existe.
+        /// - `Error::ParamInvalido` si el árbitro es el comprador, el vendedor o el
+        ///   destinatario de la orden.
+        #[ink(message)]
+        pub fn asignar_arbitro(
+            &mut self,
+            oid: u32,
+            arbitro: Option<AccountId>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self._asignar_arbitro(caller, oid, arbitro)
+        }
+
+        /// Obtiene el árbitro asignado a una orden, si lo hay.
+        #[ink(message)]
+        pub fn obtener_arbitro(&self, oid: u32) -> Option<AccountId> {
+            self.arbitros_orden.get(oid)
+        }
+
+        /// Lista una página de los usuarios registrados.
+        /// Útil para calcular rankings de reputación.
+        ///
+        /// # Argumentos
+        ///
+        /// * `desde` - Posición (desde 0) del primer usuario a devolver.
+        /// * `cantidad` - Cantidad máxima de usuarios (se limita a `MAX_USUARIOS_POR_PAGINA`).
+        ///
+        /// # Retorno
+        ///
+        /// Los usuarios de la página. Si `desde` supera la cantidad de usuarios, devuelve
+        /// un vector vacío.
+        #[ink(message)]
+        pub fn listar_usuarios(&self, desde: u32, cantidad: u32) -> Vec<AccountId> {
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_USUARIOS_POR_PAGINA))
+                .min(self.total_usuarios);
+            (desde..hasta)
+                .filter_map(|i| self.usuarios.get(i))
+                .collect()
+        }
+
+        /// Obtiene la cantidad de usuarios registrados.
+        #[ink(message)]
+        pub fn get_total_usuarios(&self) -> u32 {
+            self.total_usuarios
+        }
+
+        /// Obtiene los fondos retenidos en escrow para una orden específica.
+        ///
+        /// # Argumentos
+        ///
+        /// * `oid` - El ID de la orden a consultar.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el monto retenido para la orden, o 0 si no hay fondos (orden completada/cancelada).
+        #[ink(message)]
+        pub fn obtener_fondos_retenidos(&self, oid: u32) -> Balance {
+            self.fondos_retenidos.get(oid).unwrap_or(0)
+        }
+
+        /// Obtiene el balance total del contrato (fondos en escrow).
+        ///
+        /// Este balance representa la suma de todos los pagos retenidos
+        /// para órdenes pendientes o enviadas.
+        ///
+        /// # Retorno
+        ///
+        /// El balance actual del contrato en la moneda nativa.
+        #[ink(message)]
+        pub fn balance_contrato(&self) -> Balance {
+            self.env().balance()
+        }
+
+        /// Lógica interna para listar todos los productos.
+        ///
+        /// Itera sobre todos los IDs de productos desde 1 hasta `next_prod_id`.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número total de productos publicados.
+        fn _listar_todos_productos(&self) -> Vec<(u32, Producto)> {
+            let mut lista = Vec::new();
+            for i in 1..self.next_prod_id {
+                if let Some(p) = self.productos.get(i) {
+                    lista.push((i, self.con_estado_vigente(p)));
+                }
+            }
+            lista
+        }
+
+        /// Lógica interna para listar todas las órdenes.
+        ///
+        /// Itera sobre todos los IDs de órdenes desde 1 hasta `next_order_id`.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número total de órdenes creadas.
+        fn _listar_todas_ordenes(&self) -> Vec<(u32, Orden)> {
+            let mut lista = Vec::new();
+            for i in 1..self.next_order_id {
+                if let Some(o) = self.ordenes.get(i) {
+                    lista.push((i, o));
+                }
+            }
+            lista
+        }
+
+        /// Lógica interna para listar todas las reputaciones.
+        ///
+        /// Itera sobre todos los usuarios registrados y obtiene su reputación.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número de usuarios registrados.
+        fn _listar_todas_reputaciones(&self) -> Vec<(AccountId, ReputacionUsuario)> {
+            let mut lista = Vec::new();
+            for i in 0..self.total_usuarios {
+                let Some(usuario) = self.usuarios.get(i) else {
+                    continue;
+                };
+                if let Some(rep) = self.reputaciones.get(usuario) {
+                    lista.push((usuario, rep));
+                }
+            }
+            lista
+        }
+
+        /// Lógica interna para listar productos de un vendedor.
+        ///
+        /// Itera sobre todos los productos y filtra por vendedor.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número total de productos en el marketplace.
+        fn _listar_productos_de_vendedor(&self, vendedor: AccountId) -> Vec<Producto> {
+            let mut productos_vendedor = Vec::new();
+
+            for pid in 1..self.next_prod_id {
+                if let Some(producto) = self.productos.get(pid) {
+                    if producto.vendedor == vendedor {
+                        productos_vendedor.push(self.con_estado_vigente(producto));
+                    }
+                }
+            }
+
+            productos_vendedor
+        }
+
+        /// Lógica interna para listar órdenes de un comprador.
+        ///
+        /// Itera sobre todas las órdenes y filtra por comprador o destinatario.
+        ///
+        /// # Complejidad
+        ///
+        /// O(n) donde n es el número total de órdenes en el marketplace.
+        fn _listar_ordenes_de_comprador(&self, comprador: AccountId) -> Vec<Orden> {
+            let mut ordenes_comprador = Vec::new();
+
+            for oid in 1..self.next_order_id {
+                if let Some(orden) = self.ordenes.get(oid) {
+                    if orden.comprador == comprador || orden.destinatario == comprador {
+                        ordenes_comprador.push(orden);
+                    }
+                }
+            }
+
+            ordenes_comprador
+        }
+
+        /// Lógica interna para listar una página de las órdenes que pagó o recibe una cuenta.
+        fn _listar_ordenes_de_comprador_paginadas(
+            &self,
+            comprador: AccountId,
+            desde: u32,
+            cantidad: u32,
+        ) -> Vec<Orden> {
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_ORDENES_POR_PAGINA))
+                .min(self.get_total_ordenes_de_cuenta(comprador));
+            (desde..hasta)
+                .filter_map(|i| self.ordenes_de_cuenta.get((comprador, i)))
+                .filter_map(|oid| self.ordenes.get(oid))
+                .filter(|orden| orden.comprador == comprador || orden.destinatario == comprador)
+                .collect()
+        }
+
+        /// Lógica interna para listar una página de las órdenes que recibe una cuenta.
+        fn _listar_ordenes_de_destinatario(
+            &self,
+            destinatario: AccountId,
+            desde: u32,
+            cantidad: u32,
+        ) -> Vec<Orden> {
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_ORDENES_POR_PAGINA))
+                .min(self.get_total_ordenes_de_cuenta(destinatario));
+            (desde..hasta)
+                .filter_map(|i| self.ordenes_de_cuenta.get((destinatario, i)))
+                .filter_map(|oid| self.ordenes.get(oid))
+                .filter(|orden| orden.destinatario == destinatario)
+                .collect()
+        }
+
+        /// Lógica interna para obtener una página del historial de una orden.
+        fn _historial_orden(
+            &self,
+            caller: AccountId,
+            oid: u32,
+            desde: u32,
+            cantidad: u32,
+        ) -> Result<Vec<RegistroOrden>, Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(
+                orden.comprador == caller
+                    || orden.vendedor == caller
+                    || orden.destinatario == caller,
+                Error::SinPermiso,
+            )?;
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_REGISTROS_POR_PAGINA))
+                .min(self.get_total_registros_orden(oid));
+            Ok((desde..hasta)
+                .filter_map(|i| self.historial_ordenes.get((oid, i)))
+                .collect())
+        }
+
+        /// Lógica interna para enviar un mensaje en una orden.
+        fn _enviar_mensaje(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            contenido: Vec<u8>,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(
+                orden.comprador == caller
+                    || orden.vendedor == caller
+                    || orden.destinatario == caller,
+                Error::SinPermiso,
+            )?;
+            self.ensure(
+                !contenido.is_empty() && contenido.len() <= MAX_MENSAJE_LEN,
+                Error::ParamInvalido,
+            )?;
+            let enviados = self.mensajes_enviados.get((oid, caller)).unwrap_or(0);
+            self.ensure(
+                enviados < MAX_MENSAJES_POR_PARTICIPANTE,
+                Error::MensajesLlenos,
+            )?;
+
+            let posicion = self.get_total_mensajes_orden(oid);
+            self.total_mensajes_orden.insert(
+                oid,
+                &posicion.checked_add(1).ok_or(Error::OverflowAritmetico)?,
+            );
+            self.mensajes_enviados
+                .insert((oid, caller), &enviados.saturating_add(1));
+            self.mensajes_orden.insert(
+                (oid, posicion),
+                &MensajeOrden {
+                    remitente: caller,
+                    contenido,
+                    momento: self.env().block_timestamp(),
+                },
+            );
+            Ok(())
+        }
+
+        /// Lógica interna para obtener una página de los mensajes de una orden.
+        fn _obtener_mensajes(
+            &self,
+            caller: AccountId,
+            oid: u32,
+            desde: u32,
+            cantidad: u32,
+        ) -> Result<Vec<MensajeOrden>, Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(
+                orden.comprador == caller
+                    || orden.vendedor == caller
+                    || orden.destinatario == caller
+                    || self.arbitros_orden.get(oid) == Some(caller),
+                Error::SinPermiso,
+            )?;
+            let hasta = desde
+                .saturating_add(cantidad.min(MAX_MENSAJES_POR_PAGINA))
+                .min(self.get_total_mensajes_orden(oid));
+            Ok((desde..hasta)
+                .filter_map(|i| self.mensajes_orden.get((oid, i)))
+                .collect())
+        }
+
+        /// Lógica interna para asignar o quitar el árbitro de una orden.
+        fn _asignar_arbitro(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            arbitro: Option<AccountId>,
+        ) -> Result<(), Error> {
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            match arbitro {
+                Some(cuenta) => {
+                    self.ensure(
+                        cuenta != orden.comprador
+                            && cuenta != orden.vendedor
+                            && cuenta != orden.destinatario,
+                        Error::ParamInvalido,
+                    )?;
+                    self.arbitros_orden.insert(oid, &cuenta);
+                }
+                None => self.arbitros_orden.remove(oid),
+            }
+            Ok(())
+        }
+
+        /// Lógica interna para registrar un usuario.
+        fn _registrar(&mut self, caller: AccountId, rol: Rol) -> Result<(), Error> {
+            self.ensure(!self.roles.contains(caller), Error::YaRegistrado)?;
+            self.ensure(
+                !self.origenes_migracion.contains(caller),
+                Error::MigracionEnCurso,
+            )?;
+            self.ensure(
+                !self.cuentas_migradas.contains(caller),
+                Error::CuentaMigrada,
+            )?;
+            self.agregar_usuario(caller)?;
+            self.roles.insert(caller, &rol);
+            self.perfiles.insert(
+                caller,
+                &Perfil {
+                    nombre_visible: String::new(),
+                    uri_metadata: String::new(),
+                    hash_contenido: None,
+                    hash_contacto: None,
+                    bloque_registro: self.env().block_number(),
+                },
+            );
+            Ok(())
+        }
+
+        /// Lógica interna para registrar un usuario junto con su perfil.
+        ///
+        /// Valida el perfil antes de registrar para no dejar al usuario registrado
+        /// sin perfil si los datos no son válidos.
+        fn _registrar_con_perfil(
+            &mut self,
+            caller: AccountId,
+            rol: Rol,
+            nombre_visible: String,
+            uri_metadata: String,
+            hash_contenido: Option<Hash>,
+            hash_contacto: Option<Hash>,
+        ) -> Result<(), Error> {
+            self.ensure(!self.roles.contains(caller), Error::YaRegistrado)?;
+            self.validar_perfil(caller, &nombre_visible, &uri_metadata)?;
+            self._registrar(caller, rol)?;
+            self._actualizar_perfil(
+                caller,
+                nombre_visible,
+                uri_metadata,
+                hash_contenido,
+                hash_contacto,
+            )
+        }
+
+        /// Lógica interna para dar de baja a un usuario.
+        ///
+        /// Quita al usuario de `usuarios` moviendo el último a su lugar, y actualiza
+        /// el índice del usuario movido.
+        fn _darse_de_baja(&mut self, caller: AccountId) -> Result<(), Error> {
+            self.rol_de(caller)?;
+            self.sin_migracion_en_curso(caller)?;
+            self.ensure(
+                self.obtener_ordenes_abiertas(caller) == (0, 0),
+                Error::OrdenesAbiertas,
+            )?;
+            self.ensure(self.obtener_fianza(caller) == 0, Error::FianzaDepositada)?;
+
+            self.pausar_productos_de(caller)?;
+            self.migraciones_pendientes.remove(caller);
+
+            for id_prod in self.favoritos.take(caller).unwrap_or_default() {
+                self.descontar_favorito(id_prod);
+            }
+
+            if let Some(perfil) = self.perfiles.take(caller) {
+                if !perfil.nombre_visible.is_empty() {
+                    self.nombres_visibles
+                        .remove(Self::normalizar_nombre(&perfil.nombre_visible));
+                }
+            }
+
+            if let Some(indice) = self.indice_usuarios.take(caller) {
+                let ultimo = self.total_usuarios.saturating_sub(1);
+                if indice != ultimo {
+                    if let Some(movido) = self.usuarios.get(ultimo) {
+                        self.usuarios.insert(indice, &movido);
+                        self.indice_usuarios.insert(movido, &indice);
+                    }
+                }
+                self.usuarios.remove(ultimo);
+                self.total_usuarios = ultimo;
+            }
+
+            self.roles.remove(caller);
+            Ok(())
+        }
+
+        /// Lógica interna para iniciar una migración de identidad.
+        fn _iniciar_migracion(&mut self, caller: AccountId, nueva: AccountId) -> Result<(), Error> {
+            self.rol_de(caller)?;
+            self.sin_migracion_en_curso(caller)?;
+            self.validar_cuenta_destino(caller, nueva)?;
+            self.migraciones_pendientes.insert(caller, &nueva);
+            Ok(())
+        }
+
+        /// Lógica interna para cancelar una migración de identidad pendiente.
+        fn _cancelar_migracion(&mut self, caller: AccountId) -> Result<(), Error> {
+            self.ensure(
+                self.migraciones_pendientes.contains(caller),
+                Error::MigracionInexistente,
+            )?;
+            self.migraciones_pendientes.remove(caller);
+            Ok(())
+        }
+
+        /// Lógica interna para aceptar una migración de identidad.
+        ///
+        /// Traslada los datos propios de la cuenta y deja registrada la migración en curso
+        /// para trasladar por lotes las referencias de productos, órdenes y suscripciones.
+        ///
+        /// # Complejidad
+        ///
+        /// O(1) más un lote de `_continuar_migracion`.
+        fn _aceptar_migracion(
+            &mut self,
+            nueva: AccountId,
+            anterior: AccountId,
+        ) -> Result<(), Error> {
+            self.ensure(
+                self.migraciones_pendientes.get(anterior) == Some(nueva),
+                Error::MigracionInexistente,
+            )?;
+            let rol = self.rol_de(anterior)?;
+            self.validar_cuenta_destino(anterior, nueva)?;
+
+            self.migraciones_pendientes.remove(anterior);
+
+            self.roles.remove(anterior);
+            self.roles.insert(nueva, &rol);
+
+            if let Some(indice) = self.indice_usuarios.take(anterior) {
+                self.usuarios.insert(indice, &nueva);
+                self.indice_usuarios.insert(nueva, &indice);
+            }
+
+            if let Some(perfil) = self.perfiles.take(anterior) {
+                if !perfil.nombre_visible.is_empty() {
+                    self.nombres_visibles
+                        .insert(Self::normalizar_nombre(&perfil.nombre_visible), &nueva);
+                }
+                self.perfiles.insert(nueva, &perfil);
+            }
+
+            if let Some(rep) = self.reputaciones.take(anterior) {
+                self.reputaciones.insert(nueva, &rep);
+            }
+            self.posicion_por_promedio.remove(anterior);
+            if let Some(rep) = self.reputaciones_ponderadas.take(anterior) {
+                self.reputaciones_ponderadas.insert(nueva, &rep);
+            }
+            if let Some(favoritos) = self.favoritos.take(anterior) {
+                self.favoritos.insert(nueva, &favoritos);
+            }
+            if let Some(fianza) = self.fianzas.take(anterior) {
+                self.fianzas.insert(nueva, &fianza);
+            }
+            if let Some(saldo) = self.saldos_retirables.take(anterior) {
+                self.saldos_retirables.insert(nueva, &saldo);
+            }
+            if let Some(estadisticas) = self.estadisticas_referidos.take(anterior) {
+                self.estadisticas_referidos.insert(nueva, &estadisticas);
+            }
+            if let Some(posicion) = self.mejores_referidos.iter().position(|&c| c == anterior) {
+                self.mejores_referidos[posicion] = nueva;
+            }
+            self.cuentas_migradas.remove(nueva);
+            self.cuentas_migradas.insert(anterior, &nueva);
+
+            self.migraciones_en_curso.insert(
+                nueva,
+                &MigracionEnCurso {
+                    anterior,
+                    bloqueos: 0,
+                    bloqueos_recibidos: 0,
+                    productos: 0,
+                    ordenes: 0,
+                    suscripciones: 0,
+                    reservas: 0,
+                },
+            );
+            self.origenes_migracion.insert(anterior, &nueva);
+            self._continuar_migracion(nueva)?;
+            Ok(())
+        }
+
+        /// Lógica interna para trasladar el siguiente lote de una migración en curso.
+        ///
+        /// Recorre, en ese orden, los compradores bloqueados, los vendedores que la bloquearon,
+        /// los productos, las órdenes, las suscripciones y las reservas indexados para la
+        /// cuenta anterior, hasta `MAX_MIGRACION_POR_LLAMADA` posiciones por llamada.
+        ///
+        /// # Complejidad
+        ///
+        /// O(`MAX_MIGRACION_POR_LLAMADA`).
+        fn _continuar_migracion(&mut self, nueva: AccountId) -> Result<bool, Error> {
+            let mut migracion = self
+                .migraciones_en_curso
+                .get(nueva)
+                .ok_or(Error::MigracionInexistente)?;
+            let anterior = migracion.anterior;
+            let mut restantes = MAX_MIGRACION_POR_LLAMADA;
+
+            let total_bloqueos = self
+                .total_bloqueados_por_vendedor
+                .get(anterior)
+                .unwrap_or(0);
+            while migracion.bloqueos < total_bloqueos && restantes > 0 {
+                if let Some(comprador) = self
+                    .bloqueados_por_vendedor
+                    .take((anterior, migracion.bloqueos))
+                {
+                    self.mover_bloqueo(anterior, nueva, comprador)?;
+                }
+                migracion.bloqueos = migracion.bloqueos.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let total_bloqueos_recibidos = self
+                .total_bloqueadores_de_comprador
+                .get(anterior)
+                .unwrap_or(0);
+            while migracion.bloqueos_recibidos < total_bloqueos_recibidos && restantes > 0 {
+                if let Some(vendedor) = self
+                    .bloqueadores_de_comprador
+                    .take((anterior, migracion.bloqueos_recibidos))
+                {
+                    self.mover_bloqueo_recibido(anterior, nueva, vendedor)?;
+                }
+                migracion.bloqueos_recibidos = migracion.bloqueos_recibidos.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let total_productos = self.total_productos_por_vendedor.get(anterior).unwrap_or(0);
+            while migracion.productos < total_productos && restantes > 0 {
+                if let Some(pid) = self
+                    .productos_por_vendedor
+                    .take((anterior, migracion.productos))
+                {
+                    self.mover_producto(anterior, nueva, pid)?;
+                }
+                migracion.productos = migracion.productos.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let total_ordenes = self.total_ordenes_de_cuenta.get(anterior).unwrap_or(0);
+            while migracion.ordenes < total_ordenes && restantes > 0 {
+                if let Some(oid) = self.ordenes_de_cuenta.take((anterior, migracion.ordenes)) {
+                    self.mover_orden(anterior, nueva, oid)?;
+                }
+                migracion.ordenes = migracion.ordenes.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let total_suscripciones = self
+                .total_suscripciones_de_cuenta
+                .get(anterior)
+                .unwrap_or(0);
+            while migracion.suscripciones < total_suscripciones && restantes > 0 {
+                if let Some(sid) = self
+                    .suscripciones_de_cuenta
+                    .take((anterior, migracion.suscripciones))
+                {
+                    self.mover_suscripcion(anterior, nueva, sid)?;
+                }
+                migracion.suscripciones = migracion.suscripciones.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let total_reservas = self.total_reservas_de_cuenta.get(anterior).unwrap_or(0);
+            while migracion.reservas < total_reservas && restantes > 0 {
+                if let Some(pid) = self.reservas_de_cuenta.take((anterior, migracion.reservas)) {
+                    self.mover_reserva(anterior, nueva, pid)?;
+                }
+                migracion.reservas = migracion.reservas.saturating_add(1);
+                restantes = restantes.saturating_sub(1);
+            }
+
+            let terminada = migracion.bloqueos >= total_bloqueos
+                && migracion.bloqueos_recibidos >= total_bloqueos_recibidos
+                && migracion.productos >= total_productos
+                && migracion.ordenes >= total_ordenes
+                && migracion.suscripciones >= total_suscripciones
+                && migracion.reservas >= total_reservas;
+            if terminada {
+                self.migraciones_en_curso.remove(nueva);
+                self.origenes_migracion.remove(anterior);
+                self.total_bloqueados_por_vendedor.remove(anterior);
+                self.total_bloqueadores_de_comprador.remove(anterior);
+                self.total_productos_por_vendedor.remove(anterior);
+                self.total_ordenes_de_cuenta.remove(anterior);
+                self.total_suscripciones_de_cuenta.remove(anterior);
+                self.total_reservas_de_cuenta.remove(anterior);
+            } else {
+                self.migraciones_en_curso.insert(nueva, &migracion);
+            }
+            Ok(terminada)
+        }
+
+        /// Lógica interna para actualizar el perfil de un usuario.
+        fn _actualizar_perfil(
+            &mut self,
+            caller: AccountId,
+            nombre_visible: String,
+            uri_metadata: String,
+            hash_contenido: Option<Hash>,
+            hash_contacto: Option<Hash>,
+        ) -> Result<(), Error> {
+            self.rol_de(caller)?;
+            self.validar_perfil(caller, &nombre_visible, &uri_metadata)?;
+            let mut perfil = self.perfiles.get(caller).ok_or(Error::SinRegistro)?;
+
+            let nombre_anterior = Self::normalizar_nombre(&perfil.nombre_visible);
+            let nombre_nuevo = Self::normalizar_nombre(&nombre_visible);
+            if nombre_anterior != nombre_nuevo {
+                if !nombre_anterior.is_empty() {
+                    self.nombres_visibles.remove(nombre_anterior);
+                }
+                if !nombre_nuevo.is_empty() {
+                    self.nombres_visibles.insert(nombre_nuevo, &caller);
+                }
+            }
+
+            perfil.nombre_visible = nombre_visible;
+            perfil.uri_metadata = uri_metadata;
+            perfil.hash_contenido = hash_contenido;
+            perfil.hash_contacto = hash_contacto;
+            self.perfiles.insert(caller, &perfil);
+            Ok(())
+        }
+
+        /// Lógica interna para modificar el rol de un usuario.
+        fn _modificar_rol(&mut self, caller: AccountId, nuevo_rol: Rol) -> Result<(), Error> {
+            let rol_actual = self.rol_de(caller)?;
+            self.sin_migracion_en_curso(caller)?;
+
+            if rol_actual.es_vendedor() && !nuevo_rol.es_vendedor() {
+                let (_, ventas_abiertas) = self.obtener_ordenes_abiertas(caller);
+                self.ensure(ventas_abiertas == 0, Error::VentasPendientes)?;
+                self.pausar_productos_de(caller)?;
+            }
+
+            self.roles.insert(caller, &nuevo_rol);
+            Ok(())
+        }
+
+        /// Lógica interna para reactivar un producto de un usuario que sigue siendo vendedor.
+        fn _reactivar_producto(&mut self, caller: AccountId, id_prod: u32) -> Result<(), Error> {
+            let rol = self.rol_de(caller)?;
+            self.ensure(rol.es_vendedor(), Error::SinPermiso)?;
+            self._cambiar_estado_producto(caller, id_prod, true)
+        }
+
+        /// Lógica interna para pausar o reactivar un producto del vendedor.
+        fn _cambiar_estado_producto(
+            &mut self,
+            caller: AccountId,
+            id_prod: u32,
+            activo: bool,
+        ) -> Result<(), Error> {
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.vendedor == caller, Error::SinPermiso)?;
+            if activo {
+                self.ensure(
+                    !producto.lanzamiento.is_some_and(|l| l.cancelado),
+                    Error::LanzamientoCancelado,
+                )?;
+            }
+            if activo && !producto.activo {
+                self.indexar_producto(id_prod, &producto)?;
+            } else if !activo && producto.activo {
+                self.desindexar_producto(id_prod, &producto);
+            }
+            producto.activo = activo;
+            producto.pausas_vendedor = self.pausas_vendedor.get(caller).unwrap_or(0);
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+            Ok(())
+        }
+
+        /// Lógica interna para publicar un producto.
+        ///
+        /// Resuelve la categoría por nombre en el registro y, si no existe, la crea como
+        /// categoría raíz.
+        fn _publicar(
+            &mut self,
+            vendedor: AccountId,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            stock: u32,
+            categoria: String,
+        ) -> Result<u32, Error> {
+            self.ensure(self.rol_de(vendedor)?.es_vendedor(), Error::SinPermiso)?;
+            let id_categoria = match self.buscar_categoria(categoria.clone()) {
+                Some(id) => id,
+                None => self.agregar_categoria(String::from(categoria.trim()), None)?,
+            };
+            self._publicar_en_categoria(vendedor, nombre, descripcion, precio, stock, id_categoria)
+        }
+
+        /// Lógica interna para publicar un producto en una categoría del registro.
+        fn _publicar_en_categoria(
+            &mut self,
+            vendedor: AccountId,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            stock: u32,
+            id_categoria: u32,
+        ) -> Result<u32, Error> {
+            let producto =
+                self.nuevo_producto(vendedor, nombre, descripcion, precio, stock, id_categoria)?;
+            self.guardar_producto(producto)
+        }
+
+        /// Lógica interna para publicar un producto digital ya validado por `nuevo_producto`.
+        fn _publicar_digital(
+            &mut self,
+            mut producto: Producto,
+            referencia_cifrada: String,
+        ) -> Result<u32, Error> {
+            self.ensure(
+                !referencia_cifrada.is_empty() && referencia_cifrada.len() <= MAX_URI_LEN,
+                Error::ParamInvalido,
+            )?;
+            producto.tipo = TipoProducto::Digital { referencia_cifrada };
+            self.guardar_producto(producto)
+        }
+
+        /// Lógica interna para publicar una preventa ya validada por `nuevo_producto`.
+        fn _publicar_preventa(
+            &mut self,
+            mut producto: Producto,
+            fecha_lanzamiento: Timestamp,
+        ) -> Result<u32, Error> {
+            self.ensure(
+                fecha_lanzamiento > self.env().block_timestamp(),
+                Error::ParamInvalido,
+            )?;
+            producto.lanzamiento = Some(Lanzamiento {
+                fecha_lanzamiento,
+                cancelado: false,
+            });
+            self.guardar_producto(producto)
+        }
+
+        /// Helper que valida los datos de una publicación y arma el producto, sin guardarlo.
+        ///
+        /// # Errores
+        ///
+        /// Los mismos que `publicar`, salvo `Error::IdOverflow`.
+        fn nuevo_producto(
+            &self,
+            vendedor: AccountId,
+            nombre: String,
+            descripcion: String,
+            precio: Balance,
+            stock: u32,
+            id_categoria: u32,
+        ) -> Result<Producto, Error> {
+            let rol_vendedor = self.rol_de(vendedor)?;
+            self.ensure(rol_vendedor.es_vendedor(), Error::SinPermiso)?;
+            self.ensure(
+                precio > 0
+                    && stock > 0
+                    && !nombre.is_empty()
+                    && nombre.len() <= MAX_NOMBRE_LEN
+                    && !descripcion.is_empty()
+                    && descripcion.len() <= MAX_DESCRIPCION_LEN,
+                Error::ParamInvalido,
+            )?;
+            self.verificar_fianza(vendedor, precio)?;
+            self.ensure(
+                self.categorias.contains(id_categoria),
+                Error::CategoriaInexistente,
+            )?;
+
+            Ok(Producto {
+                vendedor,
+                nombre,
+                descripcion,
+                precio,
+                stock,
+                id_categoria,
+                requisito_reputacion: None,
+                activo: true,
+                pausas_vendedor: self.pausas_vendedor.get(vendedor).unwrap_or(0),
+                porcentaje_referido: 0,
+                tipo: TipoProducto::Fisico,
+                plan_suscripcion: None,
+                lanzamiento: None,
+                creado_en: self.env().block_timestamp(),
+                actualizado_en: self.env().block_timestamp(),
+            })
+        }
+
+        /// Helper que asigna un ID a un producto nuevo, lo guarda y lo agrega a los índices
+        /// por categoría y por vendedor.
+        fn guardar_producto(&mut self, producto: Producto) -> Result<u32, Error> {
+            let pid = self.next_prod_id;
+            let en_categoria = self
+                .total_productos_por_categoria
+                .get(producto.id_categoria)
+                .unwrap_or(0);
+            let del_vendedor = self
+                .total_productos_por_vendedor
+                .get(producto.vendedor)
+                .unwrap_or(0);
+            let nuevo_en_categoria = en_categoria
+                .checked_add(1)
+                .ok_or(Error::OverflowAritmetico)?;
+            let nuevo_del_vendedor = del_vendedor
+                .checked_add(1)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.next_prod_id = self.next_prod_id.checked_add(1).ok_or(Error::IdOverflow)?;
+            self.productos.insert(pid, &producto);
+
+            self.productos_por_categoria
+                .insert((producto.id_categoria, en_categoria), &pid);
+            self.total_productos_por_categoria
+                .insert(producto.id_categoria, &nuevo_en_categoria);
+            self.productos_por_vendedor
+                .insert((producto.vendedor, del_vendedor), &pid);
+            self.total_productos_por_vendedor
+                .insert(producto.vendedor, &nuevo_del_vendedor);
+
+            self.indexar_producto(pid, &producto)?;
+            self.actualizar_promedio_vendedor(producto.vendedor)?;
+
+            Ok(pid)
+        }
+
+        /// Lógica interna para crear una categoría.
+        fn _crear_categoria(
+            &mut self,
+            caller: AccountId,
+            nombre: String,
+            padre: Option<u32>,
+        ) -> Result<u32, Error> {
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            self.agregar_categoria(nombre, padre)
+        }
+
+        /// Helper que valida y agrega una categoría al registro.
+        fn agregar_categoria(&mut self, nombre: String, padre: Option<u32>) -> Result<u32, Error> {
+            self.ensure(
+                !nombre.is_empty() && nombre.len() <= MAX_CATEGORIA_LEN && nombre.trim() == nombre,
+                Error::ParamInvalido,
+            )?;
+            if let Some(id_padre) = padre {
+                self.ensure(
+                    self.categorias.contains(id_padre),
+                    Error::CategoriaInexistente,
+                )?;
+            }
+            let clave = Self::normalizar_categoria(&nombre);
+            self.ensure(
+                !self.nombres_categoria.contains(&clave),
+                Error::CategoriaDuplicada,
+            )?;
+
+            let hermanas = match padre {
+                Some(id_padre) => Some((
+                    id_padre,
+                    self.total_subcategorias.get(id_padre).unwrap_or(0),
+                )),
+                None => None,
+            };
+            let id = self.next_categoria_id;
+            self.next_categoria_id = self
+                .next_categoria_id
+                .checked_add(1)
+                .ok_or(Error::IdOverflow)?;
+
+            self.nombres_categoria.insert(clave, &id);
+            self.categorias.insert(id, &Categoria { nombre, padre });
+            if let Some((id_padre, total)) = hermanas {
+                self.subcategorias.insert((id_padre, total), &id);
+                self.total_subcategorias
+                    .insert(id_padre, &total.saturating_add(1));
+            }
+            Ok(id)
+        }
+
+        /// Lógica interna para cancelar el lanzamiento de un producto en preventa.
+        fn _cancelar_lanzamiento(&mut self, caller: AccountId, id_prod: u32) -> Result<u32, Error> {
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.vendedor == caller, Error::SinPermiso)?;
+            let mut lanzamiento = producto.lanzamiento.ok_or(Error::SinLanzamiento)?;
+            self.ensure(!lanzamiento.cancelado, Error::LanzamientoCancelado)?;
+            self.ensure(
+                self.env().block_timestamp() < lanzamiento.fecha_lanzamiento,
+                Error::PlazoVencido,
+            )?;
+
+            lanzamiento.cancelado = true;
+            producto.lanzamiento = Some(lanzamiento);
+            if producto.activo {
+                self.desindexar_producto(id_prod, &producto);
+            }
+            producto.activo = false;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+
+            self._reembolsar_preventas(id_prod)
+        }
+
+        /// Lógica interna para reembolsar un lote de órdenes de una preventa cancelada.
+        ///
+        /// # Complejidad
+        ///
+        /// O(`MAX_REEMBOLSOS_POR_LLAMADA`), independiente de la cantidad total de órdenes.
+        fn _reembolsar_preventas(&mut self, id_prod: u32) -> Result<u32, Error> {
+            let producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            let lanzamiento = producto.lanzamiento.ok_or(Error::SinLanzamiento)?;
+            self.ensure(lanzamiento.cancelado, Error::EstadoInvalido)?;
+
+            let desde = self.reembolsos_preventa.get(id_prod).unwrap_or(0);
+            let hasta = desde
+                .saturating_add(MAX_REEMBOLSOS_POR_LLAMADA)
+                .min(self.total_ordenes_preventa.get(id_prod).unwrap_or(0));
+            let mut canceladas: u32 = 0;
+            for posicion in desde..hasta {
+                let Some(oid) = self.ordenes_preventa.get((id_prod, posicion)) else {
+                    continue;
+                };
+                let Some(mut orden) = self.ordenes.get(oid) else {
+                    continue;
+                };
+                if orden.estado != Estado::Pendiente {
+                    continue;
+                }
+                let fondos = self.liberar_fondos(oid);
+                self.acreditar(orden.comprador, fondos)?;
+
+                orden.estado = Estado::Cancelada;
+                self.ordenes.insert(oid, &orden);
+                self.registrar_evento(oid, EventoOrden::Cancelada)?;
+                self.cancelaciones_pendientes.remove(oid);
+                self.cerrar_orden(orden.comprador, orden.vendedor);
+                canceladas = canceladas.saturating_add(1);
+            }
+            self.reembolsos_preventa.insert(id_prod, &hasta);
+            Ok(canceladas)
+        }
+
+        /// Lógica interna para buscar productos.
+        ///
+        /// # Complejidad
+        ///
+        /// O(b + c + r) donde b es la cantidad de bloques del índice recorrido, c la cantidad
+        /// de categorías del árbol buscado y r los productos recorridos (a lo sumo
+        /// `MAX_RECORRIDOS_BUSQUEDA`).
+        fn _buscar_productos(
+            &self,
+            filtro: FiltroBusqueda,
+            orden: OrdenBusqueda,
+            despues_de: Option<CursorBusqueda>,
+            cantidad: u32,
+        ) -> Result<PaginaBusqueda, Error> {
+            if let (Some(min), Some(max)) = (filtro.precio_min, filtro.precio_max) {
+                self.ensure(min <= max, Error::ParamInvalido)?;
+            }
+            self.ensure(cantidad > 0, Error::ParamInvalido)?;
+
+            let categorias_validas = match filtro.id_categoria {
+                Some(id_categoria) if filtro.incluir_subcategorias => {
+                    Some(self.arbol_de_categoria(id_categoria)?)
+                }
+                Some(id_categoria) => {
+                    self.ensure(
+                        self.categorias.contains(id_categoria),
+                        Error::CategoriaInexistente,
+                    )?;
+                    Some(Vec::from([id_categoria]))
+                }
+                None => None,
+            };
+
+            let cantidad = cantidad.min(MAX_RESULTADOS_BUSQUEDA) as usize;
+            let texto = filtro.texto.as_ref().map(|t| t.to_lowercase());
+            let cumple = |producto: &Producto| {
+                let promedio = Self::promedio_x100(
+                    self.reputaciones
+                        .get(producto.vendedor)
+                        .unwrap_or_default()
+                        .como_vendedor,
+                );
+                self.esta_activo(producto)
+                    && (!filtro.solo_con_stock || producto.stock > 0)
+                    && filtro.vendedor.is_none_or(|v| producto.vendedor == v)
+                    && filtro.precio_min.is_none_or(|min| producto.precio >= min)
+                    && filtro.precio_max.is_none_or(|max| producto.precio <= max)
+                    && filtro
+                        .reputacion_minima_x100
+                        .is_none_or(|min| promedio >= min)
+                    && categorias_validas
+                        .as_ref()
+                        .is_none_or(|ids| ids.contains(&producto.id_categoria))
+                    && texto
+                        .as_ref()
+                        .is_none_or(|t| producto.nombre.to_lowercase().contains(t.as_str()))
+            };
+
+            if orden == OrdenBusqueda::MejorCalificados && filtro.vendedor.is_none() {
+                return self.buscar_por_calificacion(
+                    despues_de,
+                    cantidad,
+                    filtro.reputacion_minima_x100,
+                    cumple,
+                );
+            }
+
+            let alcance = match (filtro.vendedor, filtro.id_categoria) {
+                (Some(vendedor), _) => AlcanceIndice::Vendedor(vendedor),
+                (None, Some(id_categoria)) => AlcanceIndice::Categoria(id_categoria),
+                (None, None) => AlcanceIndice::Todos,
+            };
+            let (criterio, descendente) = match orden {
+                OrdenBusqueda::PrecioAscendente => (CriterioIndice::Precio, false),
+                OrdenBusqueda::PrecioDescendente => (CriterioIndice::Precio, true),
+                OrdenBusqueda::MasRecientes => (CriterioIndice::Creacion, true),
+                OrdenBusqueda::MejorCalificados => (CriterioIndice::Creacion, false),
+            };
+            let desde = match despues_de {
+                None => None,
+                Some(CursorBusqueda::Indice { clave, id_producto }) => Some((clave, id_producto)),
+                Some(CursorBusqueda::Vendedores { .. }) => return Err(Error::ParamInvalido),
+            };
+
+            let mut productos = Vec::new();
+            let mut recorridos: u32 = 0;
+            let mut ultimo = desde;
+            let completo =
+                self.recorrer_indice((criterio, alcance), desde, descendente, |(clave, pid)| {
+                    if productos.len() >= cantidad || recorridos >= MAX_RECORRIDOS_BUSQUEDA {
+                        return false;
+                    }
+                    recorridos = recorridos.saturating_add(1);
+                    ultimo = Some((clave, pid));
+                    if let Some(producto) = self.productos.get(pid).filter(|p| cumple(p)) {
+                        productos.push((pid, producto));
+                    }
+                    true
+                });
+
+            Ok(PaginaBusqueda {
+                productos,
+                siguiente: ultimo
+                    .filter(|_| !completo)
+                    .map(|(clave, id_producto)| CursorBusqueda::Indice { clave, id_producto }),
+            })
+        }
+
+        /// Helper de `_buscar_productos` para `OrdenBusqueda::MejorCalificados`: recorre los
+        /// vendedores de mayor a menor promedio y los productos de cada uno en el orden de su
+        /// índice, deteniéndose al llegar a un promedio menor que `reputacion_minima_x100`.
+        fn buscar_por_calificacion(
+            &self,
+            despues_de: Option<CursorBusqueda>,
+            cantidad: usize,
+            reputacion_minima_x100: Option<u32>,
+            cumple: impl Fn(&Producto) -> bool,
+        ) -> Result<PaginaBusqueda, Error> {
+            let (desde_promedio, desde_vendedor, desde_producto) = match despues_de {
+                None => (u32::MAX, 0, 0),
+                Some(CursorBusqueda::Vendedores {
+                    promedio_x100,
+                    posicion_vendedor,
+                    posicion_producto,
+                }) => (promedio_x100, posicion_vendedor, posicion_producto),
+                Some(CursorBusqueda::Indice { .. }) => return Err(Error::ParamInvalido),
+            };
+
+            let mut productos = Vec::new();
+            let mut recorridos: u32 = 0;
+            let promedios = (self.posicion_de_promedio(desde_promedio)
+                ..self.total_promedios_con_vendedores)
+                .filter_map(|posicion| self.promedios_con_vendedores.get(posicion))
+                .take_while(|&promedio| reputacion_minima_x100.is_none_or(|min| promedio >= min));
+            for promedio in promedios {
+                let (inicio_vendedor, inicio_producto) = if promedio == desde_promedio {
+                    (desde_vendedor, desde_producto)
+                } else {
+                    (0, 0)
+                };
+                let total_vendedores = self
+                    .total_vendedores_por_promedio
+                    .get(promedio)
+                    .unwrap_or(0);
+                for posicion_vendedor in inicio_vendedor..total_vendedores {
+                    let inicio = if posicion_vendedor == inicio_vendedor {
+                        inicio_producto
+                    } else {
+                        0
+                    };
+                    let vendedor = self
+                        .vendedores_por_promedio
+                        .get((promedio, posicion_vendedor))
+                        .filter(|&v| {
+                            self.posicion_por_promedio.get(v) == Some((promedio, posicion_vendedor))
+                        });
+                    let total = vendedor
+                        .map(|v| self.total_productos_por_vendedor.get(v).unwrap_or(0))
+                        .unwrap_or(0);
+                    // La lectura del vendedor cuenta como un recorrido, para acotar también
+                    // las posiciones vencidas.
+                    for posicion_producto in inicio..total.max(inicio.saturating_add(1)) {
+                        if productos.len() >= cantidad || recorridos >= MAX_RECORRIDOS_BUSQUEDA {
+                            return Ok(PaginaBusqueda {
+                                productos,
+                                siguiente: Some(CursorBusqueda::Vendedores {
+                                    promedio_x100: promedio,
+                                    posicion_vendedor,
+                                    posicion_producto,
+                                }),
+                            });
+                        }
+                        recorridos = recorridos.saturating_add(1);
+                        let Some(vendedor) = vendedor else {
+                            continue;
+                        };
+                        let encontrado = self
+                            .productos_por_vendedor
+                            .get((vendedor, posicion_producto))
+                            .and_then(|pid| self.productos.get(pid).map(|p| (pid, p)))
+                            .filter(|(_, p)| p.vendedor == vendedor && cumple(p));
+                        if let Some(encontrado) = encontrado {
+                            productos.push(encontrado);
+                        }
+                    }
+                }
+            }
+
+            Ok(PaginaBusqueda {
+                productos,
+                siguiente: None,
+            })
+        }
+
+        /// Lógica interna para configurar la reputación mínima exigida por un producto.
+        fn _configurar_requisito_reputacion(
+            &mut self,
+            caller: AccountId,
+            id_prod: u32,
+            requisito: Option<RequisitoReputacion>,
+        ) -> Result<(), Error> {
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.vendedor == caller, Error::SinPermiso)?;
+            if let Some(req) = requisito {
+                self.ensure(req.promedio_minimo_x100 <= 500, Error::ParamInvalido)?;
+            }
+
+            producto.requisito_reputacion = requisito;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+            Ok(())
+        }
+
+        /// Lógica interna para despublicar productos inactivos.
+        fn _depurar_publicaciones(&mut self, ids: Vec<u32>) -> Result<u32, Error> {
+            let plazo = self.plazo_inactividad.ok_or(Error::SinPlazoInactividad)?;
+            let ahora = self.env().block_timestamp();
+            let mut despublicados: u32 = 0;
+            for pid in ids {
+                let Some(mut producto) = self.productos.get(pid) else {
+                    continue;
+                };
+                if self.esta_activo(&producto)
+                    && ahora.saturating_sub(producto.actualizado_en) >= plazo
+                {
+                    self.desindexar_producto(pid, &producto);
+                    producto.activo = false;
+                    producto.actualizado_en = ahora;
+                    self.productos.insert(pid, &producto);
+                    despublicados = despublicados.saturating_add(1);
+                }
+            }
+            Ok(despublicados)
+        }
+
+        /// Lógica interna para depositar fianza como vendedor.
+        fn _depositar_fianza(
+            &mut self,
+            caller: AccountId,
+            monto: Balance,
+        ) -> Result<Balance, Error> {
+            self.ensure(self.rol_de(caller)?.es_vendedor(), Error::SinPermiso)?;
+            self.ensure(monto > 0, Error::ParamInvalido)?;
+
+            let fianza = self
+                .obtener_fianza(caller)
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.fianzas.insert(caller, &fianza);
+            self.total_fianzas = self
+                .total_fianzas
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            Ok(fianza)
+        }
+
+        /// Lógica interna para liberar fianza al saldo retirable del vendedor.
+        fn _retirar_fianza(&mut self, caller: AccountId, monto: Balance) -> Result<(), Error> {
+            self.ensure(monto > 0, Error::ParamInvalido)?;
+            self.ensure(
+                monto <= self.obtener_fianza(caller),
+                Error::FianzaInsuficiente,
+            )?;
+            self.ensure(
+                self.obtener_ordenes_abiertas(caller).1 == 0,
+                Error::OrdenesAbiertas,
+            )?;
+
+            let liberado = self.descontar_fianza(caller, monto);
+            self.acreditar(caller, liberado)
+        }
+
+        /// Lógica interna para compensar al comprador de una orden con la fianza del vendedor.
+        fn _compensar_con_fianza(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            monto: Balance,
+        ) -> Result<Balance, Error> {
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.estado == Estado::Recibido, Error::EstadoInvalido)?;
+            self.ensure(monto > 0, Error::ParamInvalido)?;
+            self.ensure(
+                !self.ordenes_compensadas.get(oid).unwrap_or(false),
+                Error::OrdenYaCompensada,
+            )?;
+            let vendedor = self.cuenta_vigente(orden.vendedor);
+            self.ensure(self.obtener_fianza(vendedor) > 0, Error::FianzaInsuficiente)?;
+
+            let pagado = self.descontar_fianza(vendedor, monto.min(orden.monto_total));
+            self.ordenes_compensadas.insert(oid, &true);
+            self.acreditar(orden.comprador, pagado)?;
+            Ok(pagado)
+        }
+
+        /// Lógica interna para verificar el invariante de fondos del contrato.
+        fn _verificar_invariantes(&self, caller: AccountId) -> Result<EstadoFondos, Error> {
+            self.ensure(caller == self.admin, Error::SinPermiso)?;
+
+            let fondos_retenidos = self.total_fondos_retenidos;
+            let fondos_suscripciones = self.total_fondos_suscripciones;
+            let comprometido = [
+                fondos_suscripciones,
+                self.total_depositos_reservas,
+                self.total_fianzas,
+                self.total_saldos_retirables,
+            ]
+            .iter()
+            .try_fold(fondos_retenidos, |total, monto| total.checked_add(*monto))
+            .ok_or(Error::OverflowAritmetico)?;
+            let balance = self.env().balance();
+
+            Ok(EstadoFondos {
+                fondos_retenidos,
+                fondos_suscripciones,
+                depositos_reservas: self.total_depositos_reservas,
+                fianzas: self.total_fianzas,
+                saldos_retirables: self.total_saldos_retirables,
+                balance,
+                cumple: comprometido <= balance,
+            })
+        }
+
+        /// Lógica interna para configurar el plan de suscripción de un producto.
+        fn _configurar_suscripcion(
+            &mut self,
+            caller: AccountId,
+            id_prod: u32,
+            plan: Option<PlanSuscripcion>,
+        ) -> Result<(), Error> {
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.vendedor == caller, Error::SinPermiso)?;
+            if let Some(p) = plan {
+                self.ensure(
+                    p.periodo_bloques > 0 && p.precio_periodo > 0,
+                    Error::ParamInvalido,
+                )?;
+            }
+            producto.plan_suscripcion = plan;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+            Ok(())
+        }
+
+        /// Lógica interna para comprar un producto con validación de pago.
+        ///
+        /// ## Flujo de Pago
+        /// 1. Valida rol del comprador y parámetros
+        /// 2. Calcula el monto total (precio × cantidad)
+        /// 3. Valida que el monto enviado sea exacto
+        /// 4. Descuenta stock del producto
+        /// 5. Crea la orden con el monto total registrado
+        /// 6. Retiene los fondos en escrow
+        fn _comprar(
+            &mut self,
+            comprador: AccountId,
+            id_prod: u32,
+            cant: u32,
+            monto_enviado: Balance,
+        ) -> Result<u32, Error> {
+            let rol_comprador = self.rol_de(comprador)?;
+            self.ensure(rol_comprador.es_comprador(), Error::SinPermiso)?;
+            self.ensure(cant > 0, Error::ParamInvalido)?;
+
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.validar_compra(comprador, &producto)?;
+            let reserva = self.reservas.get((comprador, id_prod));
+            if let Some(reserva) = reserva {
+                producto.stock = producto
+                    .stock
+                    .checked_add(reserva.cantidad)
+                    .ok_or(Error::OverflowAritmetico)?;
+            }
+            self.ensure(producto.stock >= cant, Error::StockInsuf)?;
+
+            // Calcular monto total requerido
+            let monto_total = producto
+                .precio
+                .checked_mul(cant as Balance)
+                .ok_or(Error::OverflowAritmetico)?;
+
+            self.ensure(monto_enviado >= monto_total, Error::PagoInsuficiente)?;
+            self.ensure(monto_enviado <= monto_total, Error::PagoExcesivo)?;
+
+            producto.stock = producto.stock.checked_sub(cant).ok_or(Error::StockInsuf)?;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+
+            let oid = self.next_order_id;
+            self.next_order_id = self.next_order_id.checked_add(1).ok_or(Error::IdOverflow)?;
+
+            let orden = Orden {
+                comprador,
+                vendedor: producto.vendedor,
+                id_prod,
+                cantidad: cant,
+                estado: Estado::Pendiente,
+                monto_total,
+                destinatario: comprador,
+            };
+
+            self.ordenes.insert(oid, &orden);
+            self.registrar_evento(oid, EventoOrden::Creada)?;
+            self.abrir_orden(orden.comprador, orden.vendedor)?;
+            self.indexar_orden(orden.comprador, oid)?;
+            self.indexar_orden(orden.vendedor, oid)?;
+
+            if let TipoProducto::Digital { .. } = producto.tipo {
+                self.entregas_digitales.insert(
+                    oid,
+                    &EntregaDigital {
+                        bloque_compra: self.env().block_number(),
+                        clave_cifrada: None,
+                        bloque_entrega: None,
+                        disputada: false,
+                    },
+                );
+            }
+
+            self.retener_fondos(oid, monto_total)?;
+
+            if producto.lanzamiento.is_some() {
+                let posicion = self.total_ordenes_preventa.get(id_prod).unwrap_or(0);
+                self.ordenes_preventa.insert((id_prod, posicion), &oid);
+                self.total_ordenes_preventa.insert(
+                    id_prod,
+                    &posicion.checked_add(1).ok_or(Error::OverflowAritmetico)?,
+                );
+            }
+
+            if let Some(reserva) = reserva {
+                self.reservas.remove((comprador, id_prod));
+                self.devolver_deposito(comprador, producto.vendedor, &reserva)?;
+            }
+
+            Ok(oid)
+        }
+
+        /// Lógica interna para comprar un producto con referido.
+        fn _comprar_con_referido(
+            &mut self,
+            comprador: AccountId,
+            id_prod: u32,
+            cant: u32,
+            monto_enviado: Balance,
+            referido: AccountId,
+        ) -> Result<u32, Error> {
+            let producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(
+                referido != comprador && referido != producto.vendedor,
+                Error::ParamInvalido,
+            )?;
+
+            let oid = self._comprar(comprador, id_prod, cant, monto_enviado)?;
+            let posicion = self.total_ordenes_con_referido;
+            self.total_ordenes_con_referido =
+                posicion.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.ordenes_con_referido.insert(posicion, &oid);
+            self.referidos_orden.insert(
+                oid,
+                &ReferidoOrden {
+                    cuenta: referido,
+                    porcentaje: producto.porcentaje_referido,
+                    comision: None,
+                },
+            );
+
+            let mut estadisticas = self.obtener_estadisticas_referido(referido);
+            estadisticas.ordenes_referidas = estadisticas
+                .ordenes_referidas
+                .checked_add(1)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.estadisticas_referidos.insert(referido, &estadisticas);
+            Ok(oid)
+        }
+
+        /// Lógica interna para configurar la comisión de referidos de un producto.
+        fn _configurar_referidos(
+            &mut self,
+            caller: AccountId,
+            id_prod: u32,
+            porcentaje: u8,
+        ) -> Result<(), Error> {
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.vendedor == caller, Error::SinPermiso)?;
+            self.ensure(porcentaje <= MAX_PORCENTAJE_REFERIDO, Error::ParamInvalido)?;
+
+            producto.porcentaje_referido = porcentaje;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+            Ok(())
+        }
+
+        /// Lógica interna para comprar un producto para otra cuenta.
+        fn _comprar_para(
+            &mut self,
+            comprador: AccountId,
+            id_prod: u32,
+            cant: u32,
+            monto_enviado: Balance,
+            destinatario: AccountId,
+        ) -> Result<u32, Error> {
+            let producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(
+                destinatario != comprador && destinatario != producto.vendedor,
+                Error::ParamInvalido,
+            )?;
+
+            let oid = self._comprar(comprador, id_prod, cant, monto_enviado)?;
+            let mut orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            orden.destinatario = destinatario;
+            self.ordenes.insert(oid, &orden);
+            self.indexar_orden(destinatario, oid)?;
+            Ok(oid)
+        }
+
+        /// Lógica interna para reservar stock de un producto.
+        fn _reservar(
+            &mut self,
+            comprador: AccountId,
+            id_prod: u32,
+            cant: u32,
+            monto_enviado: Balance,
+        ) -> Result<(), Error> {
+            let rol_comprador = self.rol_de(comprador)?;
+            self.ensure(rol_comprador.es_comprador(), Error::SinPermiso)?;
+            self.ensure(cant > 0, Error::ParamInvalido)?;
+
+            let producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.validar_compra(comprador, &producto)?;
+            let reemplaza = match self.reservas.get((comprador, id_prod)) {
+                Some(anterior) => {
+                    self.ensure(
+                        self.env().block_number() >= anterior.bloque_vencimiento,
+                        Error::ReservaExistente,
+                    )?;
+                    self._liberar_reserva(comprador, id_prod)?;
+                    true
+                }
+                None => false,
+            };
+
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            self.ensure(producto.stock >= cant, Error::StockInsuf)?;
+
+            let deposito = producto
+                .precio
+                .checked_mul(cant as Balance)
+                .and_then(|total| total.checked_mul(DEPOSITO_RESERVA_PORCENTAJE))
+                .ok_or(Error::OverflowAritmetico)?
+                .checked_div(100)
+                .unwrap_or(0)
+                .max(1);
+            self.ensure(monto_enviado >= deposito, Error::PagoInsuficiente)?;
+            self.ensure(monto_enviado <= deposito, Error::PagoExcesivo)?;
+
+            producto.stock = producto.stock.checked_sub(cant).ok_or(Error::StockInsuf)?;
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+
+            let bloque_vencimiento = self
+                .env()
+                .block_number()
+                .checked_add(DURACION_RESERVA_BLOQUES)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.reservas.insert(
+                (comprador, id_prod),
+                &Reserva {
+                    cantidad: cant,
+                    deposito,
+                    bloque_vencimiento,
+                },
+            );
+            if !reemplaza {
+                self.indexar_reserva(comprador, id_prod)?;
+            }
+            self.total_depositos_reservas = self
+                .total_depositos_reservas
+                .checked_add(deposito)
+                .ok_or(Error::OverflowAritmetico)?;
+            Ok(())
+        }
+
+        /// Lógica interna para liberar una reserva vencida.
+        fn _liberar_reserva(&mut self, comprador: AccountId, id_prod: u32) -> Result<(), Error> {
+            let reserva = self
+                .reservas
+                .get((comprador, id_prod))
+                .ok_or(Error::ReservaInexistente)?;
+            self.ensure(
+                self.env().block_number() >= reserva.bloque_vencimiento,
+                Error::PlazoNoVencido,
+            )?;
+
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            producto.stock = producto
+                .stock
+                .checked_add(reserva.cantidad)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.productos.insert(id_prod, &producto);
+            self.reservas.remove((comprador, id_prod));
+
+            self.devolver_deposito(comprador, producto.vendedor, &reserva)
+        }
+
+        /// Lógica interna para suscribirse a un producto.
+        fn _suscribirse(
+            &mut self,
+            comprador: AccountId,
+            id_prod: u32,
+            periodos: u32,
+            monto_enviado: Balance,
+        ) -> Result<u32, Error> {
+            let rol_comprador = self.rol_de(comprador)?;
+            self.ensure(rol_comprador.es_comprador(), Error::SinPermiso)?;
+            self.ensure(periodos > 0, Error::ParamInvalido)?;
+
+            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
+            let plan = producto.plan_suscripcion.ok_or(Error::SinPlanSuscripcion)?;
+            self.validar_compra(comprador, &producto)?;
+
+            let monto_total = plan
+                .precio_periodo
+                .checked_mul(periodos as Balance)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.ensure(monto_enviado >= monto_total, Error::PagoInsuficiente)?;
+            self.ensure(monto_enviado <= monto_total, Error::PagoExcesivo)?;
+
+            let sid = self.next_suscripcion_id;
+            self.next_suscripcion_id = self
+                .next_suscripcion_id
+                .checked_add(1)
+                .ok_or(Error::IdOverflow)?;
+
+            self.suscripciones.insert(
+                sid,
+                &Suscripcion {
+                    comprador,
+                    vendedor: producto.vendedor,
+                    id_prod,
+                    periodo_bloques: plan.periodo_bloques,
+                    precio_periodo: plan.precio_periodo,
+                    periodos_pagados: periodos,
+                    periodos_cobrados: 0,
+                    bloque_inicio: self.env().block_number(),
+                    activa: true,
+                },
+            );
+            self.retener_fondos_suscripcion(sid, monto_total)?;
+            self.abrir_orden(comprador, producto.vendedor)?;
+            self.indexar_suscripcion(comprador, sid)?;
+            self.indexar_suscripcion(producto.vendedor, sid)?;
+
+            producto.actualizado_en = self.env().block_timestamp();
+            self.productos.insert(id_prod, &producto);
+            Ok(sid)
+        }
+
+        /// Lógica interna para cobrar los períodos iniciados de una suscripción.
+        fn _cobrar_suscripcion(&mut self, caller: AccountId, sid: u32) -> Result<u32, Error> {
+            let mut suscripcion = self
+                .suscripciones
+                .get(sid)
+                .ok_or(Error::SuscripcionInexistente)?;
+            self.ensure(suscripcion.vendedor == caller, Error::SinPermiso)?;
+            self.ensure(suscripcion.activa, Error::SuscripcionFinalizada)?;
+
+            let (nuevos, monto) = self.generar_ordenes_suscripcion(sid, &mut suscripcion)?;
+            self.finalizar_si_cobrada(sid, &mut suscripcion);
+            self.suscripciones.insert(sid, &suscripcion);
+
+            self.acreditar(suscripcion.vendedor, monto)?;
+            Ok(nuevos)
+        }
+
+        /// Lógica interna para cancelar una suscripción y reembolsar los períodos futuros.
+        fn _cancelar_suscripcion(&mut self, caller: AccountId, sid: u32) -> Result<(), Error> {
+            let mut suscripcion = self
+                .suscripciones
+                .get(sid)
+                .ok_or(Error::SuscripcionInexistente)?;
+            self.ensure(suscripcion.comprador == caller, Error::SinPermiso)?;
+            self.ensure(suscripcion.activa, Error::SuscripcionFinalizada)?;
+
+            let iniciados = self.periodos_iniciados(&suscripcion);
+            let reembolso = suscripcion
+                .precio_periodo
+                .checked_mul(suscripcion.periodos_pagados.saturating_sub(iniciados) as Balance)
+                .ok_or(Error::OverflowAritmetico)?;
+            suscripcion.periodos_pagados = iniciados;
+            self.descontar_fondos_suscripcion(sid, reembolso);
+
+            let (_, monto_vendedor) = self.generar_ordenes_suscripcion(sid, &mut suscripcion)?;
+            self.finalizar_si_cobrada(sid, &mut suscripcion);
+            self.suscripciones.insert(sid, &suscripcion);
+
+            self.acreditar(suscripcion.vendedor, monto_vendedor)?;
+            self.acreditar(suscripcion.comprador, reembolso)?;
+            Ok(())
+        }
+
+        /// Lógica interna para bloquear a un comprador.
+        fn _bloquear_comprador(
+            &mut self,
+            vendedor: AccountId,
+            comprador: AccountId,
+        ) -> Result<(), Error> {
+            let rol_vendedor = self.rol_de(vendedor)?;
+            self.ensure(rol_vendedor.es_vendedor(), Error::SinPermiso)?;
+            self.ensure(vendedor != comprador, Error::ParamInvalido)?;
+            if !self.compradores_bloqueados.contains((vendedor, comprador)) {
+                self.agregar_bloqueo(vendedor, comprador)?;
+            }
+            Ok(())
+        }
+
+        /// Lógica interna para desbloquear a un comprador.
+        fn _desbloquear_comprador(
+            &mut self,
+            vendedor: AccountId,
+            comprador: AccountId,
+        ) -> Result<(), Error> {
+            let rol_vendedor = self.rol_de(vendedor)?;
+            self.ensure(rol_vendedor.es_vendedor(), Error::SinPermiso)?;
+            self.compradores_bloqueados.remove((vendedor, comprador));
+            Ok(())
+        }
+
+        /// Lógica interna para agregar un producto a favoritos.
+        fn _agregar_favorito(&mut self, usuario: AccountId, id_prod: u32) -> Result<(), Error> {
+            let rol = self.rol_de(usuario)?;
+            self.ensure(rol.es_comprador(), Error::SinPermiso)?;
+            self.ensure(self.productos.contains(id_prod), Error::ProdInexistente)?;
+
+            let mut lista = self.favoritos.get(usuario).unwrap_or_default();
+            self.ensure(!lista.contains(&id_prod), Error::YaEsFavorito)?;
+            self.ensure(lista.len() < MAX_FAVORITOS, Error::FavoritosLlenos)?;
+
+            let conteo = self
+                .obtener_cantidad_favoritos(id_prod)
+                .checked_add(1)
+                .ok_or(Error::OverflowAritmetico)?;
+
+            lista.push(id_prod);
+            self.favoritos.insert(usuario, &lista);
+            self.favoritos_por_producto.insert(id_prod, &conteo);
+            Ok(())
+        }
+
+        /// Lógica interna para quitar un producto de favoritos.
+        fn _quitar_favorito(&mut self, usuario: AccountId, id_prod: u32) -> Result<(), Error> {
+            let mut lista = self.favoritos.get(usuario).unwrap_or_default();
+            let pos = lista
+                .iter()
+                .position(|id| *id == id_prod)
+                .ok_or(Error::ParamInvalido)?;
+            lista.remove(pos);
+
+            if lista.is_empty() {
+                self.favoritos.remove(usuario);
+            } else {
+                self.favoritos.insert(usuario, &lista);
+            }
+            self.descontar_favorito(id_prod);
+            Ok(())
+        }
+
+        /// Lógica interna para marcar una orden como enviada.
+        fn _marcar_enviado(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let mut orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.vendedor == caller, Error::SinPermiso)?;
+
+            if orden.estado == Estado::Cancelada {
+                return Err(Error::OrdenCancelada);
+            }
+            self.ensure(orden.estado == Estado::Pendiente, Error::EstadoInvalido)?;
+            self.ensure(
+                !self.entregas_digitales.contains(oid),
+                Error::ProductoDigital,
+            )?;
+            if let Some(lanzamiento) = self
+                .productos
+                .get(orden.id_prod)
+                .and_then(|p| p.lanzamiento)
+            {
+                self.ensure(!lanzamiento.cancelado, Error::LanzamientoCancelado)?;
+                self.ensure(
+                    self.env().block_timestamp() >= lanzamiento.fecha_lanzamiento,
+                    Error::LanzamientoPendiente,
+                )?;
+            }
+
+            orden.estado = Estado::Enviado;
+            self.ordenes.insert(oid, &orden);
+            self.registrar_evento(oid, EventoOrden::Enviada)?;
+            Ok(())
+        }
+
+        /// Lógica interna para entregar la clave de una orden digital.
+        fn _entregar_clave(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            clave_cifrada: Vec<u8>,
+        ) -> Result<(), Error> {
+            let mut orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.vendedor == caller, Error::SinPermiso)?;
+            let mut entrega = self
+                .entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)?;
+            self.ensure(orden.estado == Estado::Pendiente, Error::EstadoInvalido)?;
+            if let Some(lanzamiento) = self
+                .productos
+                .get(orden.id_prod)
+                .and_then(|p| p.lanzamiento)
+            {
+                self.ensure(!lanzamiento.cancelado, Error::LanzamientoCancelado)?;
+            }
+            self.ensure(
+                !clave_cifrada.is_empty() && clave_cifrada.len() <= MAX_CLAVE_LEN,
+                Error::ParamInvalido,
+            )?;
+            let ahora = self.env().block_number();
+            self.ensure(
+                ahora
+                    <= entrega
+                        .bloque_compra
+                        .saturating_add(PLAZO_ENTREGA_DIGITAL_BLOQUES),
+                Error::PlazoVencido,
+            )?;
+
+            entrega.clave_cifrada = Some(clave_cifrada);
+            entrega.bloque_entrega = Some(ahora);
+            self.entregas_digitales.insert(oid, &entrega);
+
+            orden.estado = Estado::Enviado;
+            self.ordenes.insert(oid, &orden);
+            self.registrar_evento(oid, EventoOrden::Enviada)?;
+            Ok(())
+        }
+
+        /// Lógica interna para completar una orden digital vencida la ventana de disputa.
+        fn _completar_orden_digital(&mut self, oid: u32) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let entrega = self
+                .entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)?;
+            self.ensure(orden.estado == Estado::Enviado, Error::EstadoInvalido)?;
+            self.ensure(
+                !self.cancelaciones_pendientes.contains(oid),
+                Error::CancelacionYaPendiente,
+            )?;
+            self.ensure(!entrega.disputada, Error::OrdenEnDisputa)?;
+            self.ensure(
+                self.env().block_number() > Self::fin_ventana_disputa(&entrega),
+                Error::PlazoNoVencido,
+            )?;
+
+            self.completar_orden(oid, orden)
+        }
+
+        /// Lógica interna para disputar una orden digital.
+        fn _disputar_orden_digital(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.destinatario == caller, Error::SinPermiso)?;
+            let mut entrega = self
+                .entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)?;
+            self.ensure(orden.estado == Estado::Enviado, Error::EstadoInvalido)?;
+            self.ensure(!entrega.disputada, Error::OrdenEnDisputa)?;
+            self.ensure(
+                self.env().block_number() <= Self::fin_ventana_disputa(&entrega),
+                Error::PlazoVencido,
+            )?;
+
+            entrega.disputada = true;
+            self.entregas_digitales.insert(oid, &entrega);
+            self.registrar_evento(oid, EventoOrden::Disputada)
+        }
+
+        /// Lógica interna para resolver la disputa de una orden digital.
+        fn _resolver_disputa_digital(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            reembolsar: bool,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let mut entrega = self
+                .entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)?;
+            self.ensure(orden.estado == Estado::Enviado, Error::EstadoInvalido)?;
+            self.ensure(entrega.disputada, Error::DisputaInexistente)?;
+            let es_admin = caller == self.admin && caller != orden.vendedor;
+            self.ensure(
+                es_admin
+                    || (reembolsar && caller == orden.vendedor)
+                    || (!reembolsar && caller == orden.destinatario),
+                Error::SinPermiso,
+            )?;
+
+            entrega.disputada = false;
+            self.entregas_digitales.insert(oid, &entrega);
+            if reembolsar {
+                self.reembolsar_orden(oid, orden)
+            } else {
+                self.completar_orden(oid, orden)
+            }
+        }
+
+        /// Helper que calcula el último bloque de la ventana de disputa de una orden digital.
+        fn fin_ventana_disputa(entrega: &EntregaDigital) -> BlockNumber {
+            entrega
+                .bloque_entrega
+                .unwrap_or(entrega.bloque_compra)
+                .saturating_add(VENTANA_DISPUTA_DIGITAL_BLOQUES)
+        }
+
+        /// Lógica interna para reembolsar una orden digital cuya clave no se entregó a tiempo.
+        fn _reclamar_reembolso_digital(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.comprador == caller, Error::SinPermiso)?;
+            let entrega = self
+                .entregas_digitales
+                .get(oid)
+                .ok_or(Error::ProductoNoDigital)?;
+            self.ensure(orden.estado == Estado::Pendiente, Error::EstadoInvalido)?;
+            self.ensure(
+                self.env().block_number()
+                    > entrega
+                        .bloque_compra
+                        .saturating_add(PLAZO_ENTREGA_DIGITAL_BLOQUES),
+                Error::PlazoNoVencido,
+            )?;
+
+            self.reembolsar_orden(oid, orden)
+        }
+
+        /// Lógica interna para marcar una orden como recibida y liberar fondos al vendedor.
+        ///
+        /// ## Flujo de liberación de fondos
+        /// 1. Valida permisos y estado de la orden
+        /// 2. Actualiza el estado de la orden y limpia datos internos
+        /// 3. Acredita los fondos al saldo retirable del vendedor
+        fn _marcar_recibido(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            self.ensure(orden.destinatario == caller, Error::SinPermiso)?;
+
+            if orden.estado == Estado::Cancelada {
+                return Err(Error::OrdenCancelada);
+            }
+            self.ensure(orden.estado == Estado::Enviado, Error::EstadoInvalido)?;
+
+            self.completar_orden(oid, orden)
+        }
+
+        /// Helper que marca una orden como recibida y libera los fondos al vendedor.
+        ///
+        /// Los llamadores deben validar permisos y estado antes de invocarlo.
+        fn completar_orden(&mut self, oid: u32, mut orden: Orden) -> Result<(), Error> {
+            let fondos = self.liberar_fondos(oid);
+
+            orden.estado = Estado::Recibido;
+            self.ordenes.insert(oid, &orden);
+            self.registrar_evento(oid, EventoOrden::Recibida)?;
+            self.cancelaciones_pendientes.remove(oid);
+            self.cerrar_orden(orden.comprador, orden.vendedor);
+
+            let vendedor = orden.vendedor;
+            let mut pago_vendedor = fondos;
+
+            if let Some(mut referido) = self.referidos_orden.get(oid) {
+                let comision = fondos
+                    .checked_mul(Balance::from(referido.porcentaje))
+                    .ok_or(Error::OverflowAritmetico)?
+                    .checked_div(100)
+                    .unwrap_or(0);
+                pago_vendedor = fondos
+                    .checked_sub(comision)
+                    .ok_or(Error::OverflowAritmetico)?;
+                let cuenta_referido = self.cuenta_vigente(referido.cuenta);
+                let mut estadisticas = self.obtener_estadisticas_referido(cuenta_referido);
+                estadisticas.ganancias = estadisticas
+                    .ganancias
+                    .checked_add(comision)
+                    .ok_or(Error::OverflowAritmetico)?;
+                estadisticas.ordenes_completadas = estadisticas
+                    .ordenes_completadas
+                    .checked_add(1)
+                    .ok_or(Error::OverflowAritmetico)?;
+                self.estadisticas_referidos
+                    .insert(cuenta_referido, &estadisticas);
+                self.actualizar_mejores_referidos(cuenta_referido, estadisticas.ganancias);
+                referido.comision = Some(comision);
+                self.referidos_orden.insert(oid, &referido);
+                self.acreditar(cuenta_referido, comision)?;
+            }
+
+            self.acreditar(vendedor, pago_vendedor)
+        }
+
+        /// Lógica interna para solicitar la cancelación de una orden.
+        fn _solicitar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+
+            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
+
+            self.ensure(
+                caller == orden.comprador || caller == orden.vendedor,
+                Error::SinPermiso,
+            )?;
+
+            self.ensure(
+                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
+                Error::EstadoInvalido,
+            )?;
+
+            self.ensure(
+                !self.cancelaciones_pendientes.contains(oid),
+                Error::CancelacionYaPendiente,
+            )?;
+
+            self.ensure(
+                caller != orden.comprador || !self.esta_bloqueado(orden.vendedor, caller),
+                Error::CompradorBloqueado,
+            )?;
+
+            self.cancelaciones_pendientes.insert(
+                oid,
+                &CancelacionPendiente {
+                    solicitante: caller,
+                },
+            );
+            self.registrar_evento(
+                oid,
+                EventoOrden::CancelacionSolicitada {
+                    solicitante: caller,
+                },
+            )?;
+            Ok(())
+        }
+
+        /// Lógica interna para aceptar la cancelación de una orden y devolver fondos.
+        ///
+        /// ## Flujo de Devolución de Fondos (patrón checks-effects-interactions)
+        /// 1. **Checks**: Valida permisos y estado de la cancelación
+        /// 2. **Effects**: Restaura stock, actualiza estado de la orden, limpia datos
+        /// 3. Acredita los fondos al saldo retirable del comprador
+        fn _aceptar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let cancelacion = self
+                .cancelaciones_pendientes
+                .get(oid)
+                .ok_or(Error::CancelacionInexistente)?;
+
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+
+            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
+
+            self.ensure(
+                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
+                Error::EstadoInvalido,
+            )?;
+
+            self.ensure(
+                caller != cancelacion.solicitante,
+                Error::SolicitanteCancelacion,
+            )?;
+
+            self.ensure(
+                self.es_otro_participante(caller, &orden, cancelacion.solicitante),
+                Error::SinPermiso,
+            )?;
+
+            self.reembolsar_orden(oid, orden)
+        }
+
+        /// Helper que cancela una orden, restaura el stock y devuelve los fondos al comprador.
+        ///
+        /// Los llamadores deben validar permisos y estado antes de invocarlo.
+        fn reembolsar_orden(&mut self, oid: u32, orden: Orden) -> Result<(), Error> {
+            // Effects: actualizar estado interno primero
+            let fondos = self.liberar_fondos(oid);
+
+            let mut producto = self
+                .productos
+                .get(orden.id_prod)
+                .ok_or(Error::ProdInexistente)?;
+            producto.stock = producto
+                .stock
+                .checked_add(orden.cantidad)
+                .ok_or(Error::StockOverflow)?;
+            self.productos.insert(orden.id_prod, &producto);
+
+            let comprador = orden.comprador;
+            self.cerrar_orden(orden.comprador, orden.vendedor);
+
+            self.ordenes.insert(
+                oid,
+                &Orden {
+                    estado: Estado::Cancelada,
+                    ..orden
+                },
+            );
+            self.registrar_evento(oid, EventoOrden::Cancelada)?;
+
+            self.cancelaciones_pendientes.remove(oid);
+
+            self.acreditar(comprador, fondos)
+        }
+
+        /// Lógica interna para rechazar la cancelación de una orden.
+        fn _rechazar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
+            let cancelacion = self
+                .cancelaciones_pendientes
+                .get(oid)
+                .ok_or(Error::CancelacionInexistente)?;
+
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+
+            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
+
+            self.ensure(
+                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
+                Error::EstadoInvalido,
+            )?;
+
+            self.ensure(
+                caller != cancelacion.solicitante,
+                Error::SolicitanteCancelacion,
+            )?;
+
+            self.ensure(
+                self.es_otro_participante(caller, &orden, cancelacion.solicitante),
+                Error::SinPermiso,
+            )?;
+
+            self.cancelaciones_pendientes.remove(oid);
+            self.registrar_evento(oid, EventoOrden::CancelacionRechazada)?;
+
+            Ok(())
+        }
+
+        /// Helper para validar condiciones.
+        ///
+        /// Esta función auxiliar facilita la validación de condiciones en el contrato,
+        /// haciendo que el código sea más legible y expresivo.
+        ///
+        /// # Argumentos
+        ///
+        /// * `cond` - La condición booleana a verificar.
+        /// * `err` - El error a devolver si la condición es falsa.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve `Ok(())` si la condición es verdadera, o `Err(err)` si es falsa.
+        fn ensure(&self, cond: bool, err: Error) -> Result<(), Error> {
+            if cond {
+                Ok(())
+            } else {
+                Err(err)
+            }
+        }
+
+        /// Helper que obtiene el rol de un usuario.
+        ///
+        /// # Argumentos
+        ///
+        /// * `quien` - La `AccountId` del usuario cuyo rol se desea obtener.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::SinRegistro` si el usuario no está registrado.
+        ///
+        /// # Retorno
+        ///
+        /// Devuelve el `Rol` del usuario si está registrado.
+        fn rol_de(&self, quien: AccountId) -> Result<Rol, Error> {
+            self.roles.get(quien).ok_or(Error::SinRegistro)
+        }
+
+        /// Helper que descuenta un favorito del contador de un producto.
+        fn descontar_favorito(&mut self, id_prod: u32) {
+            let conteo = self.obtener_cantidad_favoritos(id_prod).saturating_sub(1);
+            if conteo == 0 {
+                self.favoritos_por_producto.remove(id_prod);
+            } else {
+                self.favoritos_por_producto.insert(id_prod, &conteo);
+            }
+        }
+
+        /// Helper que devuelve la cuenta vigente de una identidad, siguiendo las migraciones
+        /// aceptadas desde `cuenta`. Las órdenes cerradas conservan la cuenta que participó,
+        /// así que calificaciones, créditos y compensaciones se resuelven con esta función.
+        ///
+        /// # Complejidad
+        ///
+        /// O(m) donde m es la cantidad de migraciones sucesivas desde `cuenta`.
+        fn cuenta_vigente(&self, cuenta: AccountId) -> AccountId {
+            let mut vigente = cuenta;
+            while let Some(nueva) = self.cuentas_migradas.get(vigente) {
+                vigente = nueva;
+            }
+            vigente
+        }
+
+        /// Helper que valida que una cuenta pueda recibir la identidad de otra.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::CuentaDestinoInvalida` si la cuenta destino es la misma, ya está
+        /// registrada, ya tiene reputación o es la cuenta anterior de una migración en curso.
+        fn validar_cuenta_destino(
+            &self,
+            anterior: AccountId,
+            nueva: AccountId,
+        ) -> Result<(), Error> {
+            self.ensure(
+                nueva != anterior
+                    && !self.roles.contains(nueva)
+                    && !self.reputaciones.contains(nueva)
+                    && !self.origenes_migracion.contains(nueva),
+                Error::CuentaDestinoInvalida,
+            )
+        }
+
+        /// Helper que verifica que una cuenta no esté recibiendo una migración en curso.
+        ///
+        /// # Errores
+        ///
+        /// Devuelve `Error::MigracionEnCurso` si la cuenta aceptó una migración cuyas
+        /// referencias todavía se trasladan.
+        fn sin_migracion_en_curso(&self, cuenta: AccountId) -> Result<(), Error> {
+            self.ensure(
+                !self.migraciones_en_curso.contains(cuenta),
+                Error::MigracionEnCurso,
+            )
+        }
+
+        /// Helper que agrega una orden al índice de órdenes de una cuenta.
+        fn indexar_orden(&mut self, cuenta: AccountId, oid: u32) -> Result<(), Error> {
+            let total = self.total_ordenes_de_cuenta.get(cuenta).unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.ordenes_de_cuenta.insert((cuenta, total), &oid);
+            self.total_ordenes_de_cuenta.insert(cuenta, &nuevo_total);
+            Ok(())
+        }
+
+        /// Helper que agrega una suscripción al índice de suscripciones de una cuenta.
+        fn indexar_suscripcion(&mut self, cuenta: AccountId, sid: u32) -> Result<(), Error> {
+            let total = self.total_suscripciones_de_cuenta.get(cuenta).unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.suscripciones_de_cuenta.insert((cuenta, total), &sid);
+            self.total_suscripciones_de_cuenta
+                .insert(cuenta, &nuevo_total);
+            Ok(())
+        }
+
+        /// Helper que agrega un producto al índice de reservas de una cuenta.
+        fn indexar_reserva(&mut self, cuenta: AccountId, id_prod: u32) -> Result<(), Error> {
+            let total = self.total_reservas_de_cuenta.get(cuenta).unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.reservas_de_cuenta.insert((cuenta, total), &id_prod);
+            self.total_reservas_de_cuenta.insert(cuenta, &nuevo_total);
+            Ok(())
+        }
+
+        /// Helper que registra un bloqueo nuevo de un vendedor a un comprador.
+        fn agregar_bloqueo(
+            &mut self,
+            vendedor: AccountId,
+            comprador: AccountId,
+        ) -> Result<(), Error> {
+            let total = self
+                .total_bloqueados_por_vendedor
+                .get(vendedor)
+                .unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            let recibidos = self
+                .total_bloqueadores_de_comprador
+                .get(comprador)
+                .unwrap_or(0);
+            let nuevos_recibidos = recibidos.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.compradores_bloqueados
+                .insert((vendedor, comprador), &true);
+            self.bloqueados_por_vendedor
+                .insert((vendedor, total), &comprador);
+            self.total_bloqueados_por_vendedor
+                .insert(vendedor, &nuevo_total);
+            self.bloqueadores_de_comprador
+                .insert((comprador, recibidos), &vendedor);
+            self.total_bloqueadores_de_comprador
+                .insert(comprador, &nuevos_recibidos);
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta un bloqueo vigente de la cuenta anterior.
+        fn mover_bloqueo(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            comprador: AccountId,
+        ) -> Result<(), Error> {
+            if self
+                .compradores_bloqueados
+                .take((anterior, comprador))
+                .is_none()
+            {
+                return Ok(());
+            }
+            if comprador != nueva && !self.compradores_bloqueados.contains((nueva, comprador)) {
+                self.agregar_bloqueo(nueva, comprador)?;
+            }
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta un bloqueo vigente que un vendedor le puso a
+        /// la cuenta anterior.
+        fn mover_bloqueo_recibido(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            vendedor: AccountId,
+        ) -> Result<(), Error> {
+            if self
+                .compradores_bloqueados
+                .take((vendedor, anterior))
+                .is_none()
+            {
+                return Ok(());
+            }
+            if vendedor != nueva && !self.compradores_bloqueados.contains((vendedor, nueva)) {
+                self.agregar_bloqueo(vendedor, nueva)?;
+            }
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta un producto publicado por la cuenta anterior.
+        fn mover_producto(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            pid: u32,
+        ) -> Result<(), Error> {
+            let Some(mut producto) = self.productos.get(pid) else {
+                return Ok(());
+            };
+            if producto.vendedor != anterior {
+                return Ok(());
+            }
+            let total = self.total_productos_por_vendedor.get(nueva).unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            let indexado = producto.activo;
+            producto.activo = self.esta_activo(&producto);
+            if indexado && !producto.activo {
+                self.desindexar_producto(pid, &producto);
+            }
+            producto.pausas_vendedor = self.pausas_vendedor.get(nueva).unwrap_or(0);
+            producto.vendedor = nueva;
+            if producto.activo {
+                for (criterio, par) in Self::claves_de_busqueda(pid, &producto) {
+                    self.insertar_en_indice((criterio, AlcanceIndice::Vendedor(nueva)), par)?;
+                    self.quitar_de_indice((criterio, AlcanceIndice::Vendedor(anterior)), par);
                 }
             }
-            lista
+            self.actualizar_promedio_vendedor(nueva)?;
+            self.productos.insert(pid, &producto);
+            self.productos_por_vendedor.insert((nueva, total), &pid);
+            self.total_productos_por_vendedor
+                .insert(nueva, &nuevo_total);
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta la participación de la cuenta anterior en
+        /// una orden abierta (`Pendiente` o `Enviado`).
+        fn mover_orden(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            oid: u32,
+        ) -> Result<(), Error> {
+            let Some(mut orden) = self.ordenes.get(oid) else {
+                return Ok(());
+            };
+            let abierta = orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado;
+            if !abierta
+                || (orden.comprador != anterior
+                    && orden.vendedor != anterior
+                    && orden.destinatario != anterior)
+            {
+                return Ok(());
+            }
+            if orden.comprador == anterior {
+                orden.comprador = nueva;
+                self.trasladar_abierta(anterior, nueva, true)?;
+            }
+            if orden.vendedor == anterior {
+                orden.vendedor = nueva;
+                self.trasladar_abierta(anterior, nueva, false)?;
+            }
+            if orden.destinatario == anterior {
+                orden.destinatario = nueva;
+            }
+            self.ordenes.insert(oid, &orden);
+            self.indexar_orden(nueva, oid)?;
+
+            if let Some(mut cancelacion) = self.cancelaciones_pendientes.get(oid) {
+                if cancelacion.solicitante == anterior {
+                    cancelacion.solicitante = nueva;
+                    self.cancelaciones_pendientes.insert(oid, &cancelacion);
+                }
+            }
+            if let Some(enviados) = self.mensajes_enviados.take((oid, anterior)) {
+                let previos = self.mensajes_enviados.get((oid, nueva)).unwrap_or(0);
+                self.mensajes_enviados
+                    .insert((oid, nueva), &previos.saturating_add(enviados));
+            }
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta una reserva de la cuenta anterior.
+        ///
+        /// Si la nueva cuenta ya reservó el mismo producto, la reserva anterior queda donde
+        /// está: al liberarse, su depósito se acredita igualmente a la nueva cuenta.
+        fn mover_reserva(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            pid: u32,
+        ) -> Result<(), Error> {
+            if self.reservas.contains((nueva, pid)) {
+                return Ok(());
+            }
+            if let Some(reserva) = self.reservas.take((anterior, pid)) {
+                self.reservas.insert((nueva, pid), &reserva);
+                self.indexar_reserva(nueva, pid)?;
+            }
+            Ok(())
+        }
+
+        /// Helper que traslada a la nueva cuenta la participación de la cuenta anterior en
+        /// una suscripción activa.
+        fn mover_suscripcion(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            sid: u32,
+        ) -> Result<(), Error> {
+            let Some(mut suscripcion) = self.suscripciones.get(sid) else {
+                return Ok(());
+            };
+            if !suscripcion.activa
+                || (suscripcion.comprador != anterior && suscripcion.vendedor != anterior)
+            {
+                return Ok(());
+            }
+            if suscripcion.comprador == anterior {
+                suscripcion.comprador = nueva;
+                self.trasladar_abierta(anterior, nueva, true)?;
+            }
+            if suscripcion.vendedor == anterior {
+                suscripcion.vendedor = nueva;
+                self.trasladar_abierta(anterior, nueva, false)?;
+            }
+            self.suscripciones.insert(sid, &suscripcion);
+            self.indexar_suscripcion(nueva, sid)
+        }
+
+        /// Helper que traslada una orden (o suscripción) abierta del contador de la cuenta
+        /// anterior al de la nueva, como compra o como venta.
+        fn trasladar_abierta(
+            &mut self,
+            anterior: AccountId,
+            nueva: AccountId,
+            como_comprador: bool,
+        ) -> Result<(), Error> {
+            let (compras, ventas) = self.obtener_ordenes_abiertas(nueva);
+            let nuevas = if como_comprador {
+                (
+                    compras.checked_add(1).ok_or(Error::OverflowAritmetico)?,
+                    ventas,
+                )
+            } else {
+                (
+                    compras,
+                    ventas.checked_add(1).ok_or(Error::OverflowAritmetico)?,
+                )
+            };
+            let (compras, ventas) = self.obtener_ordenes_abiertas(anterior);
+            let anteriores = if como_comprador {
+                (compras.saturating_sub(1), ventas)
+            } else {
+                (compras, ventas.saturating_sub(1))
+            };
+            self.ordenes_abiertas.insert(nueva, &nuevas);
+            self.ordenes_abiertas.insert(anterior, &anteriores);
+            Ok(())
+        }
+
+        /// Helper que valida los datos de un perfil antes de guardarlos.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ParamInvalido` si el nombre o la URI exceden la longitud máxima,
+        ///   o si el nombre tiene espacios al inicio o al final.
+        /// - `Error::NombreEnUso` si el nombre visible pertenece a otra cuenta.
+        fn validar_perfil(
+            &self,
+            caller: AccountId,
+            nombre_visible: &str,
+            uri_metadata: &str,
+        ) -> Result<(), Error> {
+            self.ensure(
+                nombre_visible.len() <= MAX_NOMBRE_LEN
+                    && nombre_visible.trim() == nombre_visible
+                    && uri_metadata.len() <= MAX_URI_LEN,
+                Error::ParamInvalido,
+            )?;
+            if !nombre_visible.is_empty() {
+                if let Some(duenio) = self
+                    .nombres_visibles
+                    .get(Self::normalizar_nombre(nombre_visible))
+                {
+                    self.ensure(duenio == caller, Error::NombreEnUso)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Helper que normaliza un nombre visible para comparar unicidad sin distinguir mayúsculas.
+        fn normalizar_nombre(nombre: &str) -> String {
+            nombre.to_lowercase()
+        }
+
+        /// Helper que normaliza el nombre de una categoría para comparar unicidad
+        /// sin distinguir mayúsculas, tildes ni espacios al inicio o al final.
+        fn normalizar_categoria(nombre: &str) -> String {
+            nombre
+                .trim()
+                .to_lowercase()
+                .chars()
+                .map(|c| match c {
+                    'á' | 'à' | 'ä' => 'a',
+                    'é' | 'è' | 'ë' => 'e',
+                    'í' | 'ì' | 'ï' => 'i',
+                    'ó' | 'ò' | 'ö' => 'o',
+                    'ú' | 'ù' | 'ü' => 'u',
+                    otro => otro,
+                })
+                .collect()
         }
 
-        /// Lógica interna para listar todas las órdenes.
-        ///
-        /// Itera sobre todos los IDs de órdenes desde 1 hasta `next_order_id`.
+        /// Helper que devuelve los IDs de una categoría y de todas sus descendientes.
         ///
-        /// # Complejidad
+        /// # Errores
         ///
-        /// O(n) donde n es el número total de órdenes creadas.
-        fn _listar_todas_ordenes(&self) -> Vec<(u32, Orden)> {
-            let mut lista = Vec::new();
-            for i in 1..self.next_order_id {
-                if let Some(o) = self.ordenes.get(i) {
-                    lista.push((i, o));
-                }
+        /// Devuelve `Error::CategoriaInexistente` si la categoría no está registrada.
+        fn arbol_de_categoria(&self, id_categoria: u32) -> Result<Vec<u32>, Error> {
+            self.ensure(
+                self.categorias.contains(id_categoria),
+                Error::CategoriaInexistente,
+            )?;
+            let mut ids = Vec::from([id_categoria]);
+            let mut i = 0;
+            while let Some(actual) = ids.get(i).copied() {
+                let total = self.total_subcategorias.get(actual).unwrap_or(0);
+                ids.extend((0..total).filter_map(|j| self.subcategorias.get((actual, j))));
+                i = i.saturating_add(1);
             }
-            lista
+            Ok(ids)
         }
 
-        /// Lógica interna para listar todas las reputaciones.
+        /// Helper que devuelve los alcances de los índices de búsqueda que contienen a un
+        /// producto: todos los productos, su vendedor y su categoría con cada una de sus
+        /// ancestras.
+        fn alcances_de(&self, producto: &Producto) -> Vec<AlcanceIndice> {
+            let mut alcances = Vec::from([
+                AlcanceIndice::Todos,
+                AlcanceIndice::Vendedor(producto.vendedor),
+            ]);
+            let mut actual = Some(producto.id_categoria);
+            while let Some(id_categoria) = actual {
+                alcances.push(AlcanceIndice::Categoria(id_categoria));
+                actual = self.categorias.get(id_categoria).and_then(|c| c.padre);
+            }
+            alcances
+        }
+
+        /// Helper que devuelve el par `(clave, id_producto)` de un producto en cada criterio
+        /// de los índices de búsqueda.
+        fn claves_de_busqueda(pid: u32, producto: &Producto) -> [(CriterioIndice, ParBusqueda); 2] {
+            [
+                (CriterioIndice::Precio, (producto.precio, pid)),
+                (
+                    CriterioIndice::Creacion,
+                    (u128::from(producto.creado_en), pid),
+                ),
+            ]
+        }
+
+        /// Helper que agrega un producto a todos los índices de búsqueda que lo cubren.
         ///
-        /// Itera sobre todos los usuarios registrados y obtiene su reputación.
+        /// Los índices contienen los productos con `activo` en `true`; los pausados por una
+        /// pausa general de su vendedor siguen indexados y la búsqueda los descarta.
         ///
         /// # Complejidad
         ///
-        /// O(n) donde n es el número de usuarios registrados.
-        fn _listar_todas_reputaciones(&self) -> Vec<(AccountId, ReputacionUsuario)> {
-            let mut lista = Vec::new();
-            for usuario in &self.usuarios_registrados {
-                if let Some(rep) = self.reputaciones.get(usuario) {
-                    lista.push((*usuario, rep));
+        /// Un producto está en `2 × (2 + p)` índices, donde p es la cantidad de categorías
+        /// de su rama (la suya y sus ancestras). En cada uno cuesta lo que `insertar_en_indice`:
+        /// 3 escrituras, o b + 4 en el peor caso, si divide un bloque.
+        fn indexar_producto(&mut self, pid: u32, producto: &Producto) -> Result<(), Error> {
+            for alcance in self.alcances_de(producto) {
+                for (criterio, par) in Self::claves_de_busqueda(pid, producto) {
+                    self.insertar_en_indice((criterio, alcance), par)?;
                 }
             }
-            lista
+            Ok(())
         }
 
-        /// Lógica interna para listar productos de un vendedor.
+        /// Helper que quita un producto de todos los índices de búsqueda que lo cubren.
+        fn desindexar_producto(&mut self, pid: u32, producto: &Producto) {
+            for alcance in self.alcances_de(producto) {
+                for (criterio, par) in Self::claves_de_busqueda(pid, producto) {
+                    self.quitar_de_indice((criterio, alcance), par);
+                }
+            }
+        }
+
+        /// Helper que agrega un par `(clave, id_producto)` a un índice de búsqueda.
         ///
-        /// Itera sobre todos los productos y filtra por vendedor.
+        /// El par va al último bloque cuyo primer par no es mayor que él; si el bloque supera
+        /// `MAX_PARES_POR_BLOQUE`, su segunda mitad pasa a un bloque nuevo.
         ///
         /// # Complejidad
         ///
-        /// O(n) donde n es el número total de productos en el marketplace.
-        fn _listar_productos_de_vendedor(&self, vendedor: AccountId) -> Vec<Producto> {
-            let mut productos_vendedor = Vec::new();
+        /// O(log b) lecturas de cabeceras, donde b es la cantidad de bloques del índice, y 3
+        /// escrituras (el bloque, su cabecera y el total). Al dividir un bloque se corren
+        /// además las cabeceras siguientes: en el peor caso, dividir el primer bloque cuesta
+        /// b + 4 escrituras. Como un bloque se divide a lo sumo una vez cada
+        /// `MAX_PARES_POR_BLOQUE / 2` inserciones, el costo amortizado es de
+        /// 3 + 2(b + 1) / `MAX_PARES_POR_BLOQUE` escrituras por inserción.
+        fn insertar_en_indice(
+            &mut self,
+            indice: IndiceBusqueda,
+            par: ParBusqueda,
+        ) -> Result<(), Error> {
+            let (criterio, alcance) = indice;
+            let (mut total, mut proximo) = self
+                .total_cabeceras_busqueda
+                .get(indice)
+                .unwrap_or_default();
+            let posicion = self
+                .posicion_en_indice(indice, total, |cabecera| cabecera <= par)
+                .saturating_sub(1);
+            let (bloque, mut pares) = match self.cabeceras_busqueda.get((indice, posicion)) {
+                Some((_, _, bloque)) => (
+                    bloque,
+                    self.bloques_busqueda
+                        .get((criterio, alcance, bloque))
+                        .unwrap_or_default(),
+                ),
+                None => {
+                    let bloque = proximo;
+                    proximo = proximo.checked_add(1).ok_or(Error::IdOverflow)?;
+                    total = self.insertar_cabecera(indice, total, posicion)?;
+                    (bloque, Vec::new())
+                }
+            };
 
-            for pid in 1..self.next_prod_id {
-                if let Some(producto) = self.productos.get(pid) {
-                    if producto.vendedor == vendedor {
-                        productos_vendedor.push(producto);
-                    }
+            let destino = pares.partition_point(|&otro| otro < par);
+            pares.insert(destino, par);
+            if pares.len() > MAX_PARES_POR_BLOQUE {
+                let nuevo = proximo;
+                proximo = proximo.checked_add(1).ok_or(Error::IdOverflow)?;
+                let segunda = pares.split_off(pares.len() / 2);
+                if let Some(&(clave, pid)) = segunda.first() {
+                    let siguiente = posicion.saturating_add(1);
+                    total = self.insertar_cabecera(indice, total, siguiente)?;
+                    self.cabeceras_busqueda
+                        .insert((indice, siguiente), &(clave, pid, nuevo));
                 }
+                self.bloques_busqueda
+                    .insert((criterio, alcance, nuevo), &segunda);
             }
-
-            productos_vendedor
+            if let Some(&(clave, pid)) = pares.first() {
+                self.cabeceras_busqueda
+                    .insert((indice, posicion), &(clave, pid, bloque));
+            }
+            self.bloques_busqueda
+                .insert((criterio, alcance, bloque), &pares);
+            self.total_cabeceras_busqueda
+                .insert(indice, &(total, proximo));
+            Ok(())
         }
 
-        /// Lógica interna para listar órdenes de un comprador.
-        ///
-        /// Itera sobre todas las órdenes y filtra por comprador.
+        /// Helper que quita un par `(clave, id_producto)` de un índice de búsqueda y elimina
+        /// su bloque si queda vacío.
         ///
         /// # Complejidad
         ///
-        /// O(n) donde n es el número total de órdenes en el marketplace.
-        fn _listar_ordenes_de_comprador(&self, comprador: AccountId) -> Vec<Orden> {
-            let mut ordenes_comprador = Vec::new();
+        /// O(log b) lecturas de cabeceras, donde b es la cantidad de bloques del índice, y 2
+        /// escrituras (el bloque y su cabecera). Al eliminar un bloque vacío se corren además
+        /// las cabeceras siguientes: en el peor caso, vaciar el primer bloque cuesta b + 2
+        /// escrituras.
+        fn quitar_de_indice(&mut self, indice: IndiceBusqueda, par: ParBusqueda) {
+            let (criterio, alcance) = indice;
+            let (total, proximo) = self
+                .total_cabeceras_busqueda
+                .get(indice)
+                .unwrap_or_default();
+            let Some(posicion) = self
+                .posicion_en_indice(indice, total, |cabecera| cabecera <= par)
+                .checked_sub(1)
+            else {
+                return;
+            };
+            let Some((_, _, bloque)) = self.cabeceras_busqueda.get((indice, posicion)) else {
+                return;
+            };
+            let mut pares = self
+                .bloques_busqueda
+                .get((criterio, alcance, bloque))
+                .unwrap_or_default();
+            let Ok(encontrado) = pares.binary_search(&par) else {
+                return;
+            };
+            pares.remove(encontrado);
 
-            for oid in 1..self.next_order_id {
-                if let Some(orden) = self.ordenes.get(oid) {
-                    if orden.comprador == comprador {
-                        ordenes_comprador.push(orden);
+            match pares.first() {
+                Some(&(clave, pid)) => {
+                    self.cabeceras_busqueda
+                        .insert((indice, posicion), &(clave, pid, bloque));
+                    self.bloques_busqueda
+                        .insert((criterio, alcance, bloque), &pares);
+                }
+                None => {
+                    for siguiente in posicion.saturating_add(1)..total {
+                        if let Some(cabecera) = self.cabeceras_busqueda.get((indice, siguiente)) {
+                            self.cabeceras_busqueda
+                                .insert((indice, siguiente.saturating_sub(1)), &cabecera);
+                        }
                     }
+                    let total = total.saturating_sub(1);
+                    self.cabeceras_busqueda.remove((indice, total));
+                    self.total_cabeceras_busqueda
+                        .insert(indice, &(total, proximo));
+                    self.bloques_busqueda.remove((criterio, alcance, bloque));
                 }
             }
-
-            ordenes_comprador
         }
 
-        /// Lógica interna para registrar un usuario.
-        fn _registrar(&mut self, caller: AccountId, rol: Rol) -> Result<(), Error> {
-            self.ensure(!self.roles.contains(caller), Error::YaRegistrado)?;
-            self.roles.insert(caller, &rol);
-            self.usuarios_registrados.push(caller);
-            Ok(())
-        }
-
-        /// Lógica interna para modificar el rol de un usuario.
-        fn _modificar_rol(&mut self, caller: AccountId, nuevo_rol: Rol) -> Result<(), Error> {
-            self.ensure(self.roles.contains(caller), Error::SinRegistro)?;
-            self.roles.insert(caller, &nuevo_rol);
-            Ok(())
-        }
-
-        /// Lógica interna para publicar un producto.
-        fn _publicar(
+        /// Helper que abre un lugar en la posición `posicion` de las cabeceras de un índice
+        /// de búsqueda, corriendo una posición las siguientes. Devuelve la nueva cantidad de
+        /// cabeceras; la cabecera de `posicion` debe escribirse a continuación.
+        fn insertar_cabecera(
             &mut self,
-            vendedor: AccountId,
-            nombre: String,
-            descripcion: String,
-            precio: Balance,
-            stock: u32,
-            categoria: String,
+            indice: IndiceBusqueda,
+            total: u32,
+            posicion: u32,
         ) -> Result<u32, Error> {
-            let rol_vendedor = self.rol_de(vendedor)?;
-            self.ensure(rol_vendedor.es_vendedor(), Error::SinPermiso)?;
-            self.ensure(
-                precio > 0
-                    && stock > 0
-                    && !nombre.is_empty()
-                    && nombre.len() <= MAX_NOMBRE_LEN
-                    && !descripcion.is_empty()
-                    && descripcion.len() <= MAX_DESCRIPCION_LEN
-                    && !categoria.is_empty()
-                    && categoria.len() <= MAX_CATEGORIA_LEN,
-                Error::ParamInvalido,
-            )?;
-
-            let pid = self.next_prod_id;
-            self.next_prod_id = self.next_prod_id.checked_add(1).ok_or(Error::IdOverflow)?;
-
-            let producto = Producto {
-                vendedor,
-                nombre,
-                descripcion,
-                precio,
-                stock,
-                categoria,
-            };
-
-            self.productos.insert(pid, &producto);
-            Ok(pid)
+            let nuevo_total = total.checked_add(1).ok_or(Error::IdOverflow)?;
+            for anterior in (posicion..total).rev() {
+                if let Some(cabecera) = self.cabeceras_busqueda.get((indice, anterior)) {
+                    self.cabeceras_busqueda
+                        .insert((indice, anterior.saturating_add(1)), &cabecera);
+                }
+            }
+            Ok(nuevo_total)
         }
 
-        /// Lógica interna para comprar un producto con validación de pago.
+        /// Helper que busca la primera posición de las cabeceras de un índice de búsqueda
+        /// cuyo primer par `(clave, id_producto)` no cumple `antes`.
         ///
-        /// ## Flujo de Pago
-        /// 1. Valida rol del comprador y parámetros
-        /// 2. Calcula el monto total (precio × cantidad)
-        /// 3. Valida que el monto enviado sea exacto
-        /// 4. Descuenta stock del producto
-        /// 5. Crea la orden con el monto total registrado
-        /// 6. Retiene los fondos en escrow
-        fn _comprar(
-            &mut self,
-            comprador: AccountId,
-            id_prod: u32,
-            cant: u32,
-            monto_enviado: Balance,
-        ) -> Result<u32, Error> {
-            let rol_comprador = self.rol_de(comprador)?;
-            self.ensure(rol_comprador.es_comprador(), Error::SinPermiso)?;
-            self.ensure(cant > 0, Error::ParamInvalido)?;
-
-            let mut producto = self.productos.get(id_prod).ok_or(Error::ProdInexistente)?;
-            self.ensure(producto.vendedor != comprador, Error::AutoCompraProhibida)?;
-            self.ensure(producto.stock >= cant, Error::StockInsuf)?;
-
-            // Calcular monto total requerido
-            let monto_total = producto
-                .precio
-                .checked_mul(cant as Balance)
-                .ok_or(Error::OverflowAritmetico)?;
-
-            self.ensure(monto_enviado >= monto_total, Error::PagoInsuficiente)?;
-            self.ensure(monto_enviado <= monto_total, Error::PagoExcesivo)?;
-
-            producto.stock = producto.stock.checked_sub(cant).ok_or(Error::StockInsuf)?;
-            self.productos.insert(id_prod, &producto);
-
-            let oid = self.next_order_id;
-            self.next_order_id = self.next_order_id.checked_add(1).ok_or(Error::IdOverflow)?;
+        /// # Complejidad
+        ///
+        /// O(log b) lecturas, donde b es la cantidad de bloques del índice.
+        fn posicion_en_indice(
+            &self,
+            indice: IndiceBusqueda,
+            total: u32,
+            antes: impl Fn(ParBusqueda) -> bool,
+        ) -> u32 {
+            let (mut bajo, mut alto) = (0, total);
+            while bajo < alto {
+                let medio = bajo.saturating_add(alto.saturating_sub(bajo) / 2);
+                if self
+                    .cabeceras_busqueda
+                    .get((indice, medio))
+                    .is_some_and(|(clave, pid, _)| antes((clave, pid)))
+                {
+                    bajo = medio.saturating_add(1);
+                } else {
+                    alto = medio;
+                }
+            }
+            bajo
+        }
 
-            let orden = Orden {
-                comprador,
-                vendedor: producto.vendedor,
-                id_prod,
-                cantidad: cant,
-                estado: Estado::Pendiente,
-                monto_total,
+        /// Helper que recorre un índice de búsqueda, desde el par siguiente a `despues_de` (o
+        /// desde el principio), mientras `visitar` devuelva `true`.
+        ///
+        /// Devuelve `true` si se llegó al final del índice.
+        fn recorrer_indice(
+            &self,
+            indice: IndiceBusqueda,
+            despues_de: Option<ParBusqueda>,
+            descendente: bool,
+            mut visitar: impl FnMut(ParBusqueda) -> bool,
+        ) -> bool {
+            let (criterio, alcance) = indice;
+            let (total, _) = self
+                .total_cabeceras_busqueda
+                .get(indice)
+                .unwrap_or_default();
+            let (desde, hasta) = if descendente {
+                let hasta = despues_de.map_or(total, |limite| {
+                    self.posicion_en_indice(indice, total, |cabecera| cabecera < limite)
+                });
+                (0, hasta)
+            } else {
+                let desde = despues_de.map_or(0, |limite| {
+                    self.posicion_en_indice(indice, total, |cabecera| cabecera <= limite)
+                        .saturating_sub(1)
+                });
+                (desde, total)
+            };
+            let pendiente = |par: &ParBusqueda| match despues_de {
+                None => true,
+                Some(limite) if descendente => *par < limite,
+                Some(limite) => *par > limite,
             };
 
-            self.ordenes.insert(oid, &orden);
-
-            self.fondos_retenidos.insert(oid, &monto_total);
-
-            Ok(oid)
+            for paso in 0..hasta.saturating_sub(desde) {
+                let posicion = if descendente {
+                    hasta.saturating_sub(paso).saturating_sub(1)
+                } else {
+                    desde.saturating_add(paso)
+                };
+                let Some((_, _, bloque)) = self.cabeceras_busqueda.get((indice, posicion)) else {
+                    continue;
+                };
+                let mut pares = self
+                    .bloques_busqueda
+                    .get((criterio, alcance, bloque))
+                    .unwrap_or_default();
+                if descendente {
+                    pares.reverse();
+                }
+                for par in pares.into_iter().filter(|par| pendiente(par)) {
+                    if !visitar(par) {
+                        return false;
+                    }
+                }
+            }
+            true
         }
 
-        /// Lógica interna para marcar una orden como enviada.
-        fn _marcar_enviado(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
-            let mut orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
-            self.ensure(orden.vendedor == caller, Error::SinPermiso)?;
-
-            if orden.estado == Estado::Cancelada {
-                return Err(Error::OrdenCancelada);
+        /// Helper que ubica a un vendedor en `vendedores_por_promedio` según su promedio
+        /// actual como vendedor, si todavía no figura con ese promedio.
+        fn actualizar_promedio_vendedor(&mut self, vendedor: AccountId) -> Result<(), Error> {
+            let promedio = Self::promedio_x100(
+                self.reputaciones
+                    .get(vendedor)
+                    .unwrap_or_default()
+                    .como_vendedor,
+            );
+            if self
+                .posicion_por_promedio
+                .get(vendedor)
+                .is_some_and(|(actual, _)| actual == promedio)
+            {
+                return Ok(());
+            }
+            let total = self
+                .total_vendedores_por_promedio
+                .get(promedio)
+                .unwrap_or(0);
+            let nuevo_total = total.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.vendedores_por_promedio
+                .insert((promedio, total), &vendedor);
+            self.total_vendedores_por_promedio
+                .insert(promedio, &nuevo_total);
+            self.posicion_por_promedio
+                .insert(vendedor, &(promedio, total));
+            if total == 0 {
+                let posicion = self.posicion_de_promedio(promedio);
+                let total_promedios = self.total_promedios_con_vendedores;
+                self.total_promedios_con_vendedores = total_promedios
+                    .checked_add(1)
+                    .ok_or(Error::OverflowAritmetico)?;
+                for i in (posicion..total_promedios).rev() {
+                    if let Some(otro) = self.promedios_con_vendedores.get(i) {
+                        self.promedios_con_vendedores
+                            .insert(i.saturating_add(1), &otro);
+                    }
+                }
+                self.promedios_con_vendedores.insert(posicion, &promedio);
             }
-            self.ensure(orden.estado == Estado::Pendiente, Error::EstadoInvalido)?;
-
-            orden.estado = Estado::Enviado;
-            self.ordenes.insert(oid, &orden);
             Ok(())
         }
 
-        /// Lógica interna para marcar una orden como recibida y liberar fondos al vendedor.
+        /// Helper que busca la primera posición de `promedios_con_vendedores` cuyo promedio
+        /// no supera `promedio`.
         ///
-        /// ## Flujo de liberación de fondos
-        /// 1. Valida permisos y estado de la orden
-        /// 2. Actualiza el estado de la orden y limpia datos internos
-        /// 3. Transfiere los fondos al vendedor
-        fn _marcar_recibido(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
-            let mut orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
-            self.ensure(orden.comprador == caller, Error::SinPermiso)?;
-
-            if orden.estado == Estado::Cancelada {
-                return Err(Error::OrdenCancelada);
+        /// # Complejidad
+        ///
+        /// O(log p) lecturas, donde p es la cantidad de promedios (a lo sumo 501).
+        fn posicion_de_promedio(&self, promedio: u32) -> u32 {
+            let (mut bajo, mut alto) = (0, self.total_promedios_con_vendedores);
+            while bajo < alto {
+                let medio = bajo.saturating_add(alto.saturating_sub(bajo) / 2);
+                if self
+                    .promedios_con_vendedores
+                    .get(medio)
+                    .is_some_and(|otro| otro > promedio)
+                {
+                    bajo = medio.saturating_add(1);
+                } else {
+                    alto = medio;
+                }
             }
-            self.ensure(orden.estado == Estado::Enviado, Error::EstadoInvalido)?;
-
-            let fondos = self.fondos_retenidos.get(oid).unwrap_or(0);
-            self.fondos_retenidos.remove(oid);
-
-            orden.estado = Estado::Recibido;
-            self.ordenes.insert(oid, &orden);
-            self.cancelaciones_pendientes.remove(oid);
-
-            let vendedor = orden.vendedor;
+            bajo
+        }
 
-            if fondos > 0 {
-                self.env()
-                    .transfer(vendedor, fondos)
-                    .map_err(|_| Error::TransferenciaFallida)?;
+        /// Helper que ubica a un referido en `mejores_referidos` según sus nuevas ganancias.
+        ///
+        /// Como las ganancias solo crecen, un referido fuera de la lista nunca supera al
+        /// último de ella sin pasar por esta función.
+        fn actualizar_mejores_referidos(&mut self, cuenta: AccountId, ganancias: Balance) {
+            let ganancias_de =
+                |mp: &Self, otra: AccountId| mp.obtener_estadisticas_referido(otra).ganancias;
+            let mut mejores = core::mem::take(&mut self.mejores_referidos);
+            mejores.retain(|&otra| otra != cuenta);
+            let posicion = mejores
+                .iter()
+                .position(|&otra| ganancias_de(self, otra) < ganancias)
+                .unwrap_or(mejores.len());
+            if posicion < MAX_MEJORES_REFERIDOS {
+                mejores.insert(posicion, cuenta);
+                mejores.truncate(MAX_MEJORES_REFERIDOS);
             }
-
-            Ok(())
+            self.mejores_referidos = mejores;
         }
 
-        /// Lógica interna para solicitar la cancelación de una orden.
-        fn _solicitar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
-            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
-
-            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
-
-            self.ensure(
-                caller == orden.comprador || caller == orden.vendedor,
-                Error::SinPermiso,
-            )?;
-
-            self.ensure(
-                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
-                Error::EstadoInvalido,
-            )?;
-
-            self.ensure(
-                !self.cancelaciones_pendientes.contains(oid),
-                Error::CancelacionYaPendiente,
-            )?;
+        /// Helper que calcula el promedio (x100) de un par `(suma, cantidad)` de calificaciones.
+        fn promedio_x100((suma, cantidad): (u32, u32)) -> u32 {
+            if cantidad > 0 {
+                suma.saturating_mul(100).saturating_div(cantidad)
+            } else {
+                0
+            }
+        }
 
-            self.cancelaciones_pendientes.insert(
-                oid,
-                &CancelacionPendiente {
-                    solicitante: caller,
-                },
-            );
-            Ok(())
+        /// Helper que verifica si un comprador cumple un requisito de reputación.
+        ///
+        /// Compara la cantidad de calificaciones y el promedio (x100) de
+        /// `ReputacionUsuario::como_comprador` con los mínimos exigidos.
+        fn cumple_requisito(&self, comprador: AccountId, requisito: &RequisitoReputacion) -> bool {
+            let (suma, cantidad) = self
+                .reputaciones
+                .get(comprador)
+                .unwrap_or_default()
+                .como_comprador;
+            if cantidad < requisito.calificaciones_minimas {
+                return false;
+            }
+            Self::promedio_x100((suma, cantidad)) >= requisito.promedio_minimo_x100
         }
 
-        /// Lógica interna para aceptar la cancelación de una orden y devolver fondos.
-        ///
-        /// ## Flujo de Devolución de Fondos (patrón checks-effects-interactions)
-        /// 1. **Checks**: Valida permisos y estado de la cancelación
-        /// 2. **Effects**: Restaura stock, actualiza estado de la orden, limpia datos
-        /// 3. **Interactions**: Transfiere los fondos de vuelta al comprador
-        ///
-        /// Este orden previene posibles vulnerabilidades de reentrancy.
-        fn _aceptar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
-            let cancelacion = self
-                .cancelaciones_pendientes
-                .get(oid)
-                .ok_or(Error::CancelacionInexistente)?;
+        /// Helper que verifica que el vendedor tenga la fianza exigida para ofrecer un
+        /// producto al precio indicado.
+        fn verificar_fianza(&self, vendedor: AccountId, precio: Balance) -> Result<(), Error> {
+            if let Some(requisito) = self.requisito_fianza {
+                self.ensure(
+                    precio < requisito.precio_minimo
+                        || self.obtener_fianza(vendedor) >= requisito.fianza_minima,
+                    Error::FianzaInsuficiente,
+                )?;
+            }
+            Ok(())
+        }
 
-            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+        /// Helper que pausa todos los productos activos de un vendedor.
+        ///
+        /// No recorre los productos: incrementa el contador de pausas del vendedor, y los
+        /// productos publicados o reactivados antes de la pausa dejan de estar activos.
+        fn pausar_productos_de(&mut self, vendedor: AccountId) -> Result<(), Error> {
+            let pausas = self
+                .pausas_vendedor
+                .get(vendedor)
+                .unwrap_or(0)
+                .checked_add(1)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.pausas_vendedor.insert(vendedor, &pausas);
+            Ok(())
+        }
 
-            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
+        /// Helper que indica si un producto está publicado y no quedó pausado por una
+        /// pausa general de su vendedor.
+        fn esta_activo(&self, producto: &Producto) -> bool {
+            producto.activo
+                && producto.pausas_vendedor
+                    == self.pausas_vendedor.get(producto.vendedor).unwrap_or(0)
+        }
 
-            self.ensure(
-                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
-                Error::EstadoInvalido,
-            )?;
+        /// Helper que devuelve un producto con `activo` reflejando si está activo según
+        /// `esta_activo`.
+        fn con_estado_vigente(&self, mut producto: Producto) -> Producto {
+            producto.activo = self.esta_activo(&producto);
+            producto
+        }
 
-            self.ensure(
-                caller != cancelacion.solicitante,
-                Error::SolicitanteCancelacion,
-            )?;
+        /// Helper que registra una nueva orden (o suscripción) abierta para su comprador
+        /// y su vendedor.
+        fn abrir_orden(&mut self, comprador: AccountId, vendedor: AccountId) -> Result<(), Error> {
+            let (compras, ventas) = self.obtener_ordenes_abiertas(comprador);
+            let compras = compras.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.ordenes_abiertas.insert(comprador, &(compras, ventas));
+
+            let (compras, ventas) = self.obtener_ordenes_abiertas(vendedor);
+            let ventas = ventas.checked_add(1).ok_or(Error::OverflowAritmetico)?;
+            self.ordenes_abiertas.insert(vendedor, &(compras, ventas));
+            Ok(())
+        }
 
+        /// Helper que descuenta una orden (o suscripción) que deja de estar abierta.
+        fn cerrar_orden(&mut self, comprador: AccountId, vendedor: AccountId) {
+            let (compras, ventas) = self.obtener_ordenes_abiertas(comprador);
+            self.ordenes_abiertas
+                .insert(comprador, &(compras.saturating_sub(1), ventas));
+
+            let (compras, ventas) = self.obtener_ordenes_abiertas(vendedor);
+            self.ordenes_abiertas
+                .insert(vendedor, &(compras, ventas.saturating_sub(1)));
+        }
+
+        /// Helper que valida que un comprador pueda comprar (o suscribirse a) un producto.
+        ///
+        /// # Errores
+        ///
+        /// - `Error::ProductoInactivo` si el producto fue despublicado.
+        /// - `Error::AutoCompraProhibida` si el comprador es el vendedor del producto.
+        /// - `Error::CompradorBloqueado` si el vendedor bloqueó al comprador.
+        /// - `Error::ReputacionInsuficiente` si el comprador no cumple la reputación mínima.
+        fn validar_compra(&self, comprador: AccountId, producto: &Producto) -> Result<(), Error> {
+            self.ensure(self.esta_activo(producto), Error::ProductoInactivo)?;
+            self.ensure(producto.vendedor != comprador, Error::AutoCompraProhibida)?;
             self.ensure(
-                self.es_otro_participante(caller, &orden, cancelacion.solicitante),
-                Error::SinPermiso,
+                !self.esta_bloqueado(producto.vendedor, comprador),
+                Error::CompradorBloqueado,
             )?;
+            if let Some(requisito) = producto.requisito_reputacion {
+                self.ensure(
+                    self.cumple_requisito(comprador, &requisito),
+                    Error::ReputacionInsuficiente,
+                )?;
+            }
+            Ok(())
+        }
 
-            // Effects: actualizar estado interno primero
-            let fondos = self.fondos_retenidos.get(oid).unwrap_or(0);
-            self.fondos_retenidos.remove(oid);
-
-            let mut producto = self
-                .productos
-                .get(orden.id_prod)
-                .ok_or(Error::ProdInexistente)?;
-            producto.stock = producto
-                .stock
-                .checked_add(orden.cantidad)
-                .ok_or(Error::StockOverflow)?;
-            self.productos.insert(orden.id_prod, &producto);
+        /// Helper que descuenta hasta `monto` de la fianza de un vendedor y devuelve el monto
+        /// efectivamente descontado.
+        fn descontar_fianza(&mut self, vendedor: AccountId, monto: Balance) -> Balance {
+            let fianza = self.obtener_fianza(vendedor);
+            let descontado = monto.min(fianza);
+            if descontado > 0 {
+                self.fianzas
+                    .insert(vendedor, &fianza.saturating_sub(descontado));
+                self.total_fianzas = self.total_fianzas.saturating_sub(descontado);
+            }
+            descontado
+        }
 
-            let comprador = orden.comprador;
+        /// Helper que devuelve el depósito de una reserva ya cerrada. Si la reserva venció,
+        /// retiene la comisión anti-spam y la acredita al vendedor.
+        fn devolver_deposito(
+            &mut self,
+            comprador: AccountId,
+            vendedor: AccountId,
+            reserva: &Reserva,
+        ) -> Result<(), Error> {
+            let comision = if self.env().block_number() >= reserva.bloque_vencimiento {
+                reserva
+                    .deposito
+                    .checked_mul(COMISION_RESERVA_PORCENTAJE)
+                    .ok_or(Error::OverflowAritmetico)?
+                    .checked_div(100)
+                    .unwrap_or(0)
+            } else {
+                0
+            };
+            let reembolso = reserva.deposito.saturating_sub(comision);
+            self.total_depositos_reservas = self
+                .total_depositos_reservas
+                .saturating_sub(reserva.deposito);
+            self.acreditar(vendedor, comision)?;
+            self.acreditar(comprador, reembolso)
+        }
 
-            self.ordenes.insert(
+        /// Helper que agrega un registro con el timestamp y bloque actuales al historial
+        /// de una orden.
+        fn registrar_evento(&mut self, oid: u32, evento: EventoOrden) -> Result<(), Error> {
+            let posicion = self.get_total_registros_orden(oid);
+            self.total_registros_orden.insert(
                 oid,
-                &Orden {
-                    estado: Estado::Cancelada,
-                    ..orden
+                &posicion.checked_add(1).ok_or(Error::OverflowAritmetico)?,
+            );
+            self.historial_ordenes.insert(
+                (oid, posicion),
+                &RegistroOrden {
+                    evento,
+                    momento: self.env().block_timestamp(),
+                    bloque: self.env().block_number(),
                 },
             );
+            Ok(())
+        }
 
-            self.cancelaciones_pendientes.remove(oid);
+        /// Helper que agrega un usuario al final de `usuarios`.
+        fn agregar_usuario(&mut self, usuario: AccountId) -> Result<(), Error> {
+            let indice = self.total_usuarios;
+            self.total_usuarios = indice.checked_add(1).ok_or(Error::IdOverflow)?;
+            self.usuarios.insert(indice, &usuario);
+            self.indice_usuarios.insert(usuario, &indice);
+            Ok(())
+        }
 
-            if fondos > 0 {
-                self.env()
-                    .transfer(comprador, fondos)
-                    .map_err(|_| Error::TransferenciaFallida)?;
-            }
+        /// Helper que ejecuta un mensaje que mueve fondos con el bloqueo de reentrada tomado.
+        ///
+        /// Si el bloqueo ya está tomado (una llamada reentrante durante otro mensaje que
+        /// mueve fondos), devuelve `Error::Reentrada` sin ejecutar nada.
+        fn sin_reentrada<T>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            self.ensure(!self.bloqueado, Error::Reentrada)?;
+            self.bloqueado = true;
+            let resultado = f(self);
+            self.bloqueado = false;
+            resultado
+        }
 
+        /// Helper que retiene en escrow los fondos de una orden.
+        fn retener_fondos(&mut self, oid: u32, monto: Balance) -> Result<(), Error> {
+            self.total_fondos_retenidos = self
+                .total_fondos_retenidos
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.fondos_retenidos.insert(oid, &monto);
             Ok(())
         }
 
-        /// Lógica interna para rechazar la cancelación de una orden.
-        fn _rechazar_cancelacion(&mut self, caller: AccountId, oid: u32) -> Result<(), Error> {
-            let cancelacion = self
-                .cancelaciones_pendientes
-                .get(oid)
-                .ok_or(Error::CancelacionInexistente)?;
+        /// Helper que libera el escrow de una orden y devuelve su monto.
+        fn liberar_fondos(&mut self, oid: u32) -> Balance {
+            let fondos = self.fondos_retenidos.take(oid).unwrap_or(0);
+            self.total_fondos_retenidos = self.total_fondos_retenidos.saturating_sub(fondos);
+            fondos
+        }
 
-            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+        /// Helper que retiene los fondos pagados por adelantado de una suscripción.
+        fn retener_fondos_suscripcion(&mut self, sid: u32, monto: Balance) -> Result<(), Error> {
+            self.total_fondos_suscripciones = self
+                .total_fondos_suscripciones
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.fondos_suscripciones.insert(sid, &monto);
+            Ok(())
+        }
 
-            self.ensure(orden.estado != Estado::Cancelada, Error::OrdenCancelada)?;
+        /// Helper que libera los fondos restantes de una suscripción y devuelve su monto.
+        fn liberar_fondos_suscripcion(&mut self, sid: u32) -> Balance {
+            let fondos = self.fondos_suscripciones.take(sid).unwrap_or(0);
+            self.total_fondos_suscripciones =
+                self.total_fondos_suscripciones.saturating_sub(fondos);
+            fondos
+        }
 
-            self.ensure(
-                orden.estado == Estado::Pendiente || orden.estado == Estado::Enviado,
-                Error::EstadoInvalido,
-            )?;
+        /// Helper que suma un monto al saldo retirable de un usuario.
+        fn acreditar(&mut self, usuario: AccountId, monto: Balance) -> Result<(), Error> {
+            if monto == 0 {
+                return Ok(());
+            }
+            let usuario = self.cuenta_vigente(usuario);
+            let saldo = self
+                .obtener_saldo_retirable(usuario)
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            self.saldos_retirables.insert(usuario, &saldo);
+            self.total_saldos_retirables = self
+                .total_saldos_retirables
+                .checked_add(monto)
+                .ok_or(Error::OverflowAritmetico)?;
+            Ok(())
+        }
 
-            self.ensure(
-                caller != cancelacion.solicitante,
-                Error::SolicitanteCancelacion,
-            )?;
+        /// Lógica interna para retirar todo el saldo retirable del llamante.
+        fn _retirar(&mut self, caller: AccountId) -> Result<Balance, Error> {
+            let monto = self.obtener_saldo_retirable(caller);
+            self.ensure(monto > 0, Error::SinSaldoRetirable)?;
+            self.transferir_saldo(caller, monto)?;
+            Ok(monto)
+        }
 
+        /// Lógica interna para retirar una parte del saldo retirable del llamante.
+        fn _retirar_monto(&mut self, caller: AccountId, monto: Balance) -> Result<(), Error> {
+            self.ensure(monto > 0, Error::ParamInvalido)?;
             self.ensure(
-                self.es_otro_participante(caller, &orden, cancelacion.solicitante),
-                Error::SinPermiso,
+                monto <= self.obtener_saldo_retirable(caller),
+                Error::SaldoInsuficiente,
             )?;
+            self.transferir_saldo(caller, monto)
+        }
 
-            self.cancelaciones_pendientes.remove(oid);
+        /// Helper que descuenta un monto del saldo retirable de un usuario y se lo transfiere.
+        /// El saldo se descuenta antes de transferir.
+        fn transferir_saldo(&mut self, usuario: AccountId, monto: Balance) -> Result<(), Error> {
+            let saldo = self.obtener_saldo_retirable(usuario);
+            let restante = saldo.checked_sub(monto).ok_or(Error::SaldoInsuficiente)?;
+            if restante == 0 {
+                self.saldos_retirables.remove(usuario);
+            } else {
+                self.saldos_retirables.insert(usuario, &restante);
+            }
+            self.total_saldos_retirables = self.total_saldos_retirables.saturating_sub(monto);
+            self.env()
+                .transfer(usuario, monto)
+                .map_err(|_| Error::TransferenciaFallida)
+        }
 
-            Ok(())
+        /// Helper que calcula cuántos períodos de una suscripción ya comenzaron,
+        /// acotado por la cantidad de períodos pagados.
+        fn periodos_iniciados(&self, suscripcion: &Suscripcion) -> u32 {
+            let transcurridos = self
+                .env()
+                .block_number()
+                .saturating_sub(suscripcion.bloque_inicio);
+            transcurridos
+                .checked_div(suscripcion.periodo_bloques)
+                .unwrap_or(0)
+                .saturating_add(1)
+                .min(suscripcion.periodos_pagados)
         }
 
-        /// Helper para validar condiciones.
-        ///
-        /// Esta función auxiliar facilita la validación de condiciones en el contrato,
-        /// haciendo que el código sea más legible y expresivo.
-        ///
-        /// # Argumentos
-        ///
-        /// * `cond` - La condición booleana a verificar.
-        /// * `err` - El error a devolver si la condición es falsa.
+        /// Helper que genera una orden por cada período iniciado y aún no cobrado de una
+        /// suscripción, hasta `MAX_PERIODOS_POR_COBRO`, y devuelve la cantidad de períodos y el
+        /// monto a pagar al vendedor.
         ///
-        /// # Retorno
-        ///
-        /// Devuelve `Ok(())` si la condición es verdadera, o `Err(err)` si es falsa.
-        fn ensure(&self, cond: bool, err: Error) -> Result<(), Error> {
-            if cond {
-                Ok(())
-            } else {
-                Err(err)
+        /// Solo actualiza el estado; el llamador es responsable de guardar la suscripción y de
+        /// transferir el monto.
+        fn generar_ordenes_suscripcion(
+            &mut self,
+            sid: u32,
+            suscripcion: &mut Suscripcion,
+        ) -> Result<(u32, Balance), Error> {
+            let nuevos = self
+                .periodos_iniciados(suscripcion)
+                .saturating_sub(suscripcion.periodos_cobrados)
+                .min(MAX_PERIODOS_POR_COBRO);
+            let monto = suscripcion
+                .precio_periodo
+                .checked_mul(nuevos as Balance)
+                .ok_or(Error::OverflowAritmetico)?;
+
+            for _ in 0..nuevos {
+                let oid = self.next_order_id;
+                self.next_order_id = self.next_order_id.checked_add(1).ok_or(Error::IdOverflow)?;
+                self.ordenes.insert(
+                    oid,
+                    &Orden {
+                        comprador: suscripcion.comprador,
+                        vendedor: suscripcion.vendedor,
+                        id_prod: suscripcion.id_prod,
+                        cantidad: 1,
+                        estado: Estado::Recibido,
+                        monto_total: suscripcion.precio_periodo,
+                        destinatario: suscripcion.comprador,
+                    },
+                );
+                self.registrar_evento(oid, EventoOrden::Creada)?;
+                self.registrar_evento(oid, EventoOrden::Recibida)?;
+                self.indexar_orden(suscripcion.comprador, oid)?;
+                self.indexar_orden(suscripcion.vendedor, oid)?;
             }
+
+            suscripcion.periodos_cobrados = suscripcion.periodos_cobrados.saturating_add(nuevos);
+            self.descontar_fondos_suscripcion(sid, monto);
+            Ok((nuevos, monto))
         }
 
-        /// Helper que obtiene el rol de un usuario.
-        ///
-        /// # Argumentos
-        ///
-        /// * `quien` - La `AccountId` del usuario cuyo rol se desea obtener.
-        ///
-        /// # Errores
-        ///
-        /// Devuelve `Error::SinRegistro` si el usuario no está registrado.
-        ///
-        /// # Retorno
-        ///
-        /// Devuelve el `Rol` del usuario si está registrado.
-        fn rol_de(&self, quien: AccountId) -> Result<Rol, Error> {
-            self.roles.get(quien).ok_or(Error::SinRegistro)
+        /// Helper que descuenta un monto de los fondos retenidos de una suscripción.
+        fn descontar_fondos_suscripcion(&mut self, sid: u32, monto: Balance) {
+            let fondos = self.fondos_suscripciones.get(sid).unwrap_or(0);
+            let restante = fondos.saturating_sub(monto);
+            self.fondos_suscripciones.insert(sid, &restante);
+            self.total_fondos_suscripciones = self
+                .total_fondos_suscripciones
+                .saturating_sub(fondos.saturating_sub(restante));
+        }
+
+        /// Helper que finaliza una suscripción cuando ya se cobraron todos sus períodos
+        /// pagados, liberando sus fondos y su orden abierta.
+        fn finalizar_si_cobrada(&mut self, sid: u32, suscripcion: &mut Suscripcion) {
+            if suscripcion.periodos_cobrados >= suscripcion.periodos_pagados {
+                suscripcion.activa = false;
+                self.liberar_fondos_suscripcion(sid);
+                self.cerrar_orden(suscripcion.comprador, suscripcion.vendedor);
+            }
         }
 
         /// Helper para validar que el caller sea el otro participante en una orden.
@@ -1214,8 +6694,12 @@ mod marketplace {
             puntos: u8,
         ) -> Result<(), Error> {
             let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let vendedor = self.cuenta_vigente(orden.vendedor);
 
-            self.ensure(orden.comprador == caller, Error::SinPermiso)?;
+            self.ensure(
+                self.cuenta_vigente(orden.destinatario) == caller,
+                Error::SinPermiso,
+            )?;
             self.ensure(orden.estado == Estado::Recibido, Error::OrdenNoRecibida)?;
             self.ensure((1..=5).contains(&puntos), Error::CalificacionInvalida)?;
 
@@ -1223,9 +6707,10 @@ mod marketplace {
             self.ensure(!calif.comprador_califico, Error::YaCalificado)?;
 
             calif.comprador_califico = true;
-            self.calificaciones.insert(oid, &calif);
+            calif.puntos_al_vendedor = puntos;
+            calif.bloque_calificacion_comprador = self.env().block_number();
 
-            let mut rep = self.reputaciones.get(orden.vendedor).unwrap_or_default();
+            let mut rep = self.reputaciones.get(vendedor).unwrap_or_default();
 
             rep.como_vendedor.0 = rep
                 .como_vendedor
@@ -1238,7 +6723,21 @@ mod marketplace {
                 .checked_add(1)
                 .ok_or(Error::OverflowAritmetico)?;
 
-            self.reputaciones.insert(orden.vendedor, &rep);
+            self.reputaciones.insert(vendedor, &rep);
+            self.actualizar_promedio_vendedor(vendedor)?;
+
+            let mut rep_ponderada = self
+                .reputaciones_ponderadas
+                .get(vendedor)
+                .unwrap_or_default();
+            calif.aporte_al_vendedor = rep_ponderada.como_vendedor.agregar(
+                puntos,
+                orden.monto_total,
+                self.env().block_number(),
+            )?;
+            self.reputaciones_ponderadas
+                .insert(vendedor, &rep_ponderada);
+            self.calificaciones.insert(oid, &calif);
 
             let producto = self
                 .productos
@@ -1246,7 +6745,7 @@ mod marketplace {
                 .ok_or(Error::ProdInexistente)?;
             let mut cat_rep = self
                 .calificaciones_por_categoria
-                .get(producto.categoria.clone())
+                .get(producto.id_categoria)
                 .unwrap_or((0, 0));
 
             cat_rep.0 = cat_rep
@@ -1255,7 +6754,7 @@ mod marketplace {
                 .ok_or(Error::OverflowAritmetico)?;
             cat_rep.1 = cat_rep.1.checked_add(1).ok_or(Error::OverflowAritmetico)?;
             self.calificaciones_por_categoria
-                .insert(producto.categoria, &cat_rep);
+                .insert(producto.id_categoria, &cat_rep);
 
             Ok(())
         }
@@ -1268,8 +6767,12 @@ mod marketplace {
             puntos: u8,
         ) -> Result<(), Error> {
             let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let comprador = self.cuenta_vigente(orden.comprador);
 
-            self.ensure(orden.vendedor == caller, Error::SinPermiso)?;
+            self.ensure(
+                self.cuenta_vigente(orden.vendedor) == caller,
+                Error::SinPermiso,
+            )?;
             self.ensure(orden.estado == Estado::Recibido, Error::OrdenNoRecibida)?;
             self.ensure((1..=5).contains(&puntos), Error::CalificacionInvalida)?;
 
@@ -1277,9 +6780,10 @@ mod marketplace {
             self.ensure(!calif.vendedor_califico, Error::YaCalificado)?;
 
             calif.vendedor_califico = true;
-            self.calificaciones.insert(oid, &calif);
+            calif.puntos_al_comprador = puntos;
+            calif.bloque_calificacion_vendedor = self.env().block_number();
 
-            let mut rep = self.reputaciones.get(orden.comprador).unwrap_or_default();
+            let mut rep = self.reputaciones.get(comprador).unwrap_or_default();
 
             rep.como_comprador.0 = rep
                 .como_comprador
@@ -1292,7 +6796,216 @@ mod marketplace {
                 .checked_add(1)
                 .ok_or(Error::OverflowAritmetico)?;
 
-            self.reputaciones.insert(orden.comprador, &rep);
+            self.reputaciones.insert(comprador, &rep);
+
+            let mut rep_ponderada = self
+                .reputaciones_ponderadas
+                .get(comprador)
+                .unwrap_or_default();
+            calif.aporte_al_comprador = rep_ponderada.como_comprador.agregar(
+                puntos,
+                orden.monto_total,
+                self.env().block_number(),
+            )?;
+            self.reputaciones_ponderadas
+                .insert(comprador, &rep_ponderada);
+            self.calificaciones.insert(oid, &calif);
+
+            Ok(())
+        }
+
+        /// Lógica interna para que el comprador edite la calificación del vendedor.
+        fn _editar_calificacion_vendedor(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            puntos: u8,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let vendedor = self.cuenta_vigente(orden.vendedor);
+
+            self.ensure(
+                self.cuenta_vigente(orden.destinatario) == caller,
+                Error::SinPermiso,
+            )?;
+            self.ensure((1..=5).contains(&puntos), Error::CalificacionInvalida)?;
+
+            let mut calif = self.calificaciones.get(oid).unwrap_or_default();
+            self.ensure(calif.comprador_califico, Error::CalificacionInexistente)?;
+            self.ensure(
+                !calif
+                    .historial
+                    .iter()
+                    .any(|e| self.cuenta_vigente(e.autor) == caller),
+                Error::YaEditado,
+            )?;
+
+            let bloque = self.env().block_number();
+            self.ensure(
+                bloque.saturating_sub(calif.bloque_calificacion_comprador) <= self.ventana_edicion,
+                Error::VentanaEdicionVencida,
+            )?;
+
+            let anteriores = calif.puntos_al_vendedor;
+
+            let mut rep = self.reputaciones.get(vendedor).unwrap_or_default();
+            rep.como_vendedor.0 = rep
+                .como_vendedor
+                .0
+                .checked_sub(anteriores as u32)
+                .and_then(|suma| suma.checked_add(puntos as u32))
+                .ok_or(Error::OverflowAritmetico)?;
+            self.reputaciones.insert(vendedor, &rep);
+            self.actualizar_promedio_vendedor(vendedor)?;
+
+            let mut rep_ponderada = self
+                .reputaciones_ponderadas
+                .get(vendedor)
+                .unwrap_or_default();
+            rep_ponderada.como_vendedor.reemplazar(
+                &calif.aporte_al_vendedor,
+                anteriores,
+                puntos,
+            )?;
+            self.reputaciones_ponderadas
+                .insert(vendedor, &rep_ponderada);
+
+            let producto = self
+                .productos
+                .get(orden.id_prod)
+                .ok_or(Error::ProdInexistente)?;
+            let mut cat_rep = self
+                .calificaciones_por_categoria
+                .get(producto.id_categoria)
+                .unwrap_or((0, 0));
+            cat_rep.0 = cat_rep
+                .0
+                .checked_sub(anteriores as u32)
+                .and_then(|suma| suma.checked_add(puntos as u32))
+                .ok_or(Error::OverflowAritmetico)?;
+            self.calificaciones_por_categoria
+                .insert(producto.id_categoria, &cat_rep);
+
+            calif.puntos_al_vendedor = puntos;
+            calif.historial.push(EdicionCalificacion {
+                autor: caller,
+                puntos_anteriores: anteriores,
+                puntos_nuevos: puntos,
+                bloque,
+            });
+            self.calificaciones.insert(oid, &calif);
+
+            Ok(())
+        }
+
+        /// Lógica interna para que el vendedor edite la calificación del comprador.
+        fn _editar_calificacion_comprador(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            puntos: u8,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+            let comprador = self.cuenta_vigente(orden.comprador);
+
+            self.ensure(
+                self.cuenta_vigente(orden.vendedor) == caller,
+                Error::SinPermiso,
+            )?;
+            self.ensure((1..=5).contains(&puntos), Error::CalificacionInvalida)?;
+
+            let mut calif = self.calificaciones.get(oid).unwrap_or_default();
+            self.ensure(calif.vendedor_califico, Error::CalificacionInexistente)?;
+            self.ensure(
+                !calif
+                    .historial
+                    .iter()
+                    .any(|e| self.cuenta_vigente(e.autor) == caller),
+                Error::YaEditado,
+            )?;
+
+            let bloque = self.env().block_number();
+            self.ensure(
+                bloque.saturating_sub(calif.bloque_calificacion_vendedor) <= self.ventana_edicion,
+                Error::VentanaEdicionVencida,
+            )?;
+
+            let anteriores = calif.puntos_al_comprador;
+
+            let mut rep = self.reputaciones.get(comprador).unwrap_or_default();
+            rep.como_comprador.0 = rep
+                .como_comprador
+                .0
+                .checked_sub(anteriores as u32)
+                .and_then(|suma| suma.checked_add(puntos as u32))
+                .ok_or(Error::OverflowAritmetico)?;
+            self.reputaciones.insert(comprador, &rep);
+
+            let mut rep_ponderada = self
+                .reputaciones_ponderadas
+                .get(comprador)
+                .unwrap_or_default();
+            rep_ponderada.como_comprador.reemplazar(
+                &calif.aporte_al_comprador,
+                anteriores,
+                puntos,
+            )?;
+            self.reputaciones_ponderadas
+                .insert(comprador, &rep_ponderada);
+
+            calif.puntos_al_comprador = puntos;
+            calif.historial.push(EdicionCalificacion {
+                autor: caller,
+                puntos_anteriores: anteriores,
+                puntos_nuevos: puntos,
+                bloque,
+            });
+            self.calificaciones.insert(oid, &calif);
+
+            Ok(())
+        }
+
+        /// Lógica interna para responder a la calificación recibida en una orden.
+        fn _responder_calificacion(
+            &mut self,
+            caller: AccountId,
+            oid: u32,
+            respuesta: String,
+        ) -> Result<(), Error> {
+            let orden = self.ordenes.get(oid).ok_or(Error::OrdenInexistente)?;
+
+            let comprador = self.cuenta_vigente(orden.comprador);
+            let vendedor = self.cuenta_vigente(orden.vendedor);
+            self.ensure(caller == comprador || caller == vendedor, Error::SinPermiso)?;
+            self.ensure(
+                !respuesta.is_empty() && respuesta.len() <= MAX_RESPUESTA_LEN,
+                Error::ParamInvalido,
+            )?;
+
+            let mut calif = self
+                .calificaciones
+                .get(oid)
+                .ok_or(Error::CalificacionInexistente)?;
+
+            let destinatario = if caller == vendedor {
+                self.ensure(calif.comprador_califico, Error::CalificacionInexistente)?;
+                self.ensure(calif.respuesta_vendedor.is_none(), Error::YaRespondido)?;
+                calif.respuesta_vendedor = Some(respuesta);
+                self.cuenta_vigente(orden.destinatario)
+            } else {
+                self.ensure(calif.vendedor_califico, Error::CalificacionInexistente)?;
+                self.ensure(calif.respuesta_comprador.is_none(), Error::YaRespondido)?;
+                calif.respuesta_comprador = Some(respuesta);
+                vendedor
+            };
+
+            self.calificaciones.insert(oid, &calif);
+
+            self.env().emit_event(RespuestaPublicada {
+                oid,
+                autor: caller,
+                destinatario,
+            });
 
             Ok(())
         }
@@ -1304,5 +7017,10 @@ mod marketplace {
 
 #[cfg(feature = "ink-as-dependency")]
 pub use marketplace::{
-    Error, Estado, Marketplace, MarketplaceRef, Orden, Producto, ReputacionUsuario, Rol,
+    AportePonderado, CalificacionOrden, Categoria, CursorBusqueda, EdicionCalificacion,
+    EntregaDigital, Error, EstadisticasReferido, Estado, EstadoFondos, EventoOrden, FiltroBusqueda,
+    Lanzamiento, Marketplace, MarketplaceRef, MensajeOrden, MigracionEnCurso, Orden, OrdenBusqueda,
+    PaginaBusqueda, Perfil, PlanSuscripcion, Producto, PuntajePonderado, ReferidoOrden,
+    RegistroOrden, ReputacionPonderada, ReputacionUsuario, RequisitoFianza, RequisitoReputacion,
+    Reserva, Rol, Suscripcion, TipoProducto,
 };
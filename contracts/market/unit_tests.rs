@@ -15,9 +15,22 @@ mod tests {
         test::set_value_transferred::<DefaultEnvironment>(amount);
     }
 
+    const CAT: u32 = 1;
+    const ELECTRONICA: u32 = 4;
+
+    /// Crea un marketplace con alice como admin y las categorías usadas en los tests.
+    fn nuevo_marketplace() -> Marketplace {
+        set_next_caller(get_accounts().alice);
+        let mut mp = Marketplace::new();
+        for nombre in ["Cat", "Cat1", "Cat2", "Electrónica"] {
+            mp.crear_categoria(nombre.to_string(), None).unwrap();
+        }
+        mp
+    }
+
     fn setup_vendedor() -> (test::DefaultAccounts<DefaultEnvironment>, Marketplace) {
         let accounts = get_accounts();
-        let mut mp = Marketplace::new();
+        let mut mp = nuevo_marketplace();
         set_next_caller(accounts.alice);
         mp.registrar(Rol::Vendedor).unwrap();
         (accounts, mp)
@@ -96,6 +109,493 @@ mod tests {
         assert_eq!(mp.registrar(Rol::Vendedor), Err(Error::YaRegistrado));
     }
 
+    #[ink::test]
+    fn registrar_con_perfil_y_actualizar() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
+        let hash = Hash::from([7; 32]);
+
+        test::set_block_number::<DefaultEnvironment>(42);
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.registrar_con_perfil(
+                Rol::Vendedor,
+                "Tienda Alice".to_string(),
+                "ipfs://avatar".to_string(),
+                Some(hash),
+                None,
+            ),
+            Ok(())
+        );
+        assert_eq!(mp.obtener_rol(accounts.alice), Some(Rol::Vendedor));
+
+        let perfil = mp.obtener_perfil(accounts.alice).unwrap();
+        assert_eq!(perfil.nombre_visible, "Tienda Alice");
+        assert_eq!(perfil.uri_metadata, "ipfs://avatar");
+        assert_eq!(perfil.hash_contenido, Some(hash));
+        assert_eq!(perfil.hash_contacto, None);
+        assert_eq!(perfil.bloque_registro, 42);
+        assert_eq!(
+            mp.buscar_usuario_por_nombre("tienda alice".to_string()),
+            Some(accounts.alice)
+        );
+
+        assert_eq!(
+            mp.actualizar_perfil("Alice".to_string(), "".to_string(), None, Some(hash)),
+            Ok(())
+        );
+        assert_eq!(mp.buscar_usuario_por_nombre("Tienda Alice".to_string()), None);
+        assert_eq!(mp.buscar_usuario_por_nombre("ALICE".to_string()), Some(accounts.alice));
+        assert_eq!(mp.obtener_perfil(accounts.alice).unwrap().bloque_registro, 42);
+
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        let perfil_bob = mp.obtener_perfil(accounts.bob).unwrap();
+        assert_eq!(perfil_bob.nombre_visible, "");
+        assert_eq!(perfil_bob.bloque_registro, 42);
+        assert_eq!(
+            mp.actualizar_perfil("Tienda Alice".to_string(), "".to_string(), None, None),
+            Ok(())
+        );
+    }
+
+    #[ink::test]
+    fn perfil_errores() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.actualizar_perfil("Alice".to_string(), "".to_string(), None, None),
+            Err(Error::SinRegistro)
+        );
+        mp.registrar_con_perfil(Rol::Ambos, "Alice".to_string(), "".to_string(), None, None)
+            .unwrap();
+        assert_eq!(
+            mp.registrar_con_perfil(Rol::Ambos, "Otra".to_string(), "".to_string(), None, None),
+            Err(Error::YaRegistrado)
+        );
+
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            mp.registrar_con_perfil(Rol::Comprador, "alice".to_string(), "".to_string(), None, None),
+            Err(Error::NombreEnUso)
+        );
+        assert_eq!(mp.obtener_rol(accounts.bob), None);
+        assert_eq!(
+            mp.registrar_con_perfil(Rol::Comprador, "a".repeat(65), "".to_string(), None, None),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(
+            mp.registrar_con_perfil(Rol::Comprador, " Bob".to_string(), "".to_string(), None, None),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(
+            mp.registrar_con_perfil(Rol::Comprador, "Bob".to_string(), "u".repeat(129), None, None),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(mp.obtener_perfil(accounts.bob), None);
+    }
+
+    #[ink::test]
+    fn darse_de_baja_exitoso() {
+        let (accounts, mut mp, pid, oid) = setup_orden_recibida();
+
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid, 5).unwrap();
+
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+        assert_eq!(
+            mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA),
+            vec![accounts.alice, accounts.bob, accounts.charlie]
+        );
+
+        set_next_caller(accounts.alice);
+        mp.actualizar_perfil("Alice".to_string(), "".to_string(), None, None)
+            .unwrap();
+        assert_eq!(mp.darse_de_baja(), Ok(()));
+
+        assert_eq!(mp.obtener_rol(accounts.alice), None);
+        assert_eq!(mp.obtener_perfil(accounts.alice), None);
+        assert_eq!(mp.buscar_usuario_por_nombre("Alice".to_string()), None);
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA), vec![accounts.charlie, accounts.bob]);
+        assert!(!mp.obtener_producto(pid).unwrap().activo);
+        assert_eq!(mp.obtener_orden_publica(oid).unwrap().vendedor, accounts.alice);
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (5, 1));
+
+        set_next_caller(accounts.charlie);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::ProductoInactivo));
+        assert_eq!(mp.darse_de_baja(), Ok(()));
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA), vec![accounts.bob]);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.darse_de_baja(), Err(Error::SinRegistro));
+        assert_eq!(mp.registrar(Rol::Vendedor), Ok(()));
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA), vec![accounts.bob, accounts.alice]);
+        assert!(!mp.obtener_producto(pid).unwrap().activo);
+        let nuevo = mp.publicar_en_categoria("Nuevo".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+        assert!(mp.obtener_producto(nuevo).unwrap().activo);
+        assert_eq!(mp.reactivar_producto(pid), Ok(()));
+        assert!(mp.obtener_producto(pid).unwrap().activo);
+    }
+
+    #[ink::test]
+    fn darse_de_baja_con_ordenes_abiertas() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (1, 0));
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 1));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.darse_de_baja(), Err(Error::OrdenesAbiertas));
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.darse_de_baja(), Err(Error::OrdenesAbiertas));
+
+        mp.solicitar_cancelacion(oid).unwrap();
+        assert_eq!(mp.darse_de_baja(), Err(Error::OrdenesAbiertas));
+
+        set_next_caller(accounts.alice);
+        let _ = mp.aceptar_cancelacion(oid);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 0));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.darse_de_baja(), Ok(()));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_vendedor() {
+        let (accounts, mut mp, pid, oid_cerrada) = setup_orden_recibida();
+
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid_cerrada, 4).unwrap();
+        set_value(200);
+        let oid_abierta = mp.comprar(pid, 2).unwrap();
+
+        set_next_caller(accounts.alice);
+        mp.actualizar_perfil("Alice".to_string(), "".to_string(), None, None)
+            .unwrap();
+        mp.solicitar_cancelacion(oid_abierta).unwrap();
+        assert_eq!(mp.iniciar_migracion(accounts.django), Ok(()));
+        assert_eq!(mp.obtener_migracion_pendiente(accounts.alice), Some(accounts.django));
+
+        set_next_caller(accounts.eve);
+        assert_eq!(mp.aceptar_migracion(accounts.alice), Err(Error::MigracionInexistente));
+
+        set_next_caller(accounts.django);
+        assert_eq!(mp.aceptar_migracion(accounts.alice), Ok(()));
+
+        assert_eq!(mp.obtener_rol(accounts.alice), None);
+        assert_eq!(mp.obtener_rol(accounts.django), Some(Rol::Vendedor));
+        assert_eq!(mp.obtener_migracion_pendiente(accounts.alice), None);
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA), vec![accounts.django, accounts.bob]);
+        assert_eq!(mp.buscar_usuario_por_nombre("Alice".to_string()), Some(accounts.django));
+        assert_eq!(mp.obtener_reputacion(accounts.alice), None);
+        assert_eq!(mp.obtener_reputacion(accounts.django).unwrap().como_vendedor, (4, 1));
+        assert_eq!(mp.obtener_producto(pid).unwrap().vendedor, accounts.django);
+        let de_django = FiltroBusqueda {
+            vendedor: Some(accounts.django),
+            ..Default::default()
+        };
+        assert_eq!(
+            mp.buscar_productos(de_django, OrdenBusqueda::MasRecientes, None, 10)
+                .unwrap()
+                .productos
+                .len(),
+            1
+        );
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.django), (0, 1));
+
+        assert_eq!(mp.obtener_orden_publica(oid_cerrada).unwrap().vendedor, accounts.alice);
+        assert_eq!(mp.obtener_orden_publica(oid_abierta).unwrap().vendedor, accounts.django);
+
+        set_next_caller(accounts.bob);
+        assert!(mp.aceptar_cancelacion(oid_abierta).is_ok());
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.django), (0, 0));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_conserva_calificaciones() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
+        set_next_caller(accounts.alice);
+        mp.calificar_comprador(oid, 4).unwrap();
+        let saldo = mp.obtener_saldo_retirable(accounts.alice);
+        assert!(saldo > 0);
+        mp.iniciar_migracion(accounts.django).unwrap();
+        set_next_caller(accounts.django);
+        mp.aceptar_migracion(accounts.alice).unwrap();
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 0);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.django), saldo);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.calificar_vendedor(oid, 5), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.django).unwrap().como_vendedor, (5, 1));
+        assert_eq!(mp.obtener_reputacion(accounts.alice), None);
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 2), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.django).unwrap().como_vendedor, (2, 1));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.editar_calificacion_comprador(oid, 1), Err(Error::SinPermiso));
+        assert_eq!(mp.registrar(Rol::Comprador), Err(Error::CuentaMigrada));
+        set_next_caller(accounts.django);
+        assert_eq!(mp.editar_calificacion_comprador(oid, 1), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.bob).unwrap().como_comprador, (1, 1));
+
+        set_next_caller(accounts.bob);
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        mp.aceptar_migracion(accounts.bob).unwrap();
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 4), Err(Error::YaEditado));
+        assert_eq!(mp.obtener_reputacion(accounts.eve).unwrap().como_comprador, (1, 1));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_errores() {
+        let (accounts, mut mp, _) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.iniciar_migracion(accounts.django), Err(Error::SinRegistro));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.iniciar_migracion(accounts.alice), Err(Error::CuentaDestinoInvalida));
+        assert_eq!(mp.iniciar_migracion(accounts.bob), Err(Error::CuentaDestinoInvalida));
+
+        assert_eq!(mp.cancelar_migracion(), Err(Error::MigracionInexistente));
+        mp.iniciar_migracion(accounts.charlie).unwrap();
+        assert_eq!(mp.cancelar_migracion(), Ok(()));
+        assert_eq!(mp.cancelar_migracion(), Err(Error::MigracionInexistente));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.aceptar_migracion(accounts.alice), Err(Error::MigracionInexistente));
+
+        set_next_caller(accounts.alice);
+        mp.iniciar_migracion(accounts.charlie).unwrap();
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+        assert_eq!(mp.aceptar_migracion(accounts.alice), Err(Error::CuentaDestinoInvalida));
+        assert_eq!(mp.obtener_rol(accounts.alice), Some(Rol::Vendedor));
+
+        set_next_caller(accounts.bob);
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        mp.darse_de_baja().unwrap();
+        assert_eq!(mp.obtener_migracion_pendiente(accounts.bob), None);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_next_caller(accounts.eve);
+        assert_eq!(mp.aceptar_migracion(accounts.bob), Err(Error::MigracionInexistente));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_por_lotes_con_bloqueos() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.alice);
+        mp.bloquear_comprador(accounts.charlie).unwrap();
+        for i in 0..MAX_MIGRACION_POR_LLAMADA {
+            mp.publicar_en_categoria(format!("P{i}"), "D".to_string(), 100, 1, CAT).unwrap();
+        }
+        set_next_caller(accounts.bob);
+        set_value(100);
+        let oid = mp.comprar(pid, 1).unwrap();
+
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+
+        set_next_caller(accounts.alice);
+        mp.iniciar_migracion(accounts.django).unwrap();
+        set_next_caller(accounts.django);
+        assert_eq!(mp.aceptar_migracion(accounts.alice), Ok(()));
+
+        let migracion = mp.obtener_migracion_en_curso(accounts.django).unwrap();
+        assert_eq!(
+            (migracion.bloqueos, migracion.productos, migracion.ordenes),
+            (1, MAX_MIGRACION_POR_LLAMADA - 1, 0)
+        );
+        assert_eq!(mp.obtener_orden_publica(oid).unwrap().vendedor, accounts.alice);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 1));
+        assert_eq!(mp.darse_de_baja(), Err(Error::MigracionEnCurso));
+        assert_eq!(mp.iniciar_migracion(accounts.eve), Err(Error::MigracionEnCurso));
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.registrar(Rol::Comprador), Err(Error::MigracionEnCurso));
+
+        set_next_caller(accounts.django);
+        assert_eq!(mp.continuar_migracion(), Ok(true));
+        assert_eq!(mp.obtener_migracion_en_curso(accounts.django), None);
+        assert_eq!(mp.continuar_migracion(), Err(Error::MigracionInexistente));
+        assert_eq!(mp.obtener_orden_publica(oid).unwrap().vendedor, accounts.django);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.django), (0, 1));
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 0));
+        assert_eq!(mp.listar_productos_de_vendedor(accounts.django).len(), 51);
+
+        assert!(!mp.esta_bloqueado(accounts.alice, accounts.charlie));
+        assert!(mp.esta_bloqueado(accounts.django, accounts.charlie));
+        set_next_caller(accounts.charlie);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::CompradorBloqueado));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_traslada_bloqueos_recibidos() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.alice);
+        mp.bloquear_comprador(accounts.bob).unwrap();
+        mp.desbloquear_comprador(accounts.bob).unwrap();
+        mp.bloquear_comprador(accounts.bob).unwrap();
+        for i in 0..MAX_MIGRACION_POR_LLAMADA {
+            mp.publicar_en_categoria(format!("P{i}"), "D".to_string(), 100, 1, CAT).unwrap();
+        }
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.iniciar_migracion(accounts.eve), Ok(()));
+
+        set_next_caller(accounts.alice);
+        mp.iniciar_migracion(accounts.django).unwrap();
+        set_next_caller(accounts.django);
+        mp.aceptar_migracion(accounts.alice).unwrap();
+        assert!(mp.esta_bloqueado(accounts.django, accounts.bob));
+        assert!(!mp.esta_bloqueado(accounts.alice, accounts.bob));
+
+        set_next_caller(accounts.eve);
+        assert_eq!(mp.aceptar_migracion(accounts.bob), Ok(()));
+        assert_eq!(mp.obtener_migracion_en_curso(accounts.eve), None);
+        assert!(mp.esta_bloqueado(accounts.django, accounts.eve));
+        assert!(!mp.esta_bloqueado(accounts.django, accounts.bob));
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::CompradorBloqueado));
+
+        set_next_caller(accounts.django);
+        mp.desbloquear_comprador(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        set_value(100);
+        assert!(mp.comprar(pid, 1).is_ok());
+    }
+
+    #[ink::test]
+    fn migracion_en_curso_respeta_bloqueos_sin_trasladar() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        mp.modificar_rol(Rol::Ambos).unwrap();
+        for i in 0..MAX_MIGRACION_POR_LLAMADA {
+            mp.bloquear_comprador(AccountId::from([i as u8 + 100; 32])).unwrap();
+        }
+        set_next_caller(accounts.alice);
+        mp.bloquear_comprador(accounts.bob).unwrap();
+
+        set_next_caller(accounts.bob);
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        mp.aceptar_migracion(accounts.bob).unwrap();
+        let migracion = mp.obtener_migracion_en_curso(accounts.eve).unwrap();
+        assert_eq!(
+            (migracion.bloqueos, migracion.bloqueos_recibidos),
+            (MAX_MIGRACION_POR_LLAMADA, 0)
+        );
+        assert!(mp.esta_bloqueado(accounts.alice, accounts.eve));
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::CompradorBloqueado));
+
+        assert_eq!(mp.continuar_migracion(), Ok(true));
+        assert!(mp.esta_bloqueado(accounts.alice, accounts.eve));
+        assert!(!mp.esta_bloqueado(accounts.alice, accounts.bob));
+        assert!(mp.esta_bloqueado(accounts.eve, AccountId::from([100; 32])));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_traslada_fianza_y_reputacion_ponderada() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid, 5).unwrap();
+        let ponderada = mp.obtener_reputacion_ponderada(accounts.alice).unwrap();
+        set_next_caller(accounts.alice);
+        set_value(500);
+        mp.depositar_fianza().unwrap();
+
+        mp.iniciar_migracion(accounts.django).unwrap();
+        set_next_caller(accounts.django);
+        mp.aceptar_migracion(accounts.alice).unwrap();
+        assert_eq!(mp.obtener_fianza(accounts.alice), 0);
+        assert_eq!(mp.obtener_fianza(accounts.django), 500);
+        assert_eq!(mp.obtener_reputacion_ponderada(accounts.alice), None);
+        assert_eq!(mp.obtener_reputacion_ponderada(accounts.django), Some(ponderada));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_traslada_favoritos_y_referidos() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.alice);
+        mp.configurar_referidos(pid, 10).unwrap();
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_next_caller(accounts.bob);
+        mp.agregar_favorito(pid).unwrap();
+        set_value(100);
+        let oid = mp.comprar_con_referido(pid, 1, accounts.charlie).unwrap();
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid).unwrap();
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid).unwrap();
+        let estadisticas = mp.obtener_estadisticas_referido(accounts.charlie);
+
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        mp.aceptar_migracion(accounts.bob).unwrap();
+        assert_eq!(mp.listar_favoritos(accounts.bob, 0, 10), Vec::<u32>::new());
+        assert_eq!(mp.listar_favoritos(accounts.eve, 0, 10), vec![pid]);
+        assert_eq!(mp.obtener_cantidad_favoritos(pid), 1);
+
+        set_next_caller(accounts.charlie);
+        mp.iniciar_migracion(accounts.frank).unwrap();
+        set_next_caller(accounts.frank);
+        mp.aceptar_migracion(accounts.charlie).unwrap();
+        assert_eq!(mp.obtener_estadisticas_referido(accounts.frank), estadisticas);
+        assert_eq!(
+            mp.obtener_estadisticas_referido(accounts.charlie),
+            EstadisticasReferido::default()
+        );
+        assert_eq!(mp.listar_mejores_referidos(), vec![(accounts.frank, estadisticas)]);
+    }
+
+    #[ink::test]
+    fn migrar_identidad_traslada_suscripciones() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(3);
+        set_next_caller(accounts.bob);
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        mp.aceptar_migracion(accounts.bob).unwrap();
+        assert_eq!(mp.obtener_suscripcion(sid).unwrap().comprador, accounts.eve);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.eve), (1, 0));
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.cancelar_suscripcion(sid), Err(Error::SinPermiso));
+        set_next_caller(accounts.eve);
+        assert_eq!(mp.cancelar_suscripcion(sid), Ok(()));
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.eve), (0, 0));
+    }
+
+    #[ink::test]
+    fn migrar_identidad_traslada_reservas_y_mensajes() {
+        let (accounts, mut mp, pid, oid) = setup_con_orden(1, 100);
+        set_next_caller(accounts.bob);
+        set_value(10);
+        mp.reservar(pid, 2).unwrap();
+        let reserva = mp.obtener_reserva(accounts.bob, pid).unwrap();
+        for _ in 1..MAX_MENSAJES_POR_PARTICIPANTE {
+            mp.enviar_mensaje(oid, vec![1]).unwrap();
+        }
+
+        mp.iniciar_migracion(accounts.eve).unwrap();
+        set_next_caller(accounts.eve);
+        mp.aceptar_migracion(accounts.bob).unwrap();
+        assert_eq!(mp.obtener_reserva(accounts.bob, pid), None);
+        assert_eq!(mp.obtener_reserva(accounts.eve, pid), Some(reserva));
+        assert_eq!(mp.enviar_mensaje(oid, vec![1]), Ok(()));
+        assert_eq!(mp.enviar_mensaje(oid, vec![1]), Err(Error::MensajesLlenos));
+
+        test::set_block_number::<DefaultEnvironment>(DURACION_RESERVA_BLOQUES);
+        assert_eq!(mp.liberar_reserva(accounts.bob, pid), Err(Error::ReservaInexistente));
+        assert_eq!(mp.liberar_reserva(accounts.eve, pid), Ok(()));
+        assert!(mp.obtener_saldo_retirable(accounts.eve) > 0);
+    }
+
     #[ink::test]
     fn modificar_rol_casos() {
         let accounts = get_accounts();
@@ -116,6 +616,63 @@ mod tests {
         assert_eq!(mp.obtener_rol(accounts.bob), Some(Rol::Ambos));
     }
 
+    #[ink::test]
+    fn modificar_rol_con_ventas_pendientes() {
+        let (accounts, mut mp, pid, oid) = setup_con_orden(1, 100);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.modificar_rol(Rol::Comprador), Err(Error::VentasPendientes));
+        assert_eq!(mp.obtener_rol(accounts.alice), Some(Rol::Vendedor));
+        assert!(mp.obtener_producto(pid).unwrap().activo);
+
+        assert_eq!(mp.modificar_rol(Rol::Ambos), Ok(()));
+
+        mp.marcar_enviado(oid).unwrap();
+        set_next_caller(accounts.bob);
+        let _ = mp.marcar_recibido(oid);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.modificar_rol(Rol::Comprador), Ok(()));
+        assert!(!mp.obtener_producto(pid).unwrap().activo);
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::ProductoInactivo));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.reactivar_producto(pid), Err(Error::SinPermiso));
+        mp.modificar_rol(Rol::Vendedor).unwrap();
+        assert_eq!(mp.reactivar_producto(pid), Ok(()));
+        assert!(mp.obtener_producto(pid).unwrap().activo);
+    }
+
+    #[ink::test]
+    fn pausar_y_reactivar_producto() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.pausar_producto(pid), Err(Error::SinPermiso));
+        assert_eq!(mp.reactivar_producto(pid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.eve);
+        assert_eq!(mp.reactivar_producto(pid), Err(Error::SinRegistro));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.pausar_producto(999), Err(Error::ProdInexistente));
+        assert_eq!(mp.pausar_producto(pid), Ok(()));
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::ProductoInactivo));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.reactivar_producto(pid), Ok(()));
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert!(mp.comprar(pid, 1).is_ok());
+    }
+
     #[ink::test]
     fn publicar_producto_exitoso() {
         let (accounts, mut mp) = setup_vendedor();
@@ -136,7 +693,10 @@ mod tests {
         assert_eq!(producto.descripcion, "Laptop gaming de alta gama");
         assert_eq!(producto.precio, 1500);
         assert_eq!(producto.stock, 5);
-        assert_eq!(producto.categoria, "Electrónica");
+        assert_eq!(
+            mp.obtener_categoria(producto.id_categoria).unwrap().nombre,
+            "Electrónica"
+        );
     }
 
     #[ink::test]
@@ -203,15 +763,379 @@ mod tests {
     }
 
     #[ink::test]
-    fn listar_productos_de_vendedor() {
-        let (accounts, mut mp) = setup_vendedor();
-
-        assert!(mp.listar_productos_de_vendedor(accounts.alice).is_empty());
+    fn registro_de_categorias() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
 
-        mp.publicar("Producto1".to_string(), "Desc1".to_string(), 100, 5, "Cat1".to_string()).unwrap();
-        mp.publicar("Producto2".to_string(), "Desc2".to_string(), 200, 10, "Cat2".to_string()).unwrap();
+        let hogar = mp.crear_categoria("Hogar".to_string(), None).unwrap();
+        let cocina = mp.crear_categoria("Cocina".to_string(), Some(hogar)).unwrap();
+        assert_eq!(
+            mp.obtener_categoria(cocina),
+            Some(Categoria {
+                nombre: "Cocina".to_string(),
+                padre: Some(hogar),
+            })
+        );
+        assert_eq!(mp.listar_categorias_registradas().len(), 6);
+        assert_eq!(mp.buscar_categoria("electronica".to_string()), Some(ELECTRONICA));
+        assert_eq!(mp.buscar_categoria("COCINA".to_string()), Some(cocina));
+        assert_eq!(mp.buscar_categoria("Jardín".to_string()), None);
 
-        let productos = mp.listar_productos_de_vendedor(accounts.alice);
+        assert_eq!(
+            mp.crear_categoria("ELECTRONICA".to_string(), None),
+            Err(Error::CategoriaDuplicada)
+        );
+        assert_eq!(
+            mp.crear_categoria("Electronica ".to_string(), None),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(mp.crear_categoria("".to_string(), None), Err(Error::ParamInvalido));
+        assert_eq!(mp.crear_categoria("a".repeat(33), None), Err(Error::ParamInvalido));
+        assert_eq!(
+            mp.crear_categoria("Jardín".to_string(), Some(99)),
+            Err(Error::CategoriaInexistente)
+        );
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.crear_categoria("Jardín".to_string(), None), Err(Error::SinPermiso));
+
+        mp.registrar(Rol::Vendedor).unwrap();
+        let pid = mp
+            .publicar_en_categoria("Sartén".to_string(), "Desc".to_string(), 100, 5, cocina)
+            .unwrap();
+        let producto = mp.obtener_producto(pid).unwrap();
+        assert_eq!(producto.id_categoria, cocina);
+    }
+
+    fn ids(pagina: PaginaBusqueda) -> Vec<u32> {
+        pagina.productos.into_iter().map(|(pid, _)| pid).collect()
+    }
+
+    #[ink::test]
+    fn buscar_productos_filtros() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
+        let hogar = mp.crear_categoria("Hogar".to_string(), None).unwrap();
+        let cocina = mp.crear_categoria("Cocina".to_string(), Some(hogar)).unwrap();
+
+        mp.registrar(Rol::Vendedor).unwrap();
+        let mesa = mp.publicar_en_categoria("Mesa".to_string(), "D".to_string(), 300, 1, hogar).unwrap();
+        let sarten = mp.publicar_en_categoria("Sartén".to_string(), "D".to_string(), 50, 5, cocina).unwrap();
+        let laptop = mp.publicar_en_categoria("Laptop".to_string(), "D".to_string(), 900, 2, ELECTRONICA).unwrap();
+
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Ambos).unwrap();
+        let olla = mp.publicar_en_categoria("Olla".to_string(), "D".to_string(), 80, 3, cocina).unwrap();
+
+        assert_eq!(
+            ids(mp.buscar_productos(FiltroBusqueda::default(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![sarten, olla, mesa, laptop]
+        );
+
+        let por_categoria = FiltroBusqueda {
+            id_categoria: Some(hogar),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(por_categoria.clone(), OrdenBusqueda::MasRecientes, None, 10).unwrap()),
+            vec![mesa]
+        );
+        let arbol = FiltroBusqueda {
+            incluir_subcategorias: true,
+            ..por_categoria
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(arbol.clone(), OrdenBusqueda::MasRecientes, None, 10).unwrap()),
+            vec![olla, sarten, mesa]
+        );
+
+        let rango = FiltroBusqueda {
+            precio_min: Some(60),
+            precio_max: Some(300),
+            ..arbol.clone()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(rango, OrdenBusqueda::PrecioDescendente, None, 10).unwrap()),
+            vec![mesa, olla]
+        );
+
+        let de_bob = FiltroBusqueda {
+            vendedor: Some(accounts.bob),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(de_bob, OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![olla]
+        );
+
+        let texto = FiltroBusqueda {
+            texto: Some("LAP".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(texto.clone(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![laptop]
+        );
+        let texto_en_hogar = FiltroBusqueda {
+            id_categoria: Some(hogar),
+            ..texto
+        };
+        assert!(mp
+            .buscar_productos(texto_en_hogar, OrdenBusqueda::PrecioAscendente, None, 10)
+            .unwrap()
+            .productos
+            .is_empty());
+
+        set_value(300);
+        mp.comprar(mesa, 1).unwrap();
+        let con_stock = FiltroBusqueda {
+            solo_con_stock: true,
+            ..arbol.clone()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(con_stock, OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![sarten, olla]
+        );
+
+        set_next_caller(accounts.alice);
+        mp.pausar_producto(sarten).unwrap();
+        assert_eq!(
+            ids(mp.buscar_productos(arbol.clone(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![olla, mesa]
+        );
+
+        // Las páginas siguen el orden global y saltean el producto pausado.
+        let primera = mp
+            .buscar_productos(arbol.clone(), OrdenBusqueda::PrecioAscendente, None, 1)
+            .unwrap();
+        assert_eq!(ids(primera.clone()), vec![olla]);
+        let segunda = mp
+            .buscar_productos(arbol.clone(), OrdenBusqueda::PrecioAscendente, primera.siguiente, 1)
+            .unwrap();
+        assert_eq!(ids(segunda.clone()), vec![mesa]);
+        assert_eq!(segunda.siguiente, None);
+
+        let recientes = mp
+            .buscar_productos(arbol.clone(), OrdenBusqueda::MasRecientes, None, 1)
+            .unwrap();
+        assert_eq!(ids(recientes.clone()), vec![olla]);
+        assert_eq!(
+            ids(mp.buscar_productos(arbol.clone(), OrdenBusqueda::MasRecientes, recientes.siguiente, 5).unwrap()),
+            vec![mesa]
+        );
+        assert_eq!(
+            mp.buscar_productos(arbol.clone(), OrdenBusqueda::MejorCalificados, recientes.siguiente, 5),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(
+            mp.buscar_productos(arbol, OrdenBusqueda::PrecioAscendente, None, 0),
+            Err(Error::ParamInvalido)
+        );
+    }
+
+    #[ink::test]
+    fn buscar_productos_por_reputacion_y_errores() {
+        let (accounts, mut mp, pid, oid) = setup_orden_recibida();
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid, 3).unwrap();
+
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Vendedor).unwrap();
+        let nuevo = mp.publicar_en_categoria("Nuevo".to_string(), "D".to_string(), 100, 5, CAT).unwrap();
+
+        let en_cat = FiltroBusqueda {
+            id_categoria: Some(CAT),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(en_cat.clone(), OrdenBusqueda::MejorCalificados, None, 10).unwrap()),
+            vec![pid, nuevo]
+        );
+        let primera = mp
+            .buscar_productos(en_cat.clone(), OrdenBusqueda::MejorCalificados, None, 1)
+            .unwrap();
+        assert_eq!(ids(primera.clone()), vec![pid]);
+        let segunda = mp
+            .buscar_productos(en_cat.clone(), OrdenBusqueda::MejorCalificados, primera.siguiente, 1)
+            .unwrap();
+        assert_eq!(ids(segunda.clone()), vec![nuevo]);
+        assert_eq!(segunda.siguiente, None);
+
+        let exigente = FiltroBusqueda {
+            reputacion_minima_x100: Some(300),
+            ..en_cat
+        };
+        assert_eq!(
+            ids(mp.buscar_productos(exigente.clone(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![pid]
+        );
+        assert_eq!(
+            ids(mp.buscar_productos(exigente, OrdenBusqueda::MejorCalificados, None, 10).unwrap()),
+            vec![pid]
+        );
+
+        let invertido = FiltroBusqueda {
+            precio_min: Some(200),
+            precio_max: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            mp.buscar_productos(invertido, OrdenBusqueda::PrecioAscendente, None, 10),
+            Err(Error::ParamInvalido)
+        );
+        let sin_categoria = FiltroBusqueda {
+            id_categoria: Some(99),
+            ..Default::default()
+        };
+        assert_eq!(
+            mp.buscar_productos(sin_categoria, OrdenBusqueda::PrecioAscendente, None, 10),
+            Err(Error::CategoriaInexistente)
+        );
+    }
+
+    #[ink::test]
+    fn buscar_productos_pagina_un_indice_de_varios_bloques() {
+        let (_, mut mp) = setup_vendedor();
+        for i in 0..150u128 {
+            let precio = (i * 37) % 150 + 1;
+            mp.publicar_en_categoria(format!("P{i}"), "D".to_string(), precio, 1, CAT).unwrap();
+        }
+
+        for orden in [OrdenBusqueda::PrecioAscendente, OrdenBusqueda::PrecioDescendente] {
+            let mut precios = Vec::new();
+            let mut cursor = None;
+            loop {
+                let pagina = mp
+                    .buscar_productos(FiltroBusqueda::default(), orden, cursor, MAX_RESULTADOS_BUSQUEDA)
+                    .unwrap();
+                precios.extend(pagina.productos.iter().map(|(_, p)| p.precio));
+                cursor = pagina.siguiente;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            let mut esperados: Vec<Balance> = (1..=150).collect();
+            if orden == OrdenBusqueda::PrecioDescendente {
+                esperados.reverse();
+            }
+            assert_eq!(precios, esperados);
+        }
+    }
+
+    #[ink::test]
+    fn indice_de_busqueda_escrituras_en_el_peor_caso() {
+        let mut mp = nuevo_marketplace();
+        let indice = (CriterioIndice::Precio, AlcanceIndice::Todos);
+        let contrato = test::callee::<DefaultEnvironment>();
+        let escrituras = || test::get_contract_storage_rw::<DefaultEnvironment>(&contrato).1;
+        let bloques = |mp: &Marketplace| mp.total_cabeceras_busqueda.get(indice).unwrap_or_default().0;
+
+        // Cada par nuevo es el menor del índice: va al primer bloque, y al dividirlo se
+        // corren todas las cabeceras siguientes.
+        let mut divisiones = 0;
+        for pid in 0..2_000u32 {
+            let (previos, antes) = (bloques(&mp), escrituras());
+            mp.insertar_en_indice(indice, (u128::from(10_000 - pid), pid)).unwrap();
+            let costo = escrituras() - antes;
+            if bloques(&mp) > previos.max(1) {
+                divisiones += 1;
+                assert_eq!(costo, previos as usize + 4);
+            } else if previos > 0 {
+                assert_eq!(costo, 3);
+            }
+        }
+        assert!(divisiones > 50);
+
+        // Vaciar el primer bloque también corre todas las cabeceras siguientes.
+        let mut pid = 2_000u32;
+        loop {
+            pid -= 1;
+            let (previos, antes) = (bloques(&mp), escrituras());
+            mp.quitar_de_indice(indice, (u128::from(10_000 - pid), pid));
+            let costo = escrituras() - antes;
+            if bloques(&mp) < previos {
+                assert_eq!(costo, previos as usize + 2);
+                break;
+            }
+            assert_eq!(costo, 2);
+        }
+    }
+
+    #[ink::test]
+    fn pausar_productos_los_quita_de_los_indices() {
+        let (_, mut mp) = setup_vendedor();
+        for precio in 1..=120u128 {
+            mp.publicar_en_categoria(format!("P{precio}"), "D".to_string(), precio, 1, CAT).unwrap();
+        }
+        let indice = (CriterioIndice::Precio, AlcanceIndice::Todos);
+        let (bloques, _) = mp.total_cabeceras_busqueda.get(indice).unwrap();
+        assert!(bloques > 2);
+
+        for pid in 1..=80 {
+            mp.pausar_producto(pid).unwrap();
+        }
+        assert_eq!(mp.pausar_producto(1), Ok(()));
+        let (restantes, _) = mp.total_cabeceras_busqueda.get(indice).unwrap();
+        assert!(restantes < bloques);
+        let precios = |mp: &Marketplace, orden| {
+            mp.buscar_productos(FiltroBusqueda::default(), orden, None, MAX_RESULTADOS_BUSQUEDA)
+                .unwrap()
+                .productos
+                .iter()
+                .map(|(_, p)| p.precio)
+                .collect::<Vec<_>>()
+        };
+        let esperados: Vec<Balance> = (81..=120).collect();
+        assert_eq!(precios(&mp, OrdenBusqueda::PrecioAscendente), esperados);
+
+        mp.reactivar_producto(7).unwrap();
+        assert_eq!(mp.reactivar_producto(7), Ok(()));
+        let mut esperados = esperados;
+        esperados.insert(0, 7);
+        assert_eq!(precios(&mp, OrdenBusqueda::PrecioAscendente), esperados);
+        esperados.reverse();
+        assert_eq!(precios(&mp, OrdenBusqueda::PrecioDescendente), esperados);
+    }
+
+    #[ink::test]
+    fn publicar_resuelve_categoria_por_nombre() {
+        let (_, mut mp) = setup_vendedor();
+
+        let pid = mp
+            .publicar("Radio".to_string(), "Desc".to_string(), 100, 5, " ELECTRONICA ".to_string())
+            .unwrap();
+        assert_eq!(mp.obtener_producto(pid).unwrap().id_categoria, ELECTRONICA);
+
+        let pid = mp
+            .publicar("Pala".to_string(), "Desc".to_string(), 100, 5, "Jardín".to_string())
+            .unwrap();
+        let id_jardin = mp.obtener_producto(pid).unwrap().id_categoria;
+        assert_eq!(mp.buscar_categoria("jardin".to_string()), Some(id_jardin));
+        assert_eq!(
+            mp.obtener_categoria(id_jardin),
+            Some(Categoria { nombre: "Jardín".to_string(), padre: None })
+        );
+
+        assert_eq!(
+            mp.publicar_en_categoria("Test".to_string(), "Desc".to_string(), 100, 5, 99),
+            Err(Error::CategoriaInexistente)
+        );
+        let pid = mp
+            .publicar_en_categoria("Test".to_string(), "Desc".to_string(), 100, 5, id_jardin)
+            .unwrap();
+        assert_eq!(mp.obtener_producto(pid).unwrap().id_categoria, id_jardin);
+    }
+
+    #[ink::test]
+    fn listar_productos_de_vendedor() {
+        let (accounts, mut mp) = setup_vendedor();
+
+        assert!(mp.listar_productos_de_vendedor(accounts.alice).is_empty());
+
+        mp.publicar("Producto1".to_string(), "Desc1".to_string(), 100, 5, "Cat1".to_string()).unwrap();
+        mp.publicar("Producto2".to_string(), "Desc2".to_string(), 200, 10, "Cat2".to_string()).unwrap();
+
+        let productos = mp.listar_productos_de_vendedor(accounts.alice);
         assert_eq!(productos.len(), 2);
         assert_eq!(productos[0].nombre, "Producto1");
         assert_eq!(productos[1].nombre, "Producto2");
@@ -281,6 +1205,182 @@ mod tests {
         assert_eq!(mp.comprar(pid, 1), Err(Error::AutoCompraProhibida));
     }
 
+    #[ink::test]
+    fn comprador_bloqueado_no_puede_comprar() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.bloquear_comprador(accounts.bob), Ok(()));
+        assert!(mp.esta_bloqueado(accounts.alice, accounts.bob));
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::CompradorBloqueado));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.desbloquear_comprador(accounts.bob), Ok(()));
+        assert!(!mp.esta_bloqueado(accounts.alice, accounts.bob));
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert!(mp.comprar(pid, 1).is_ok());
+    }
+
+    #[ink::test]
+    fn comprador_bloqueado_no_puede_solicitar_cancelacion() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+
+        set_next_caller(accounts.alice);
+        mp.bloquear_comprador(accounts.bob).unwrap();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.solicitar_cancelacion(oid), Err(Error::CompradorBloqueado));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.solicitar_cancelacion(oid), Ok(()));
+    }
+
+    #[ink::test]
+    fn bloquear_comprador_errores() {
+        let (accounts, mut mp, _) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.bloquear_comprador(accounts.bob), Err(Error::SinRegistro));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.bloquear_comprador(accounts.alice), Err(Error::SinPermiso));
+        assert_eq!(mp.desbloquear_comprador(accounts.alice), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.bloquear_comprador(accounts.alice), Err(Error::ParamInvalido));
+    }
+
+    #[ink::test]
+    fn favoritos_agregar_quitar_y_paginar() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.alice);
+        let pid2 = mp
+            .publicar_en_categoria("Otro".to_string(), "Desc".to_string(), 50, 5, CAT)
+            .unwrap();
+        let pid3 = mp
+            .publicar_en_categoria("Tercero".to_string(), "Desc".to_string(), 50, 5, CAT)
+            .unwrap();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.agregar_favorito(pid), Ok(()));
+        assert_eq!(mp.agregar_favorito(pid2), Ok(()));
+        assert_eq!(mp.agregar_favorito(pid3), Ok(()));
+        assert_eq!(mp.agregar_favorito(pid), Err(Error::YaEsFavorito));
+        assert_eq!(mp.agregar_favorito(99), Err(Error::ProdInexistente));
+
+        assert_eq!(mp.listar_favoritos(accounts.bob, 0, 2), vec![pid, pid2]);
+        assert_eq!(mp.listar_favoritos(accounts.bob, 2, 2), vec![pid3]);
+        assert!(mp.listar_favoritos(accounts.bob, 5, 2).is_empty());
+        assert_eq!(mp.obtener_cantidad_favoritos(pid), 1);
+
+        assert_eq!(mp.quitar_favorito(pid2), Ok(()));
+        assert_eq!(mp.quitar_favorito(pid2), Err(Error::ParamInvalido));
+        assert_eq!(mp.listar_favoritos(accounts.bob, 0, 10), vec![pid, pid3]);
+        assert_eq!(mp.obtener_cantidad_favoritos(pid2), 0);
+        assert_eq!(mp.listar_conteo_favoritos(0, 10), vec![(pid, 1), (pid3, 1)]);
+        assert_eq!(mp.listar_conteo_favoritos(pid2, 10), vec![(pid3, 1)]);
+        assert_eq!(mp.listar_conteo_favoritos(pid, 1), vec![(pid, 1)]);
+        assert!(mp.listar_conteo_favoritos(pid3 + 1, 10).is_empty());
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.agregar_favorito(pid), Err(Error::SinRegistro));
+        mp.registrar(Rol::Vendedor).unwrap();
+        assert_eq!(mp.agregar_favorito(pid), Err(Error::SinPermiso));
+    }
+
+    #[ink::test]
+    fn favoritos_limite_y_baja() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.alice);
+        for _ in 1..MAX_FAVORITOS {
+            mp.publicar_en_categoria("P".to_string(), "D".to_string(), 1, 1, CAT)
+                .unwrap();
+        }
+        let extra = mp
+            .publicar_en_categoria("P".to_string(), "D".to_string(), 1, 1, CAT)
+            .unwrap();
+
+        set_next_caller(accounts.bob);
+        for id in pid..extra {
+            mp.agregar_favorito(id).unwrap();
+        }
+        assert_eq!(mp.agregar_favorito(extra), Err(Error::FavoritosLlenos));
+
+        assert_eq!(mp.darse_de_baja(), Ok(()));
+        assert!(mp.listar_favoritos(accounts.bob, 0, 10).is_empty());
+        assert_eq!(mp.obtener_cantidad_favoritos(pid), 0);
+        assert!(mp
+            .listar_conteo_favoritos(0, MAX_CONTEO_FAVORITOS_POR_PAGINA)
+            .is_empty());
+    }
+
+    #[ink::test]
+    fn requisito_reputacion_comprador() {
+        let (accounts, mut mp, pid, oid) = setup_orden_recibida();
+
+        set_next_caller(accounts.alice);
+        let requisito = RequisitoReputacion {
+            promedio_minimo_x100: 400,
+            calificaciones_minimas: 1,
+        };
+        assert_eq!(mp.configurar_requisito_reputacion(pid, Some(requisito)), Ok(()));
+        assert_eq!(
+            mp.obtener_producto(pid).unwrap().requisito_reputacion,
+            Some(requisito)
+        );
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::ReputacionInsuficiente));
+
+        set_next_caller(accounts.alice);
+        mp.calificar_comprador(oid, 3).unwrap();
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::ReputacionInsuficiente));
+
+        set_next_caller(accounts.alice);
+        mp.editar_calificacion_comprador(oid, 4).unwrap();
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert!(mp.comprar(pid, 1).is_ok());
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.configurar_requisito_reputacion(pid, None), Ok(()));
+        assert_eq!(mp.obtener_producto(pid).unwrap().requisito_reputacion, None);
+    }
+
+    #[ink::test]
+    fn requisito_reputacion_errores() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        let requisito = RequisitoReputacion {
+            promedio_minimo_x100: 501,
+            calificaciones_minimas: 0,
+        };
+
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            mp.configurar_requisito_reputacion(pid, None),
+            Err(Error::SinPermiso)
+        );
+
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.configurar_requisito_reputacion(999, None),
+            Err(Error::ProdInexistente)
+        );
+        assert_eq!(
+            mp.configurar_requisito_reputacion(pid, Some(requisito)),
+            Err(Error::ParamInvalido)
+        );
+    }
+
     #[ink::test]
     fn comprar_todo_el_stock() {
         let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
@@ -333,6 +1433,25 @@ mod tests {
         assert_eq!(ordenes[1].cantidad, 3);
     }
 
+    #[ink::test]
+    fn listar_ordenes_de_comprador_paginadas() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.bob);
+        set_value(200);
+        mp.comprar(pid, 2).unwrap();
+        set_value(300);
+        mp.comprar(pid, 3).unwrap();
+
+        let ordenes = mp.listar_ordenes_de_comprador_paginadas(accounts.bob, 0, MAX_ORDENES_POR_PAGINA);
+        assert_eq!(ordenes, mp.listar_ordenes_de_comprador(accounts.bob));
+        let segunda = mp.listar_ordenes_de_comprador_paginadas(accounts.bob, 1, 1);
+        assert_eq!(segunda, vec![ordenes[1].clone()]);
+        assert!(mp
+            .listar_ordenes_de_comprador_paginadas(accounts.alice, 0, MAX_ORDENES_POR_PAGINA)
+            .is_empty());
+    }
+
     #[ink::test]
     fn multiples_ordenes_mismo_producto() {
         let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
@@ -380,27 +1499,301 @@ mod tests {
         assert_eq!(mp.marcar_enviado(oid), Err(Error::EstadoInvalido));
     }
 
-    #[ink::test]
-    fn marcar_recibido_errores() {
-        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
-
+    fn setup_orden_digital() -> (test::DefaultAccounts<DefaultEnvironment>, Marketplace, u32, u32) {
+        let (accounts, mut mp) = setup_vendedor();
+        let pid = mp
+            .publicar_digital(
+                "Ebook".to_string(),
+                "Desc".to_string(),
+                100,
+                10,
+                CAT,
+                "ipfs://cifrado".to_string(),
+            )
+            .unwrap();
         set_next_caller(accounts.bob);
-        assert_eq!(mp.marcar_recibido(oid), Err(Error::EstadoInvalido));
-
-        assert_eq!(mp.marcar_recibido(999), Err(Error::OrdenInexistente));
-
-        set_next_caller(accounts.alice);
-        mp.marcar_enviado(oid).unwrap();
-
-        assert_eq!(mp.marcar_recibido(oid), Err(Error::SinPermiso));
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(100);
+        let oid = mp.comprar(pid, 1).unwrap();
+        (accounts, mp, pid, oid)
     }
 
     #[ink::test]
-    fn marcar_orden_cancelada_falla() {
-        let (accounts, mut mp, _, oid) = setup_orden_cancelada();
+    fn orden_digital_se_completa_tras_ventana_de_disputa() {
+        let (accounts, mut mp, pid, oid) = setup_orden_digital();
+        assert_eq!(
+            mp.obtener_producto(pid).unwrap().tipo,
+            TipoProducto::Digital {
+                referencia_cifrada: "ipfs://cifrado".to_string()
+            }
+        );
 
         set_next_caller(accounts.alice);
-        assert_eq!(mp.marcar_enviado(oid), Err(Error::OrdenCancelada));
+        assert_eq!(mp.marcar_enviado(oid), Err(Error::ProductoDigital));
+        assert_eq!(mp.entregar_clave(oid, Vec::new()), Err(Error::ParamInvalido));
+        assert_eq!(mp.entregar_clave(oid, vec![1, 2, 3]), Ok(()));
+        assert_eq!(mp.obtener_orden(oid).unwrap().estado, Estado::Enviado);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.entregar_clave(oid, vec![1]), Err(Error::SinPermiso));
+        let entrega = mp.obtener_entrega_digital(oid).unwrap();
+        assert_eq!(entrega.clave_cifrada, Some(vec![1, 2, 3]));
+        assert_eq!(entrega.bloque_entrega, Some(0));
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.obtener_entrega_digital(oid), Err(Error::SinPermiso));
+        assert_eq!(mp.completar_orden_digital(oid), Err(Error::PlazoNoVencido));
+
+        test::set_block_number::<DefaultEnvironment>(VENTANA_DISPUTA_DIGITAL_BLOQUES + 1);
+        assert_eq!(mp.completar_orden_digital(oid), Ok(()));
+        assert_eq!(mp.obtener_orden_publica(oid).unwrap().estado, Estado::Recibido);
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 0));
+        assert_eq!(mp.completar_orden_digital(oid), Err(Error::EstadoInvalido));
+    }
+
+    #[ink::test]
+    fn orden_digital_disputada_no_se_completa() {
+        let (accounts, mut mp, _, oid) = setup_orden_digital();
+        set_next_caller(accounts.alice);
+        mp.entregar_clave(oid, vec![7]).unwrap();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.disputar_orden_digital(oid), Err(Error::SinPermiso));
+        set_next_caller(accounts.bob);
+        mp.solicitar_cancelacion(oid).unwrap();
+        assert_eq!(mp.disputar_orden_digital(oid), Ok(()));
+        assert_eq!(mp.disputar_orden_digital(oid), Err(Error::OrdenEnDisputa));
+        assert!(mp.obtener_entrega_digital(oid).unwrap().disputada);
+
+        test::set_block_number::<DefaultEnvironment>(VENTANA_DISPUTA_DIGITAL_BLOQUES + 1);
+        set_next_caller(accounts.alice);
+        mp.rechazar_cancelacion(oid).unwrap();
+        assert_eq!(mp.completar_orden_digital(oid), Err(Error::OrdenEnDisputa));
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 100);
+
+        // La admin es también la vendedora: no puede resolver a su favor.
+        assert_eq!(mp.resolver_disputa_digital(oid, false), Err(Error::SinPermiso));
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.resolver_disputa_digital(oid, true), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.resolver_disputa_digital(oid, true), Ok(()));
+        assert_eq!(mp.obtener_orden(oid).unwrap().estado, Estado::Cancelada);
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100);
+        assert_eq!(mp.resolver_disputa_digital(oid, true), Err(Error::EstadoInvalido));
+    }
+
+    #[ink::test]
+    fn disputa_digital_retirada_o_resuelta_por_admin() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Vendedor).unwrap();
+        let pid = mp
+            .publicar_digital("Ebook".to_string(), "D".to_string(), 100, 10, CAT, "ipfs://x".to_string())
+            .unwrap();
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(100);
+        let retirada = mp.comprar(pid, 1).unwrap();
+        let arbitrada = mp.comprar(pid, 1).unwrap();
+        let sin_disputa = mp.comprar(pid, 1).unwrap();
+
+        set_next_caller(accounts.charlie);
+        for oid in [retirada, arbitrada, sin_disputa] {
+            mp.entregar_clave(oid, vec![1]).unwrap();
+        }
+        assert_eq!(mp.resolver_disputa_digital(sin_disputa, true), Err(Error::DisputaInexistente));
+
+        set_next_caller(accounts.bob);
+        mp.disputar_orden_digital(retirada).unwrap();
+        mp.disputar_orden_digital(arbitrada).unwrap();
+        assert_eq!(mp.resolver_disputa_digital(retirada, false), Ok(()));
+        assert_eq!(mp.obtener_orden(retirada).unwrap().estado, Estado::Recibido);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.resolver_disputa_digital(arbitrada, false), Ok(()));
+        assert_eq!(mp.obtener_orden_publica(arbitrada).unwrap().estado, Estado::Recibido);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.charlie), 200);
+
+        test::set_block_number::<DefaultEnvironment>(VENTANA_DISPUTA_DIGITAL_BLOQUES + 1);
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.disputar_orden_digital(sin_disputa), Err(Error::PlazoVencido));
+        assert_eq!(mp.completar_orden_digital(sin_disputa), Ok(()));
+    }
+
+    #[ink::test]
+    fn orden_digital_reembolso_si_no_se_entrega_a_tiempo() {
+        let (accounts, mut mp, pid, oid) = setup_orden_digital();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.reclamar_reembolso_digital(oid), Err(Error::PlazoNoVencido));
+
+        test::set_block_number::<DefaultEnvironment>(PLAZO_ENTREGA_DIGITAL_BLOQUES + 1);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.entregar_clave(oid, vec![1]), Err(Error::PlazoVencido));
+        assert_eq!(mp.reclamar_reembolso_digital(oid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.reclamar_reembolso_digital(oid), Ok(()));
+        assert_eq!(mp.obtener_orden(oid).unwrap().estado, Estado::Cancelada);
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 10);
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        assert_eq!(mp.reclamar_reembolso_digital(oid), Err(Error::EstadoInvalido));
+    }
+
+    #[ink::test]
+    fn operaciones_digitales_en_producto_fisico() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.entregar_clave(oid, vec![1]), Err(Error::ProductoNoDigital));
+        assert_eq!(mp.completar_orden_digital(oid), Err(Error::ProductoNoDigital));
+        assert_eq!(mp.obtener_entrega_digital(oid), Err(Error::ProductoNoDigital));
+        assert_eq!(
+            mp.publicar_digital("E".to_string(), "D".to_string(), 1, 1, CAT, "".to_string()),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(mp.obtener_producto(1).unwrap().tipo, TipoProducto::Fisico);
+    }
+
+    fn setup_suscripcion(periodos: u32) -> (test::DefaultAccounts<DefaultEnvironment>, Marketplace, u32, u32) {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.alice);
+        let plan = PlanSuscripcion {
+            periodo_bloques: 100,
+            precio_periodo: 30,
+        };
+        mp.configurar_suscripcion(pid, Some(plan)).unwrap();
+
+        set_next_caller(accounts.bob);
+        set_value(30 * periodos as Balance);
+        let sid = mp.suscribirse(pid, periodos).unwrap();
+        (accounts, mp, pid, sid)
+    }
+
+    #[ink::test]
+    fn suscripcion_cobro_por_periodos() {
+        let (accounts, mut mp, pid, sid) = setup_suscripcion(3);
+        let ordenes_previas = mp.get_total_ordenes();
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (1, 0));
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 10);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.cobrar_suscripcion(sid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(1));
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(0));
+
+        test::set_block_number::<DefaultEnvironment>(250);
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(2));
+        assert_eq!(mp.get_total_ordenes(), ordenes_previas + 3);
+        for oid in ordenes_previas + 1..=ordenes_previas + 3 {
+            let orden = mp.obtener_orden_publica(oid).unwrap();
+            assert_eq!(orden.estado, Estado::Recibido);
+            assert_eq!((orden.cantidad, orden.monto_total), (1, 30));
+            assert_eq!(orden.comprador, accounts.bob);
+        }
+        assert_eq!(mp.get_total_ordenes_de_cuenta(accounts.bob), 3);
+        assert_eq!(mp.get_total_ordenes_de_cuenta(accounts.alice), 3);
+
+        let suscripcion = mp.obtener_suscripcion(sid).unwrap();
+        assert_eq!(suscripcion.periodos_cobrados, 3);
+        assert!(!suscripcion.activa);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+        assert_eq!(mp.cobrar_suscripcion(sid), Err(Error::SuscripcionFinalizada));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.calificar_vendedor(ordenes_previas + 1, 5), Ok(()));
+    }
+
+    #[ink::test]
+    fn suscripcion_cancelacion_reembolsa_periodos_futuros() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(4);
+        let ordenes_previas = mp.get_total_ordenes();
+
+        test::set_block_number::<DefaultEnvironment>(150);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cancelar_suscripcion(sid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.cancelar_suscripcion(sid), Ok(()));
+        let suscripcion = mp.obtener_suscripcion(sid).unwrap();
+        assert!(!suscripcion.activa);
+        assert_eq!(suscripcion.periodos_cobrados, 2);
+        assert_eq!(suscripcion.periodos_pagados, 2);
+        assert_eq!(mp.get_total_ordenes(), ordenes_previas + 2);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 60);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.alice), (0, 0));
+        assert_eq!(mp.cancelar_suscripcion(sid), Err(Error::SuscripcionFinalizada));
+        assert_eq!(mp.cancelar_suscripcion(99), Err(Error::SuscripcionInexistente));
+    }
+
+    #[ink::test]
+    fn suscripcion_errores() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.bob);
+        set_value(30);
+        assert_eq!(mp.suscribirse(pid, 1), Err(Error::SinPlanSuscripcion));
+        assert_eq!(
+            mp.configurar_suscripcion(pid, None),
+            Err(Error::SinPermiso)
+        );
+
+        set_next_caller(accounts.alice);
+        let invalido = PlanSuscripcion {
+            periodo_bloques: 0,
+            precio_periodo: 30,
+        };
+        assert_eq!(
+            mp.configurar_suscripcion(pid, Some(invalido)),
+            Err(Error::ParamInvalido)
+        );
+        let plan = PlanSuscripcion {
+            periodo_bloques: 100,
+            precio_periodo: 30,
+        };
+        mp.configurar_suscripcion(pid, Some(plan)).unwrap();
+        assert_eq!(mp.suscribirse(pid, 1), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.bob);
+        set_value(0);
+        assert_eq!(mp.suscribirse(pid, 0), Err(Error::ParamInvalido));
+        set_value(50);
+        assert_eq!(mp.suscribirse(pid, 2), Err(Error::PagoInsuficiente));
+        set_value(70);
+        assert_eq!(mp.suscribirse(pid, 2), Err(Error::PagoExcesivo));
+
+        set_value(60);
+        mp.suscribirse(pid, 2).unwrap();
+        assert_eq!(mp.darse_de_baja(), Err(Error::OrdenesAbiertas));
+    }
+
+    #[ink::test]
+    fn marcar_recibido_errores() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.marcar_recibido(oid), Err(Error::EstadoInvalido));
+
+        assert_eq!(mp.marcar_recibido(999), Err(Error::OrdenInexistente));
+
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid).unwrap();
+
+        assert_eq!(mp.marcar_recibido(oid), Err(Error::SinPermiso));
+    }
+
+    #[ink::test]
+    fn marcar_orden_cancelada_falla() {
+        let (accounts, mut mp, _, oid) = setup_orden_cancelada();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.marcar_enviado(oid), Err(Error::OrdenCancelada));
 
         set_next_caller(accounts.bob);
         assert_eq!(mp.marcar_recibido(oid), Err(Error::OrdenCancelada));
@@ -408,7 +1801,7 @@ mod tests {
 
     #[ink::test]
     fn obtener_orden_permisos() {
-        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+        let (accounts, mp, _, oid) = setup_con_orden(1, 100);
 
         set_next_caller(accounts.bob);
         assert!(mp.obtener_orden(oid).is_ok());
@@ -706,108 +2099,398 @@ mod tests {
     }
 
     #[ink::test]
-    fn reputacion_sin_calificaciones() {
-        let accounts = get_accounts();
-        let mp = Marketplace::new();
-        assert_eq!(mp.obtener_reputacion(accounts.alice), None);
+    fn responder_calificaciones() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.responder_calificacion(oid, "Gracias".to_string()),
+            Err(Error::CalificacionInexistente)
+        );
+
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid, 2).unwrap();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.responder_calificacion(oid, "Lamento la demora".to_string()),
+            Ok(())
+        );
+        assert_eq!(test::recorded_events().count(), 1);
+
+        let calif = mp.obtener_calificacion(oid).unwrap();
+        assert_eq!(calif.puntos_al_vendedor, 2);
+        assert_eq!(calif.respuesta_vendedor, Some("Lamento la demora".to_string()));
+        assert_eq!(calif.respuesta_comprador, None);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(
+            mp.responder_calificacion(oid, "Ok".to_string()),
+            Err(Error::CalificacionInexistente)
+        );
+
+        set_next_caller(accounts.alice);
+        mp.calificar_comprador(oid, 4).unwrap();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.responder_calificacion(oid, "Gracias".to_string()), Ok(()));
+        assert_eq!(
+            mp.obtener_calificacion(oid).unwrap().respuesta_comprador,
+            Some("Gracias".to_string())
+        );
     }
 
     #[ink::test]
-    fn overflow_reputacion() {
+    fn responder_calificacion_errores() {
         let (accounts, mut mp, _, oid) = setup_orden_recibida();
 
-        let mut rep = mp.reputaciones.get(accounts.alice).unwrap_or(ReputacionUsuario {
-            como_comprador: (0, 0),
-            como_vendedor: (u32::MAX - 2, 1),
-        });
-        rep.como_vendedor = (u32::MAX - 2, 1);
-        mp.reputaciones.insert(accounts.alice, &rep);
-
         set_next_caller(accounts.bob);
-        assert_eq!(mp.calificar_vendedor(oid, 5), Err(Error::OverflowAritmetico));
+        mp.calificar_vendedor(oid, 3).unwrap();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(
+            mp.responder_calificacion(oid, "Hola".to_string()),
+            Err(Error::SinPermiso)
+        );
+        assert_eq!(
+            mp.responder_calificacion(999, "Hola".to_string()),
+            Err(Error::OrdenInexistente)
+        );
+
+        set_next_caller(accounts.alice);
+        assert_eq!(
+            mp.responder_calificacion(oid, "".to_string()),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(
+            mp.responder_calificacion(oid, "a".repeat(257)),
+            Err(Error::ParamInvalido)
+        );
+
+        assert!(mp.responder_calificacion(oid, "a".repeat(256)).is_ok());
+        assert_eq!(
+            mp.responder_calificacion(oid, "Otra".to_string()),
+            Err(Error::YaRespondido)
+        );
     }
 
     #[ink::test]
-    fn overflow_cantidad_calificaciones() {
+    fn editar_calificacion_ajusta_reputacion() {
         let (accounts, mut mp, _, oid) = setup_orden_recibida();
 
-        let mut rep = mp.reputaciones.get(accounts.alice).unwrap_or(ReputacionUsuario {
-            como_comprador: (0, 0),
-            como_vendedor: (10, u32::MAX),
-        });
-        rep.como_vendedor = (10, u32::MAX);
-        mp.reputaciones.insert(accounts.alice, &rep);
-
         set_next_caller(accounts.bob);
-        assert_eq!(mp.calificar_vendedor(oid, 5), Err(Error::OverflowAritmetico));
+        assert_eq!(
+            mp.editar_calificacion_vendedor(oid, 4),
+            Err(Error::CalificacionInexistente)
+        );
+        mp.calificar_vendedor(oid, 1).unwrap();
+
+        test::set_block_number::<DefaultEnvironment>(10);
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 4), Ok(()));
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 5), Err(Error::YaEditado));
+
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (4, 1));
+        assert_eq!(mp.obtener_calificacion_categoria_por_id(CAT), Some((4, 1)));
+        assert_eq!(
+            mp.obtener_reputacion_ponderada(accounts.alice)
+                .unwrap()
+                .como_vendedor
+                .promedio_x100(),
+            400
+        );
+
+        let calif = mp.obtener_calificacion(oid).unwrap();
+        assert_eq!(calif.puntos_al_vendedor, 4);
+        assert_eq!(
+            calif.historial,
+            vec![EdicionCalificacion {
+                autor: accounts.bob,
+                puntos_anteriores: 1,
+                puntos_nuevos: 4,
+                bloque: 10,
+            }]
+        );
+
+        set_next_caller(accounts.alice);
+        mp.calificar_comprador(oid, 5).unwrap();
+        assert_eq!(mp.editar_calificacion_comprador(oid, 3), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.bob).unwrap().como_comprador, (3, 1));
+        assert_eq!(mp.obtener_calificacion(oid).unwrap().historial.len(), 2);
     }
 
     #[ink::test]
-    fn fondos_retenidos_y_liberados() {
-        let (accounts, mut mp, _, oid) = setup_con_orden(3, 100);
+    fn editar_calificacion_errores() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
 
-        assert_eq!(mp.obtener_fondos_retenidos(oid), 300);
+        set_next_caller(accounts.bob);
+        mp.calificar_vendedor(oid, 2).unwrap();
 
-        set_next_caller(accounts.alice);
-        mp.marcar_enviado(oid).unwrap();
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 4), Err(Error::SinPermiso));
+        assert_eq!(mp.editar_calificacion_comprador(oid, 4), Err(Error::SinPermiso));
 
         set_next_caller(accounts.bob);
-        let _ = mp.marcar_recibido(oid);
+        assert_eq!(mp.editar_calificacion_vendedor(999, 4), Err(Error::OrdenInexistente));
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 0), Err(Error::CalificacionInvalida));
+        assert_eq!(mp.editar_calificacion_vendedor(oid, 6), Err(Error::CalificacionInvalida));
 
-        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        test::set_block_number::<DefaultEnvironment>(mp.obtener_ventana_edicion() + 1);
+        assert_eq!(
+            mp.editar_calificacion_vendedor(oid, 4),
+            Err(Error::VentanaEdicionVencida)
+        );
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (2, 1));
     }
 
     #[ink::test]
-    fn fondos_devueltos_al_cancelar() {
-        let (accounts, mut mp, _, oid) = setup_con_orden(2, 100);
+    fn configurar_ventana_edicion() {
+        let accounts = get_accounts();
+        set_next_caller(accounts.alice);
+        let mut mp = nuevo_marketplace();
 
-        assert_eq!(mp.obtener_fondos_retenidos(oid), 200);
+        assert_eq!(mp.obtener_admin(), accounts.alice);
+        assert_eq!(mp.obtener_ventana_edicion(), VENTANA_EDICION_BLOQUES_DEFAULT);
 
         set_next_caller(accounts.bob);
-        mp.solicitar_cancelacion(oid).unwrap();
+        assert_eq!(mp.configurar_ventana_edicion(10), Err(Error::SinPermiso));
 
         set_next_caller(accounts.alice);
-        let _ = mp.aceptar_cancelacion(oid);
-
-        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        assert_eq!(mp.configurar_ventana_edicion(10), Ok(()));
+        assert_eq!(mp.obtener_ventana_edicion(), 10);
     }
 
     #[ink::test]
-    fn monto_total_en_orden() {
+    fn reputacion_ponderada_por_monto() {
         let (accounts, mut mp) = setup_vendedor();
-        let pid = mp.publicar("Test".to_string(), "Desc".to_string(), 50, 10, "Cat".to_string()).unwrap();
+
+        let pid_barato = mp.publicar_en_categoria("Barato".to_string(), "Desc".to_string(), 10, 10, CAT).unwrap();
+        let pid_caro = mp.publicar_en_categoria("Caro".to_string(), "Desc".to_string(), 990, 10, CAT).unwrap();
 
         set_next_caller(accounts.bob);
         mp.registrar(Rol::Comprador).unwrap();
-        set_value(250);
-        let oid = mp.comprar(pid, 5).unwrap();
+        set_value(10);
+        let oid1 = mp.comprar(pid_barato, 1).unwrap();
+        set_value(990);
+        let oid2 = mp.comprar(pid_caro, 1).unwrap();
 
-        let orden = mp.obtener_orden(oid).unwrap();
-        assert_eq!(orden.monto_total, 250);
-    }
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid1).unwrap();
+        mp.marcar_enviado(oid2).unwrap();
 
-    #[ink::test]
-    fn overflow_id_producto() {
-        let (_, mut mp) = setup_vendedor();
+        set_next_caller(accounts.bob);
+        let _ = mp.marcar_recibido(oid1);
+        let _ = mp.marcar_recibido(oid2);
+        mp.calificar_vendedor(oid1, 5).unwrap();
+        mp.calificar_vendedor(oid2, 1).unwrap();
 
-        mp.next_prod_id = u32::MAX;
-        assert_eq!(
-            mp.publicar("Test".to_string(), "Desc".to_string(), 100, 5, "Cat".to_string()),
-            Err(Error::IdOverflow)
-        );
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (6, 2));
+
+        let pond = mp.obtener_reputacion_ponderada(accounts.alice).unwrap();
+        assert_eq!(pond.como_vendedor.peso_en(0), 1000);
+        assert_eq!(pond.como_vendedor.promedio_x100(), 104);
+        assert_eq!(pond.como_comprador.promedio_x100(), 0);
     }
 
     #[ink::test]
-    fn overflow_id_orden() {
+    fn reputacion_ponderada_decae_con_los_bloques() {
         let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
 
-        mp.next_order_id = u32::MAX;
         set_next_caller(accounts.bob);
         set_value(100);
-        assert_eq!(mp.comprar(pid, 1), Err(Error::IdOverflow));
-    }
+        let oid1 = mp.comprar(pid, 1).unwrap();
+        set_value(100);
+        let oid2 = mp.comprar(pid, 1).unwrap();
 
-    #[ink::test]
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid1).unwrap();
+        mp.marcar_enviado(oid2).unwrap();
+
+        set_next_caller(accounts.bob);
+        let _ = mp.marcar_recibido(oid1);
+        let _ = mp.marcar_recibido(oid2);
+        mp.calificar_vendedor(oid1, 1).unwrap();
+
+        test::set_block_number::<DefaultEnvironment>(VIDA_MEDIA_REPUTACION_BLOQUES);
+        mp.calificar_vendedor(oid2, 5).unwrap();
+
+        let pond = mp.obtener_reputacion_ponderada(accounts.alice).unwrap();
+        assert_eq!(pond.como_vendedor.peso_en(VIDA_MEDIA_REPUTACION_BLOQUES), 150);
+        assert_eq!(pond.como_vendedor.bloque_base, 0);
+        assert_eq!(pond.como_vendedor.promedio_x100(), 366);
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (6, 2));
+    }
+
+    #[ink::test]
+    fn decaimiento_reputacion() {
+        let h = VIDA_MEDIA_REPUTACION_BLOQUES;
+        assert_eq!(escala(0), Some(h as Balance));
+        assert_eq!(escala(h / 2), Some((h + h / 2) as Balance));
+        assert_eq!(escala(h * 2), Some(4 * h as Balance));
+        assert_eq!(escala(BlockNumber::MAX), None);
+
+        let puntaje = PuntajePonderado {
+            peso_total: 1000 * h as Balance,
+            ..Default::default()
+        };
+        assert_eq!(puntaje.peso_en(0), 1000);
+        assert_eq!(puntaje.peso_en(h), 500);
+        assert_eq!(puntaje.peso_en(h / 2), 666);
+        assert_eq!(puntaje.peso_en(h * 2), 250);
+        assert_eq!(puntaje.peso_en(BlockNumber::MAX), 0);
+    }
+
+    #[ink::test]
+    fn editar_calificacion_reemplaza_su_aporte_exacto() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        let h = VIDA_MEDIA_REPUTACION_BLOQUES;
+
+        set_next_caller(accounts.alice);
+        mp.configurar_ventana_edicion(h * 40).unwrap();
+
+        let mut oids = Vec::new();
+        for _ in 0..3 {
+            set_next_caller(accounts.bob);
+            set_value(100);
+            let oid = mp.comprar(pid, 1).unwrap();
+            set_next_caller(accounts.alice);
+            mp.marcar_enviado(oid).unwrap();
+            set_next_caller(accounts.bob);
+            let _ = mp.marcar_recibido(oid);
+            oids.push(oid);
+        }
+
+        mp.calificar_vendedor(oids[0], 1).unwrap();
+        test::set_block_number::<DefaultEnvironment>(h + h / 3);
+        mp.calificar_vendedor(oids[1], 5).unwrap();
+        test::set_block_number::<DefaultEnvironment>(h + h / 2);
+        mp.editar_calificacion_vendedor(oids[0], 5).unwrap();
+
+        let pond = mp.obtener_reputacion_ponderada(accounts.alice).unwrap();
+        assert_eq!(pond.como_vendedor.suma_ponderada, 5 * pond.como_vendedor.peso_total);
+        assert_eq!(pond.como_vendedor.promedio_x100(), 500);
+
+        test::set_block_number::<DefaultEnvironment>(h * 20);
+        mp.calificar_vendedor(oids[2], 1).unwrap();
+        assert_eq!(
+            mp.obtener_reputacion_ponderada(accounts.alice)
+                .unwrap()
+                .como_vendedor
+                .bloque_base,
+            h * 20
+        );
+        mp.editar_calificacion_vendedor(oids[2], 5).unwrap();
+        assert_eq!(
+            mp.obtener_reputacion_ponderada(accounts.alice)
+                .unwrap()
+                .como_vendedor
+                .promedio_x100(),
+            500
+        );
+    }
+
+    #[ink::test]
+    fn reputacion_sin_calificaciones() {
+        let accounts = get_accounts();
+        let mp = Marketplace::new();
+        assert_eq!(mp.obtener_reputacion(accounts.alice), None);
+    }
+
+    #[ink::test]
+    fn overflow_reputacion() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
+
+        let mut rep = mp.reputaciones.get(accounts.alice).unwrap_or(ReputacionUsuario {
+            como_comprador: (0, 0),
+            como_vendedor: (u32::MAX - 2, 1),
+        });
+        rep.como_vendedor = (u32::MAX - 2, 1);
+        mp.reputaciones.insert(accounts.alice, &rep);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.calificar_vendedor(oid, 5), Err(Error::OverflowAritmetico));
+    }
+
+    #[ink::test]
+    fn overflow_cantidad_calificaciones() {
+        let (accounts, mut mp, _, oid) = setup_orden_recibida();
+
+        let mut rep = mp.reputaciones.get(accounts.alice).unwrap_or(ReputacionUsuario {
+            como_comprador: (0, 0),
+            como_vendedor: (10, u32::MAX),
+        });
+        rep.como_vendedor = (10, u32::MAX);
+        mp.reputaciones.insert(accounts.alice, &rep);
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.calificar_vendedor(oid, 5), Err(Error::OverflowAritmetico));
+    }
+
+    #[ink::test]
+    fn fondos_retenidos_y_liberados() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(3, 100);
+
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 300);
+
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid).unwrap();
+
+        set_next_caller(accounts.bob);
+        let _ = mp.marcar_recibido(oid);
+
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+    }
+
+    #[ink::test]
+    fn fondos_devueltos_al_cancelar() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(2, 100);
+
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 200);
+
+        set_next_caller(accounts.bob);
+        mp.solicitar_cancelacion(oid).unwrap();
+
+        set_next_caller(accounts.alice);
+        let _ = mp.aceptar_cancelacion(oid);
+
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+    }
+
+    #[ink::test]
+    fn monto_total_en_orden() {
+        let (accounts, mut mp) = setup_vendedor();
+        let pid = mp.publicar("Test".to_string(), "Desc".to_string(), 50, 10, "Cat".to_string()).unwrap();
+
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(250);
+        let oid = mp.comprar(pid, 5).unwrap();
+
+        let orden = mp.obtener_orden(oid).unwrap();
+        assert_eq!(orden.monto_total, 250);
+    }
+
+    #[ink::test]
+    fn overflow_id_producto() {
+        let (_, mut mp) = setup_vendedor();
+
+        mp.next_prod_id = u32::MAX;
+        assert_eq!(
+            mp.publicar("Test".to_string(), "Desc".to_string(), 100, 5, "Cat".to_string()),
+            Err(Error::IdOverflow)
+        );
+    }
+
+    #[ink::test]
+    fn overflow_id_orden() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        mp.next_order_id = u32::MAX;
+        set_next_caller(accounts.bob);
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::IdOverflow));
+    }
+
+    #[ink::test]
     fn get_total_productos() {
         let (_, mut mp) = setup_vendedor();
 
@@ -859,17 +2542,43 @@ mod tests {
     #[ink::test]
     fn listar_usuarios() {
         let accounts = get_accounts();
-        let mut mp = Marketplace::new();
+        let mut mp = nuevo_marketplace();
 
-        assert!(mp.listar_usuarios().is_empty());
+        assert!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA).is_empty());
 
         set_next_caller(accounts.alice);
         mp.registrar(Rol::Vendedor).unwrap();
-        assert_eq!(mp.listar_usuarios().len(), 1);
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA).len(), 1);
 
         set_next_caller(accounts.bob);
         mp.registrar(Rol::Comprador).unwrap();
-        assert_eq!(mp.listar_usuarios().len(), 2);
+        assert_eq!(mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA).len(), 2);
+        assert_eq!(mp.get_total_usuarios(), 2);
+
+        assert_eq!(mp.listar_usuarios(1, 5), vec![accounts.bob]);
+        assert_eq!(mp.listar_usuarios(0, 1), vec![accounts.alice]);
+        assert!(mp.listar_usuarios(2, 5).is_empty());
+        assert!(mp.listar_usuarios(u32::MAX, u32::MAX).is_empty());
+    }
+
+    #[ink::test]
+    fn darse_de_baja_reacomoda_usuarios() {
+        let accounts = get_accounts();
+        let mut mp = nuevo_marketplace();
+        for cuenta in [accounts.alice, accounts.bob, accounts.charlie, accounts.django] {
+            set_next_caller(cuenta);
+            mp.registrar(Rol::Comprador).unwrap();
+        }
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.darse_de_baja(), Ok(()));
+        assert_eq!(mp.get_total_usuarios(), 3);
+        assert_eq!(
+            mp.listar_usuarios(0, MAX_USUARIOS_POR_PAGINA),
+            vec![accounts.alice, accounts.django, accounts.charlie]
+        );
+        assert_eq!(mp.indice_usuarios.get(accounts.django), Some(1));
+        assert_eq!(mp.indice_usuarios.get(accounts.bob), None);
     }
 
     #[ink::test]
@@ -927,4 +2636,984 @@ mod tests {
         let reputaciones = mp.listar_todas_reputaciones();
         assert_eq!(reputaciones.len(), 2);
     }
+
+    fn setup_preventa() -> (test::DefaultAccounts<DefaultEnvironment>, Marketplace, u32, u32) {
+        let (accounts, mut mp) = setup_vendedor();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        assert_eq!(
+            mp.publicar_preventa("Consola".to_string(), "Desc".to_string(), 100, CAT, 1_000, 5),
+            Err(Error::ParamInvalido)
+        );
+        assert_eq!(
+            mp.publicar_preventa("Consola".to_string(), "Desc".to_string(), 100, CAT, 5_000, 0),
+            Err(Error::ParamInvalido)
+        );
+        let pid = mp
+            .publicar_preventa("Consola".to_string(), "Desc".to_string(), 100, CAT, 5_000, 5)
+            .unwrap();
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(200);
+        let oid = mp.comprar(pid, 2).unwrap();
+        (accounts, mp, pid, oid)
+    }
+
+    #[ink::test]
+    fn preventa_bloquea_envio_hasta_lanzamiento() {
+        let (accounts, mut mp, pid, oid) = setup_preventa();
+        let producto = mp.obtener_producto(pid).unwrap();
+        assert_eq!(producto.stock, 3);
+        assert_eq!(
+            producto.lanzamiento,
+            Some(Lanzamiento {
+                fecha_lanzamiento: 5_000,
+                cancelado: false
+            })
+        );
+
+        set_next_caller(accounts.bob);
+        set_value(400);
+        assert_eq!(mp.comprar(pid, 4), Err(Error::StockInsuf));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.marcar_enviado(oid), Err(Error::LanzamientoPendiente));
+        test::set_block_timestamp::<DefaultEnvironment>(5_000);
+        assert_eq!(mp.marcar_enviado(oid), Ok(()));
+        assert_eq!(mp.cancelar_lanzamiento(pid), Err(Error::PlazoVencido));
+    }
+
+    #[ink::test]
+    fn cancelar_lanzamiento_reembolsa_preventas() {
+        let (accounts, mut mp, pid, oid) = setup_preventa();
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.solicitar_cancelacion(oid), Ok(()));
+        assert_eq!(mp.cancelar_lanzamiento(pid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        let pid_normal = mp
+            .publicar_en_categoria("Test".to_string(), "Desc".to_string(), 100, 10, CAT)
+            .unwrap();
+        assert_eq!(mp.cancelar_lanzamiento(pid_normal), Err(Error::SinLanzamiento));
+        assert_eq!(mp.cancelar_lanzamiento(pid), Ok(1));
+        assert_eq!(mp.cancelar_lanzamiento(pid), Err(Error::LanzamientoCancelado));
+        assert_eq!(mp.reactivar_producto(pid), Err(Error::LanzamientoCancelado));
+
+        assert_eq!(mp.obtener_orden(oid).unwrap().estado, Estado::Cancelada);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+        assert!(!mp.obtener_producto(pid).unwrap().activo);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 200);
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+    }
+
+    #[ink::test]
+    fn cancelar_lanzamiento_reembolsa_por_lotes() {
+        let (accounts, mut mp) = setup_vendedor();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let cupo = MAX_REEMBOLSOS_POR_LLAMADA + 5;
+        let pid = mp
+            .publicar_preventa("Consola".to_string(), "Desc".to_string(), 100, CAT, 5_000, cupo)
+            .unwrap();
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(100);
+        for _ in 0..cupo {
+            mp.comprar(pid, 1).unwrap();
+        }
+        assert_eq!(mp.obtener_preventas_sin_reembolsar(pid), cupo);
+        assert_eq!(mp.reembolsar_preventas(pid), Err(Error::EstadoInvalido));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cancelar_lanzamiento(pid), Ok(MAX_REEMBOLSOS_POR_LLAMADA));
+        assert_eq!(mp.obtener_preventas_sin_reembolsar(pid), 5);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (5, 0));
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.reembolsar_preventas(pid), Ok(5));
+        assert_eq!(mp.reembolsar_preventas(pid), Ok(0));
+        assert_eq!(mp.obtener_preventas_sin_reembolsar(pid), 0);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100 * cupo as Balance);
+        assert_eq!(mp.reembolsar_preventas(99), Err(Error::ProdInexistente));
+    }
+
+    #[ink::test]
+    fn preventa_cancelada_no_se_envia_tras_lanzamiento() {
+        let (accounts, mut mp) = setup_vendedor();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let cupo = MAX_REEMBOLSOS_POR_LLAMADA + 1;
+        let pid = mp
+            .publicar_preventa("Consola".to_string(), "Desc".to_string(), 100, CAT, 5_000, cupo)
+            .unwrap();
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(100);
+        let mut ultima = 0;
+        for _ in 0..cupo {
+            ultima = mp.comprar(pid, 1).unwrap();
+        }
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cancelar_lanzamiento(pid), Ok(MAX_REEMBOLSOS_POR_LLAMADA));
+        assert_eq!(mp.obtener_orden(ultima).unwrap().estado, Estado::Pendiente);
+
+        test::set_block_timestamp::<DefaultEnvironment>(5_000);
+        assert_eq!(mp.marcar_enviado(ultima), Err(Error::LanzamientoCancelado));
+        assert_eq!(mp.obtener_orden(ultima).unwrap().estado, Estado::Pendiente);
+        assert_eq!(mp.reembolsar_preventas(pid), Ok(1));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100 * cupo as Balance);
+    }
+
+    #[ink::test]
+    fn reserva_retiene_stock_y_se_completa_con_compra() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        set_value(49);
+        assert_eq!(mp.reservar(pid, 10), Err(Error::PagoInsuficiente));
+        set_value(50);
+        assert_eq!(mp.reservar(pid, 10), Ok(()));
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 0);
+        assert_eq!(
+            mp.obtener_reserva(accounts.bob, pid),
+            Some(Reserva {
+                cantidad: 10,
+                deposito: 50,
+                bloque_vencimiento: DURACION_RESERVA_BLOQUES
+            })
+        );
+        assert_eq!(mp.reservar(pid, 1), Err(Error::ReservaExistente));
+
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::StockInsuf));
+
+        set_next_caller(accounts.bob);
+        set_value(300);
+        let oid = mp.comprar(pid, 3).unwrap();
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 300);
+        assert_eq!(mp.obtener_reserva(accounts.bob, pid), None);
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 7);
+    }
+
+    #[ink::test]
+    fn reserva_vencida_se_libera_con_comision() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        set_value(50);
+        mp.reservar(pid, 10).unwrap();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.liberar_reserva(accounts.bob, pid), Err(Error::PlazoNoVencido));
+        assert_eq!(mp.liberar_reserva(accounts.charlie, pid), Err(Error::ReservaInexistente));
+
+        test::set_block_number::<DefaultEnvironment>(DURACION_RESERVA_BLOQUES);
+        assert_eq!(mp.liberar_reserva(accounts.bob, pid), Ok(()));
+        assert_eq!(mp.obtener_reserva(accounts.bob, pid), None);
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 10);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 10);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 40);
+
+        // Una reserva vencida no impide volver a reservar
+        set_next_caller(accounts.bob);
+        set_value(5);
+        mp.reservar(pid, 1).unwrap();
+        test::set_block_number::<DefaultEnvironment>(2 * DURACION_RESERVA_BLOQUES);
+        set_value(10);
+        assert_eq!(mp.reservar(pid, 2), Ok(()));
+        assert_eq!(mp.obtener_producto(pid).unwrap().stock, 8);
+    }
+
+    #[ink::test]
+    fn compra_con_referido_reparte_escrow() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.configurar_referidos(pid, 10), Err(Error::SinPermiso));
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.configurar_referidos(pid, 51), Err(Error::ParamInvalido));
+        assert_eq!(mp.configurar_referidos(pid, 10), Ok(()));
+
+        set_next_caller(accounts.bob);
+        set_value(200);
+        assert_eq!(mp.comprar_con_referido(pid, 2, accounts.bob), Err(Error::ParamInvalido));
+        assert_eq!(mp.comprar_con_referido(pid, 2, accounts.alice), Err(Error::ParamInvalido));
+        let oid = mp.comprar_con_referido(pid, 2, accounts.charlie).unwrap();
+
+        // Los cambios de porcentaje no afectan a las órdenes ya creadas
+        set_next_caller(accounts.alice);
+        mp.configurar_referidos(pid, 30).unwrap();
+        mp.marcar_enviado(oid).unwrap();
+
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid).unwrap();
+
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 180);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.charlie), 20);
+        assert_eq!(mp.obtener_ganancias_referido(accounts.charlie), 20);
+        assert_eq!(
+            mp.obtener_referido_orden(oid),
+            Some(ReferidoOrden {
+                cuenta: accounts.charlie,
+                porcentaje: 10,
+                comision: Some(20)
+            })
+        );
+        assert_eq!(mp.listar_referidos_orden(0, 10).len(), 1);
+
+        set_next_caller(accounts.bob);
+        set_value(100);
+        let oid_django = mp.comprar_con_referido(pid, 1, accounts.django).unwrap();
+        let oid_eve = mp.comprar_con_referido(pid, 1, accounts.eve).unwrap();
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid_django).unwrap();
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid_django).unwrap();
+
+        assert_eq!(
+            mp.obtener_estadisticas_referido(accounts.eve),
+            EstadisticasReferido {
+                ordenes_referidas: 1,
+                ordenes_completadas: 0,
+                ganancias: 0
+            }
+        );
+        assert_eq!(
+            mp.listar_mejores_referidos(),
+            vec![
+                (
+                    accounts.django,
+                    EstadisticasReferido {
+                        ordenes_referidas: 1,
+                        ordenes_completadas: 1,
+                        ganancias: 30
+                    }
+                ),
+                (
+                    accounts.charlie,
+                    EstadisticasReferido {
+                        ordenes_referidas: 1,
+                        ordenes_completadas: 1,
+                        ganancias: 20
+                    }
+                ),
+            ]
+        );
+        let pagina: Vec<u32> = mp.listar_referidos_orden(1, 10).into_iter().map(|(oid, _)| oid).collect();
+        assert_eq!(pagina, vec![oid_django, oid_eve]);
+        assert!(mp.listar_referidos_orden(3, 10).is_empty());
+    }
+
+    #[ink::test]
+    fn fianza_minima_y_bloqueo_de_retiro() {
+        test::set_callee::<DefaultEnvironment>(get_accounts().django);
+        test::set_account_balance::<DefaultEnvironment>(get_accounts().django, 10_000_000);
+        let (accounts, mut mp) = setup_vendedor();
+        let requisito = RequisitoFianza {
+            precio_minimo: 1_000,
+            fianza_minima: 500,
+        };
+
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        assert_eq!(mp.configurar_requisito_fianza(Some(requisito)), Err(Error::SinPermiso));
+        set_value(500);
+        assert_eq!(mp.depositar_fianza(), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.configurar_requisito_fianza(Some(requisito)), Ok(()));
+        assert_eq!(mp.obtener_requisito_fianza(), Some(requisito));
+        assert_eq!(
+            mp.publicar_en_categoria("Caro".to_string(), "Desc".to_string(), 1_000, 1, CAT),
+            Err(Error::FianzaInsuficiente)
+        );
+        assert!(mp.publicar_en_categoria("Barato".to_string(), "Desc".to_string(), 999, 1, CAT).is_ok());
+
+        set_value(0);
+        assert_eq!(mp.depositar_fianza(), Err(Error::ParamInvalido));
+        set_value(300);
+        assert_eq!(mp.depositar_fianza(), Ok(300));
+        set_value(200);
+        assert_eq!(mp.depositar_fianza(), Ok(500));
+        let pid = mp
+            .publicar_en_categoria("Caro".to_string(), "Desc".to_string(), 1_000, 1, CAT)
+            .unwrap();
+
+        set_next_caller(accounts.bob);
+        set_value(1_000);
+        let oid = mp.comprar(pid, 1).unwrap();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.retirar_fianza(100), Err(Error::OrdenesAbiertas));
+        mp.marcar_enviado(oid).unwrap();
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid).unwrap();
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.retirar_fianza(0), Err(Error::ParamInvalido));
+        assert_eq!(mp.retirar_fianza(501), Err(Error::FianzaInsuficiente));
+        let saldo = mp.obtener_saldo_retirable(accounts.alice);
+        assert_eq!(mp.retirar_fianza(200), Ok(()));
+        assert_eq!(mp.obtener_fianza(accounts.alice), 300);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), saldo + 200);
+        assert_eq!(mp.darse_de_baja(), Err(Error::FianzaDepositada));
+    }
+
+    #[ink::test]
+    fn compensar_con_fianza_tras_liberar_escrow() {
+        let (accounts, mut mp, _pid, oid) = setup_con_orden(1, 100);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.compensar_con_fianza(oid, 80), Err(Error::EstadoInvalido));
+        mp.marcar_enviado(oid).unwrap();
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid).unwrap();
+
+        assert_eq!(mp.compensar_con_fianza(oid, 80), Err(Error::SinPermiso));
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.compensar_con_fianza(oid, 80), Err(Error::FianzaInsuficiente));
+        assert_eq!(mp.compensar_con_fianza(99, 80), Err(Error::OrdenInexistente));
+
+        set_value(500);
+        mp.depositar_fianza().unwrap();
+        assert_eq!(mp.compensar_con_fianza(oid, 0), Err(Error::ParamInvalido));
+        assert_eq!(mp.compensar_con_fianza(oid, 300), Ok(100));
+        assert_eq!(mp.compensar_con_fianza(oid, 80), Err(Error::OrdenYaCompensada));
+        assert_eq!(mp.obtener_fianza(accounts.alice), 400);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100);
+    }
+
+    #[ink::test]
+    fn pagos_se_acreditan_y_se_retiran() {
+        test::set_callee::<DefaultEnvironment>(get_accounts().django);
+        test::set_account_balance::<DefaultEnvironment>(get_accounts().django, 10_000_000);
+        let (accounts, mut mp, pid, oid) = setup_orden_recibida();
+        set_next_caller(accounts.bob);
+        set_value(100);
+        let oid_cancelada = mp.comprar(pid, 1).unwrap();
+        mp.solicitar_cancelacion(oid_cancelada).unwrap();
+        set_next_caller(accounts.alice);
+        mp.aceptar_cancelacion(oid_cancelada).unwrap();
+
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 0);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 100);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100);
+
+        let saldo_vendedor = test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(mp.retirar_monto(0), Err(Error::ParamInvalido));
+        assert_eq!(mp.retirar_monto(101), Err(Error::SaldoInsuficiente));
+        assert_eq!(mp.retirar_monto(30), Ok(()));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 70);
+        assert_eq!(mp.retirar(), Ok(70));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 0);
+        assert_eq!(mp.retirar(), Err(Error::SinSaldoRetirable));
+        assert_eq!(
+            test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap(),
+            saldo_vendedor + 100
+        );
+    }
+
+    #[ink::test]
+    fn mensajes_de_fondos_rechazan_reentrada() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.verificar_invariantes(), Err(Error::SinPermiso));
+
+        // Simula una llamada reentrante durante otro mensaje que mueve fondos
+        mp.bloqueado = true;
+        set_value(100);
+        assert_eq!(mp.comprar(pid, 1), Err(Error::Reentrada));
+        assert_eq!(mp.retirar(), Err(Error::Reentrada));
+        mp.bloqueado = false;
+
+        let oid = mp.comprar(pid, 1).unwrap();
+        assert!(!mp.bloqueado);
+        assert_eq!(mp.obtener_fondos_retenidos(oid), 100);
+    }
+
+    #[ink::test]
+    fn cancelacion_rechaza_reentrada() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+        set_next_caller(accounts.bob);
+        mp.solicitar_cancelacion(oid).unwrap();
+        set_next_caller(accounts.alice);
+        mp.bloqueado = true;
+        assert_eq!(mp.aceptar_cancelacion(oid), Err(Error::Reentrada));
+        mp.bloqueado = false;
+        assert_eq!(mp.aceptar_cancelacion(oid), Ok(()));
+        assert!(!mp.bloqueado);
+    }
+
+    #[ink::test]
+    fn disputa_digital_rechaza_reentrada() {
+        let (accounts, mut mp, _, oid) = setup_orden_digital();
+        set_next_caller(accounts.alice);
+        mp.entregar_clave(oid, vec![7]).unwrap();
+        set_next_caller(accounts.bob);
+        mp.disputar_orden_digital(oid).unwrap();
+        set_next_caller(accounts.alice);
+        mp.bloqueado = true;
+        assert_eq!(mp.resolver_disputa_digital(oid, true), Err(Error::Reentrada));
+        mp.bloqueado = false;
+        assert_eq!(mp.resolver_disputa_digital(oid, true), Ok(()));
+        assert!(!mp.bloqueado);
+    }
+
+    #[ink::test]
+    fn suscripcion_rechaza_reentrada() {
+        let (accounts, mut mp, pid, sid) = setup_suscripcion(3);
+        mp.bloqueado = true;
+        set_value(30);
+        assert_eq!(mp.suscribirse(pid, 1), Err(Error::Reentrada));
+        set_value(0);
+        assert_eq!(mp.cancelar_suscripcion(sid), Err(Error::Reentrada));
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cobrar_suscripcion(sid), Err(Error::Reentrada));
+        mp.bloqueado = false;
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(1));
+        assert!(!mp.bloqueado);
+    }
+
+    #[ink::test]
+    fn fianza_rechaza_reentrada() {
+        let (_, mut mp) = setup_vendedor();
+        set_value(500);
+        mp.bloqueado = true;
+        assert_eq!(mp.depositar_fianza(), Err(Error::Reentrada));
+        set_value(0);
+        assert_eq!(mp.retirar_fianza(100), Err(Error::Reentrada));
+        mp.bloqueado = false;
+        set_value(500);
+        assert_eq!(mp.depositar_fianza(), Ok(500));
+        set_value(0);
+        assert_eq!(mp.retirar_fianza(100), Ok(()));
+        assert!(!mp.bloqueado);
+    }
+
+    #[ink::test]
+    fn reembolso_de_preventas_rechaza_reentrada() {
+        let (accounts, mut mp, pid, _) = setup_preventa();
+        set_next_caller(accounts.alice);
+        set_value(0);
+        mp.bloqueado = true;
+        assert_eq!(mp.cancelar_lanzamiento(pid), Err(Error::Reentrada));
+        assert_eq!(mp.reembolsar_preventas(pid), Err(Error::Reentrada));
+        mp.bloqueado = false;
+        assert!(mp.cancelar_lanzamiento(pid).is_ok());
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 200);
+        assert!(!mp.bloqueado);
+    }
+
+    /// Balance mínimo de la cuenta del contrato en el entorno de tests.
+    const BALANCE_BASE: Balance = 1_000_000;
+
+    /// Suma al balance del contrato un pago recibido por un mensaje `payable`.
+    fn recibir_pago(monto: Balance) {
+        let contrato = test::callee::<DefaultEnvironment>();
+        let balance = test::get_account_balance::<DefaultEnvironment>(contrato).unwrap();
+        test::set_account_balance::<DefaultEnvironment>(contrato, balance + monto);
+    }
+
+    #[ink::test]
+    fn invariante_de_fondos_en_secuencias_aleatorias() {
+        let accounts = get_accounts();
+        test::set_callee::<DefaultEnvironment>(accounts.django);
+        test::set_account_balance::<DefaultEnvironment>(accounts.django, BALANCE_BASE);
+        let (_, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.charlie);
+        mp.registrar(Rol::Comprador).unwrap();
+        set_next_caller(accounts.alice);
+        let pid2 = mp
+            .publicar_en_categoria("Otro".to_string(), "Desc".to_string(), 35, 1_000, CAT)
+            .unwrap();
+
+        let compradores = [accounts.bob, accounts.charlie];
+        let productos = [(pid, 100), (pid2, 35)];
+        let cuentas = [accounts.alice, accounts.bob, accounts.charlie];
+        // Generador congruencial lineal con semilla fija para que el test sea reproducible
+        let mut semilla: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut aleatorio = |n: u64| -> usize {
+            semilla = semilla
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((semilla >> 33) % n) as usize
+        };
+
+        for _ in 0..300 {
+            let total_ordenes = mp.get_total_ordenes() as u64;
+            match aleatorio(5) {
+                0 => {
+                    let comprador = compradores[aleatorio(2)];
+                    let (id_prod, precio) = productos[aleatorio(2)];
+                    let cantidad = aleatorio(3) as u32 + 1;
+                    let monto = precio * cantidad as Balance;
+                    set_next_caller(comprador);
+                    set_value(monto);
+                    if mp.comprar(id_prod, cantidad).is_ok() {
+                        recibir_pago(monto);
+                    }
+                }
+                1 if total_ordenes > 0 => {
+                    set_next_caller(accounts.alice);
+                    let _ = mp.marcar_enviado(aleatorio(total_ordenes) as u32 + 1);
+                }
+                2 if total_ordenes > 0 => {
+                    let oid = aleatorio(total_ordenes) as u32 + 1;
+                    set_next_caller(mp.obtener_orden_publica(oid).unwrap().comprador);
+                    let _ = mp.marcar_recibido(oid);
+                }
+                3 if total_ordenes > 0 => {
+                    let oid = aleatorio(total_ordenes) as u32 + 1;
+                    set_next_caller(mp.obtener_orden_publica(oid).unwrap().comprador);
+                    if mp.solicitar_cancelacion(oid).is_ok() {
+                        set_next_caller(accounts.alice);
+                        let _ = mp.aceptar_cancelacion(oid);
+                    }
+                }
+                _ => {
+                    set_next_caller(cuentas[aleatorio(3)]);
+                    let _ = mp.retirar();
+                }
+            }
+
+            set_next_caller(accounts.alice);
+            let estado = mp.verificar_invariantes().unwrap();
+            assert!(estado.cumple);
+            assert_eq!(
+                estado.balance - BALANCE_BASE,
+                estado.fondos_retenidos + estado.saldos_retirables
+            );
+        }
+        assert!(mp.get_total_ordenes() > 0);
+    }
+
+    #[ink::test]
+    fn totales_de_fondos_siguen_a_las_suscripciones() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(3);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.verificar_invariantes().unwrap().fondos_suscripciones, 90);
+
+        test::set_block_number::<DefaultEnvironment>(150);
+        let cobrados = mp.cobrar_suscripcion(sid).unwrap();
+        assert!(cobrados > 0);
+        let estado = mp.verificar_invariantes().unwrap();
+        assert_eq!(estado.fondos_suscripciones, 90 - 30 * cobrados as Balance);
+        assert_eq!(estado.saldos_retirables, 30 * cobrados as Balance);
+
+        set_next_caller(accounts.bob);
+        mp.cancelar_suscripcion(sid).unwrap();
+        set_next_caller(accounts.alice);
+        let estado = mp.verificar_invariantes().unwrap();
+        assert_eq!(estado.fondos_suscripciones, 0);
+        assert_eq!(estado.fondos_retenidos, 0);
+        assert_eq!(estado.saldos_retirables, 90);
+    }
+
+    #[ink::test]
+    fn historial_orden_registra_cada_transicion() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        set_next_caller(accounts.bob);
+        set_value(200);
+        let oid = mp.comprar(pid, 2).unwrap();
+
+        test::set_block_timestamp::<DefaultEnvironment>(2_000);
+        mp.solicitar_cancelacion(oid).unwrap();
+        test::set_block_timestamp::<DefaultEnvironment>(3_000);
+        set_next_caller(accounts.alice);
+        mp.rechazar_cancelacion(oid).unwrap();
+        test::set_block_timestamp::<DefaultEnvironment>(4_000);
+        mp.marcar_enviado(oid).unwrap();
+        test::set_block_timestamp::<DefaultEnvironment>(5_000);
+        set_next_caller(accounts.bob);
+        mp.marcar_recibido(oid).unwrap();
+
+        assert_eq!(mp.get_total_registros_orden(oid), 5);
+        let historial = mp.historial_orden(oid, 0, MAX_REGISTROS_POR_PAGINA).unwrap();
+        let eventos: Vec<(EventoOrden, Timestamp)> = historial.iter().map(|r| (r.evento, r.momento)).collect();
+        assert_eq!(
+            eventos,
+            vec![
+                (EventoOrden::Creada, 1_000),
+                (
+                    EventoOrden::CancelacionSolicitada {
+                        solicitante: accounts.bob
+                    },
+                    2_000
+                ),
+                (EventoOrden::CancelacionRechazada, 3_000),
+                (EventoOrden::Enviada, 4_000),
+                (EventoOrden::Recibida, 5_000),
+            ]
+        );
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.historial_orden(oid, 0, 10), Ok(historial.clone()));
+        assert_eq!(mp.historial_orden(oid, 3, 10), Ok(historial[3..].to_vec()));
+        assert_eq!(mp.historial_orden(oid, 1, 2), Ok(historial[1..3].to_vec()));
+        assert_eq!(mp.historial_orden(oid, u32::MAX, u32::MAX), Ok(Vec::new()));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.historial_orden(oid, 0, 10), Err(Error::SinPermiso));
+        assert_eq!(mp.historial_orden(99, 0, 10), Err(Error::OrdenInexistente));
+    }
+
+    #[ink::test]
+    fn historial_orden_cancelada() {
+        let (accounts, mp, _, oid) = setup_orden_cancelada();
+        set_next_caller(accounts.bob);
+        let eventos: Vec<EventoOrden> = mp.historial_orden(oid, 0, 10).unwrap().into_iter().map(|r| r.evento).collect();
+        assert_eq!(
+            eventos,
+            vec![
+                EventoOrden::Creada,
+                EventoOrden::CancelacionSolicitada {
+                    solicitante: accounts.bob
+                },
+                EventoOrden::Cancelada,
+            ]
+        );
+    }
+
+    #[ink::test]
+    fn historial_orden_de_suscripcion() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(2);
+        test::set_block_number::<DefaultEnvironment>(100);
+        test::set_block_timestamp::<DefaultEnvironment>(7_000);
+        set_next_caller(accounts.alice);
+        mp.cobrar_suscripcion(sid).unwrap();
+
+        let oid = mp.get_total_ordenes();
+        let historial = mp.historial_orden(oid, 0, 10).unwrap();
+        assert_eq!(historial.len(), 2);
+        assert_eq!(historial[0].evento, EventoOrden::Creada);
+        assert_eq!(historial[1].evento, EventoOrden::Recibida);
+        assert!(historial.iter().all(|r| r.momento == 7_000 && r.bloque == 100));
+    }
+
+    #[ink::test]
+    fn producto_registra_modificaciones_y_ventas() {
+        let (accounts, mut mp) = setup_vendedor();
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+
+        set_next_caller(accounts.alice);
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let pid = mp.publicar_en_categoria("Test".to_string(), "Desc".to_string(), 100, 10, CAT).unwrap();
+        let producto = mp.obtener_producto(pid).unwrap();
+        assert_eq!((producto.creado_en, producto.actualizado_en), (1_000, 1_000));
+
+        test::set_block_timestamp::<DefaultEnvironment>(2_000);
+        mp.pausar_producto(pid).unwrap();
+        assert_eq!(mp.obtener_producto(pid).unwrap().actualizado_en, 2_000);
+        mp.reactivar_producto(pid).unwrap();
+
+        test::set_block_timestamp::<DefaultEnvironment>(3_000);
+        set_next_caller(accounts.bob);
+        set_value(100);
+        mp.comprar(pid, 1).unwrap();
+        let producto = mp.obtener_producto(pid).unwrap();
+        assert_eq!((producto.creado_en, producto.actualizado_en), (1_000, 3_000));
+
+        test::set_block_timestamp::<DefaultEnvironment>(4_000);
+        set_value(5);
+        mp.reservar(pid, 1).unwrap();
+        assert_eq!(mp.obtener_producto(pid).unwrap().actualizado_en, 4_000);
+    }
+
+    #[ink::test]
+    fn buscar_mas_recientes_por_fecha_de_publicacion() {
+        let (_, mut mp) = setup_vendedor();
+        test::set_block_timestamp::<DefaultEnvironment>(5_000);
+        let nuevo = mp.publicar_en_categoria("Nuevo".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let viejo = mp.publicar_en_categoria("Viejo".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+        let mismo_momento = mp.publicar_en_categoria("Otro".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+
+        let ids: Vec<u32> = mp
+            .buscar_productos(
+                FiltroBusqueda {
+                    id_categoria: Some(CAT),
+                    ..Default::default()
+                },
+                OrdenBusqueda::MasRecientes,
+                None,
+                10,
+            )
+            .unwrap()
+            .productos
+            .into_iter()
+            .map(|(pid, _)| pid)
+            .collect();
+        assert_eq!(ids, vec![nuevo, mismo_momento, viejo]);
+    }
+
+    #[ink::test]
+    fn depurar_publicaciones_inactivas() {
+        let (accounts, mut mp) = setup_vendedor();
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let inactivo = mp.publicar_en_categoria("Viejo".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+        let vendido = mp.publicar_en_categoria("Otro".to_string(), "Desc".to_string(), 100, 1, CAT).unwrap();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.depurar_publicaciones(vec![inactivo]), Err(Error::SinPlazoInactividad));
+        assert_eq!(mp.configurar_plazo_inactividad(Some(10_000)), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.configurar_plazo_inactividad(Some(0)), Err(Error::ParamInvalido));
+        assert_eq!(mp.configurar_plazo_inactividad(Some(10_000)), Ok(()));
+        assert_eq!(mp.obtener_plazo_inactividad(), Some(10_000));
+
+        set_next_caller(accounts.bob);
+        mp.registrar(Rol::Comprador).unwrap();
+        test::set_block_timestamp::<DefaultEnvironment>(5_000);
+        set_value(100);
+        mp.comprar(vendido, 1).unwrap();
+
+        set_next_caller(accounts.charlie);
+        test::set_block_timestamp::<DefaultEnvironment>(10_999);
+        assert_eq!(mp.depurar_publicaciones(vec![inactivo, vendido]), Ok(0));
+        test::set_block_timestamp::<DefaultEnvironment>(11_000);
+        assert_eq!(mp.depurar_publicaciones(vec![inactivo, vendido, 99]), Ok(1));
+        assert!(!mp.obtener_producto(inactivo).unwrap().activo);
+        assert!(mp.obtener_producto(vendido).unwrap().activo);
+        assert_eq!(mp.depurar_publicaciones(vec![inactivo]), Ok(0));
+        assert_eq!(
+            ids(mp.buscar_productos(FiltroBusqueda::default(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![vendido]
+        );
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.reactivar_producto(inactivo), Ok(()));
+        assert_eq!(mp.obtener_producto(inactivo).unwrap().actualizado_en, 11_000);
+        assert_eq!(
+            ids(mp.buscar_productos(FiltroBusqueda::default(), OrdenBusqueda::PrecioAscendente, None, 10).unwrap()),
+            vec![inactivo, vendido]
+        );
+    }
+
+    #[ink::test]
+    fn mensajes_de_orden_entre_participantes() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.enviar_mensaje(oid, b"direccion cifrada".to_vec()), Ok(()));
+        assert_eq!(mp.enviar_mensaje(oid, Vec::new()), Err(Error::ParamInvalido));
+        assert_eq!(mp.enviar_mensaje(oid, vec![0; 513]), Err(Error::ParamInvalido));
+        assert_eq!(mp.enviar_mensaje(99, vec![1]), Err(Error::OrdenInexistente));
+
+        test::set_block_timestamp::<DefaultEnvironment>(2_000);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.enviar_mensaje(oid, vec![7; 512]), Ok(()));
+
+        let mensajes = mp.obtener_mensajes(oid, 0, MAX_MENSAJES_POR_PAGINA).unwrap();
+        assert_eq!(
+            mensajes,
+            vec![
+                MensajeOrden {
+                    remitente: accounts.bob,
+                    contenido: b"direccion cifrada".to_vec(),
+                    momento: 1_000,
+                },
+                MensajeOrden {
+                    remitente: accounts.alice,
+                    contenido: vec![7; 512],
+                    momento: 2_000,
+                },
+            ]
+        );
+
+        assert_eq!(mp.get_total_mensajes_orden(oid), 2);
+        assert_eq!(mp.obtener_mensajes(oid, 1, 5), Ok(mensajes[1..].to_vec()));
+        assert_eq!(mp.obtener_mensajes(oid, u32::MAX, u32::MAX), Ok(Vec::new()));
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.enviar_mensaje(oid, vec![1]), Err(Error::SinPermiso));
+        assert_eq!(mp.obtener_mensajes(oid, 0, 5), Err(Error::SinPermiso));
+    }
+
+    #[ink::test]
+    fn arbitro_puede_leer_mensajes_de_orden() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+        set_next_caller(accounts.bob);
+        mp.enviar_mensaje(oid, vec![1, 2, 3]).unwrap();
+        assert_eq!(mp.asignar_arbitro(oid, Some(accounts.charlie)), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.asignar_arbitro(oid, Some(accounts.bob)), Err(Error::ParamInvalido));
+        assert_eq!(mp.asignar_arbitro(99, Some(accounts.charlie)), Err(Error::OrdenInexistente));
+        assert_eq!(mp.asignar_arbitro(oid, Some(accounts.charlie)), Ok(()));
+        assert_eq!(mp.obtener_arbitro(oid), Some(accounts.charlie));
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.obtener_mensajes(oid, 0, 5).unwrap().len(), 1);
+        assert_eq!(mp.enviar_mensaje(oid, vec![1]), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.asignar_arbitro(oid, None), Ok(()));
+        assert_eq!(mp.obtener_arbitro(oid), None);
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.obtener_mensajes(oid, 0, 5), Err(Error::SinPermiso));
+    }
+
+    #[ink::test]
+    fn mensajes_de_orden_tienen_limite_por_participante() {
+        let (accounts, mut mp, _, oid) = setup_con_orden(1, 100);
+        set_next_caller(accounts.bob);
+        for _ in 0..MAX_MENSAJES_POR_PARTICIPANTE {
+            mp.enviar_mensaje(oid, vec![1]).unwrap();
+        }
+        assert_eq!(mp.enviar_mensaje(oid, vec![1]), Err(Error::MensajesLlenos));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.enviar_mensaje(oid, vec![2]), Ok(()));
+        assert_eq!(mp.get_total_mensajes_orden(oid), MAX_MENSAJES_POR_PARTICIPANTE + 1);
+        assert_eq!(
+            mp.obtener_mensajes(oid, 0, u32::MAX).unwrap().len(),
+            MAX_MENSAJES_POR_PAGINA as usize
+        );
+        let ultimo = mp.obtener_mensajes(oid, MAX_MENSAJES_POR_PARTICIPANTE, 5).unwrap();
+        assert_eq!(ultimo.len(), 1);
+        assert_eq!(ultimo[0].remitente, accounts.alice);
+    }
+
+    #[ink::test]
+    fn comprar_para_destinatario_confirma_y_califica() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        set_value(200);
+        assert_eq!(mp.comprar_para(pid, 2, accounts.bob), Err(Error::ParamInvalido));
+        assert_eq!(mp.comprar_para(pid, 2, accounts.alice), Err(Error::ParamInvalido));
+        assert_eq!(mp.comprar_para(99, 2, accounts.charlie), Err(Error::ProdInexistente));
+        let oid = mp.comprar_para(pid, 2, accounts.charlie).unwrap();
+
+        let orden = mp.obtener_orden(oid).unwrap();
+        assert_eq!((orden.comprador, orden.destinatario), (accounts.bob, accounts.charlie));
+        assert_eq!(mp.listar_ordenes_de_comprador(accounts.bob), vec![orden.clone()]);
+        assert_eq!(mp.listar_ordenes_de_comprador(accounts.charlie), vec![orden.clone()]);
+        assert_eq!(
+            mp.listar_ordenes_de_comprador_paginadas(accounts.charlie, 0, MAX_ORDENES_POR_PAGINA),
+            vec![orden.clone()]
+        );
+        assert_eq!(
+            mp.listar_ordenes_de_destinatario(accounts.charlie, 0, MAX_ORDENES_POR_PAGINA),
+            vec![orden.clone()]
+        );
+        assert!(mp.listar_ordenes_de_destinatario(accounts.bob, 0, MAX_ORDENES_POR_PAGINA).is_empty());
+
+        set_value(100);
+        let propia = mp.comprar(pid, 1).unwrap();
+        assert_eq!(mp.get_total_ordenes_de_cuenta(accounts.bob), 2);
+        let recibidas = mp.listar_ordenes_de_destinatario(accounts.bob, 1, 1);
+        assert_eq!(recibidas, vec![mp.obtener_orden(propia).unwrap()]);
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.obtener_orden(oid), Ok(orden));
+        assert_eq!(mp.enviar_mensaje(oid, b"direccion".to_vec()), Ok(()));
+
+        set_next_caller(accounts.alice);
+        mp.marcar_enviado(oid).unwrap();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.marcar_recibido(oid), Err(Error::SinPermiso));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.marcar_recibido(oid), Ok(()));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.calificar_vendedor(oid, 5), Err(Error::SinPermiso));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.calificar_vendedor(oid, 4), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.alice).unwrap().como_vendedor, (4, 1));
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.calificar_comprador(oid, 5), Ok(()));
+        assert_eq!(mp.obtener_reputacion(accounts.bob).unwrap().como_comprador, (5, 1));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 200);
+    }
+
+    #[ink::test]
+    fn comprar_para_reembolsa_al_comprador() {
+        let (accounts, mut mp, pid) = setup_vendedor_producto_comprador();
+        set_next_caller(accounts.bob);
+        set_value(100);
+        let oid = mp.comprar_para(pid, 1, accounts.charlie).unwrap();
+
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.solicitar_cancelacion(oid), Err(Error::SinPermiso));
+
+        set_next_caller(accounts.bob);
+        mp.solicitar_cancelacion(oid).unwrap();
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.aceptar_cancelacion(oid), Ok(()));
+
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.charlie), 0);
+        assert_eq!(mp.asignar_arbitro(oid, Some(accounts.charlie)), Err(Error::ParamInvalido));
+    }
+
+    #[ink::test]
+    fn regalo_digital_lo_disputa_el_destinatario() {
+        let (accounts, mut mp, pid, _) = setup_orden_digital();
+        set_next_caller(accounts.bob);
+        set_value(100);
+        let retirada = mp.comprar_para(pid, 1, accounts.charlie).unwrap();
+        let reembolsada = mp.comprar_para(pid, 1, accounts.charlie).unwrap();
+        set_next_caller(accounts.alice);
+        mp.entregar_clave(retirada, b"clave".to_vec()).unwrap();
+        mp.entregar_clave(reembolsada, b"clave".to_vec()).unwrap();
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.disputar_orden_digital(retirada), Err(Error::SinPermiso));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.disputar_orden_digital(retirada), Ok(()));
+        assert_eq!(mp.disputar_orden_digital(reembolsada), Ok(()));
+
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.resolver_disputa_digital(retirada, false), Err(Error::SinPermiso));
+        set_next_caller(accounts.charlie);
+        assert_eq!(mp.resolver_disputa_digital(retirada, false), Ok(()));
+        assert_eq!(mp.obtener_orden(retirada).unwrap().estado, Estado::Recibido);
+
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.resolver_disputa_digital(reembolsada, true), Ok(()));
+        assert_eq!(mp.obtener_orden(reembolsada).unwrap().estado, Estado::Cancelada);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 100);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.charlie), 0);
+    }
+
+    #[ink::test]
+    fn suscripcion_cobra_periodos_acumulados_por_lotes() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(45);
+        let ordenes_previas = mp.get_total_ordenes();
+
+        test::set_block_number::<DefaultEnvironment>(100 * 44);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(MAX_PERIODOS_POR_COBRO));
+        assert_eq!(mp.get_total_ordenes(), ordenes_previas + MAX_PERIODOS_POR_COBRO);
+        assert!(mp.obtener_suscripcion(sid).unwrap().activa);
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(MAX_PERIODOS_POR_COBRO));
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(5));
+        assert_eq!(mp.get_total_ordenes(), ordenes_previas + 45);
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 30 * 45);
+        assert!(!mp.obtener_suscripcion(sid).unwrap().activa);
+    }
+
+    #[ink::test]
+    fn suscripcion_cancelada_con_periodos_pendientes_sigue_hasta_cobrarlos() {
+        let (accounts, mut mp, _, sid) = setup_suscripcion(45);
+
+        test::set_block_number::<DefaultEnvironment>(100 * 29);
+        set_next_caller(accounts.bob);
+        assert_eq!(mp.cancelar_suscripcion(sid), Ok(()));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.bob), 30 * 15);
+        let suscripcion = mp.obtener_suscripcion(sid).unwrap();
+        assert_eq!((suscripcion.periodos_pagados, suscripcion.periodos_cobrados), (30, 20));
+        assert!(suscripcion.activa);
+
+        test::set_block_number::<DefaultEnvironment>(100 * 40);
+        set_next_caller(accounts.alice);
+        assert_eq!(mp.cobrar_suscripcion(sid), Ok(10));
+        assert_eq!(mp.obtener_saldo_retirable(accounts.alice), 30 * 30);
+        assert!(!mp.obtener_suscripcion(sid).unwrap().activa);
+        assert_eq!(mp.verificar_invariantes().unwrap().fondos_suscripciones, 0);
+        assert_eq!(mp.obtener_ordenes_abiertas(accounts.bob), (0, 0));
+    }
 }
\ No newline at end of file
@@ -9,7 +9,9 @@
 /// - Top 5 vendedores con mejor reputación
 /// - Top 5 compradores con mejor reputación
 /// - Productos más vendidos
-/// - Estadísticas por categoría
+/// - Productos más deseados (guardados como favoritos)
+/// - Ranking de referidos por comisiones cobradas
+/// - Estadísticas por categoría, acumuladas a lo largo del árbol de categorías
 /// - Cantidad de órdenes por usuario
 ///
 /// ## Nota importante
@@ -20,7 +22,15 @@ mod reportes {
     use ink::prelude::vec::Vec;
     use scale::{Decode, Encode};
 
-    use market::{Estado, MarketplaceRef, Orden, Producto, ReputacionUsuario};
+    use market::{
+        Categoria, EstadisticasReferido, Estado, MarketplaceRef, Orden, Producto, ReputacionUsuario,
+    };
+
+    /// Cantidad de usuarios pedidos por página al Marketplace (su máximo por página).
+    const TAMANO_PAGINA_USUARIOS: u32 = 100;
+
+    /// Cantidad de IDs de producto pedidos por página al contar favoritos (su máximo por página).
+    const TAMANO_PAGINA_FAVORITOS: u32 = 100;
 
     /// Representa un usuario con su reputación calculada.
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
@@ -43,14 +53,44 @@ mod reportes {
         pub id_producto: u32,
         /// El nombre del producto.
         pub nombre: String,
-        /// La categoría del producto.
-        pub categoria: String,
+        /// El ID de la categoría del producto en el registro del Marketplace.
+        pub id_categoria: u32,
         /// El vendedor del producto.
         pub vendedor: AccountId,
         /// Cantidad total de unidades vendidas.
         pub unidades_vendidas: u32,
     }
 
+    /// Representa un producto con la cantidad de usuarios que lo guardaron como favorito.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ProductoDeseado {
+        /// El ID del producto.
+        pub id_producto: u32,
+        /// El nombre del producto.
+        pub nombre: String,
+        /// El ID de la categoría del producto en el registro del Marketplace.
+        pub id_categoria: u32,
+        /// El vendedor del producto.
+        pub vendedor: AccountId,
+        /// Cantidad de usuarios que tienen el producto en favoritos.
+        pub cantidad_favoritos: u32,
+    }
+
+    /// Rendimiento de una cuenta como referido.
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RendimientoReferido {
+        /// La cuenta del referido.
+        pub referido: AccountId,
+        /// Cantidad de órdenes compradas con este referido.
+        pub ordenes_referidas: u32,
+        /// Cantidad de órdenes referidas que ya se completaron y pagaron comisión.
+        pub ordenes_completadas: u32,
+        /// Total de comisiones cobradas.
+        pub ganancias: Balance,
+    }
+
     /// Estadísticas agregadas por categoría.
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -181,6 +221,39 @@ mod reportes {
             self._productos_mas_vendidos(limite)
         }
 
+        /// Obtiene los productos más deseados del marketplace.
+        ///
+        /// # Argumentos
+        ///
+        /// * `limite` - Cantidad máxima de productos a retornar.
+        ///
+        /// # Retorno
+        ///
+        /// Lista de productos ordenada por cantidad de usuarios que los guardaron
+        /// como favoritos (descendente). Solo incluye productos con al menos un favorito.
+        #[ink(message)]
+        pub fn productos_mas_deseados(&self, limite: u32) -> Vec<ProductoDeseado> {
+            self._productos_mas_deseados(limite)
+        }
+
+        /// Obtiene el ranking de referidos del marketplace.
+        ///
+        /// # Argumentos
+        ///
+        /// * `limite` - Cantidad máxima de referidos a retornar.
+        ///
+        /// # Retorno
+        ///
+        /// Lista de referidos ordenada por comisiones cobradas (descendente), con la
+        /// cantidad de órdenes referidas y completadas de cada uno. Se arma a partir de
+        /// los referidos que el marketplace mantiene como mejores, por lo que nunca supera
+        /// esa cantidad.
+        #[ink(message)]
+        pub fn ranking_referidos(&self, limite: u32) -> Vec<RendimientoReferido> {
+            let mejores = self.marketplace().listar_mejores_referidos();
+            Self::_procesar_ranking_referidos(mejores, limite)
+        }
+
         /// Obtiene estadísticas agregadas de todas las categorías.
         ///
         /// # Retorno
@@ -203,18 +276,72 @@ mod reportes {
         ///
         /// # Argumentos
         ///
-        /// * `categoria` - Nombre exacto de la categoría a consultar.
+        /// * `categoria` - Nombre de la categoría a consultar. Se busca en el registro del
+        ///   Marketplace sin distinguir mayúsculas, tildes ni espacios al inicio o al final.
         ///
         /// # Retorno
         ///
         /// - `Ok(EstadisticasCategoria)` con las estadísticas de la categoría.
-        /// - `Err(Error::CategoriaNoEncontrada)` si la categoría no existe.
+        /// - `Err(Error::CategoriaNoEncontrada)` si la categoría no está registrada o no
+        ///   tiene productos.
         #[ink(message)]
         pub fn estadisticas_categoria(
             &self,
             categoria: String,
         ) -> Result<EstadisticasCategoria, Error> {
-            self._estadisticas_categoria(categoria)
+            let id_categoria = self
+                .marketplace()
+                .buscar_categoria(categoria)
+                .ok_or(Error::CategoriaNoEncontrada)?;
+            self._estadisticas_categoria(id_categoria)
+        }
+
+        /// Obtiene las estadísticas de una categoría del registro, indicada por su ID.
+        ///
+        /// # Argumentos
+        ///
+        /// * `id_categoria` - El ID de la categoría en el registro del Marketplace.
+        ///
+        /// # Retorno
+        ///
+        /// Lo mismo que `estadisticas_categoria`.
+        #[ink(message)]
+        pub fn estadisticas_categoria_por_id(
+            &self,
+            id_categoria: u32,
+        ) -> Result<EstadisticasCategoria, Error> {
+            self._estadisticas_categoria(id_categoria)
+        }
+
+        /// Obtiene las estadísticas de una categoría del registro, acumulando las de
+        /// todas sus subcategorías.
+        ///
+        /// # Argumentos
+        ///
+        /// * `id_categoria` - El ID de la categoría en el registro del Marketplace.
+        ///
+        /// # Retorno
+        ///
+        /// - `Ok(EstadisticasCategoria)` con las ventas, unidades, calificaciones y productos
+        ///   de la categoría y de todas sus descendientes.
+        /// - `Err(Error::CategoriaNoEncontrada)` si la categoría no está registrada.
+        #[ink(message)]
+        pub fn estadisticas_categoria_acumuladas(
+            &self,
+            id_categoria: u32,
+        ) -> Result<EstadisticasCategoria, Error> {
+            self._estadisticas_categoria_acumuladas(id_categoria)
+        }
+
+        /// Obtiene las estadísticas acumuladas de todas las categorías del registro.
+        ///
+        /// # Retorno
+        ///
+        /// Una entrada por cada categoría registrada, en orden de ID, donde cada una
+        /// incluye también los datos de sus subcategorías.
+        #[ink(message)]
+        pub fn estadisticas_por_categoria_acumuladas(&self) -> Vec<EstadisticasCategoria> {
+            self._estadisticas_por_categoria_acumuladas()
         }
 
         /// Obtiene el conteo de órdenes de un usuario específico.
@@ -261,7 +388,7 @@ mod reportes {
         ///
         /// # Retorno
         ///
-        /// Lista de nombres de categorías únicas extraídas de los productos publicados.
+        /// Lista de nombres de las categorías del registro que tienen productos publicados.
         #[ink(message)]
         pub fn listar_categorias(&self) -> Vec<String> {
             self._listar_categorias()
@@ -272,6 +399,43 @@ mod reportes {
             ink::env::call::FromAccountId::from_account_id(self.marketplace_address)
         }
 
+        /// Obtiene todos los usuarios registrados recorriendo las páginas de
+        /// `listar_usuarios` del Marketplace.
+        ///
+        /// Complejidad: O(u / p) llamadas externas, con p = `TAMANO_PAGINA_USUARIOS`.
+        fn listar_todos_usuarios(&self) -> Vec<AccountId> {
+            let marketplace = self.marketplace();
+            let mut usuarios = Vec::new();
+            loop {
+                let desde = u32::try_from(usuarios.len()).unwrap_or(u32::MAX);
+                let pagina = marketplace.listar_usuarios(desde, TAMANO_PAGINA_USUARIOS);
+                let completa = pagina.len() as u32 == TAMANO_PAGINA_USUARIOS;
+                usuarios.extend(pagina);
+                if !completa {
+                    return usuarios;
+                }
+            }
+        }
+
+        /// Obtiene los contadores de favoritos de todos los productos recorriendo las páginas
+        /// de `listar_conteo_favoritos` del Marketplace.
+        ///
+        /// Complejidad: O(p / t) llamadas externas, con t = `TAMANO_PAGINA_FAVORITOS`.
+        fn listar_todos_conteos_favoritos(&self) -> Vec<(u32, u32)> {
+            let marketplace = self.marketplace();
+            let total = marketplace.get_total_productos();
+            let mut conteos = Vec::new();
+            let mut desde: u32 = 1;
+            while desde <= total {
+                conteos.extend(marketplace.listar_conteo_favoritos(desde, TAMANO_PAGINA_FAVORITOS));
+                let Some(siguiente) = desde.checked_add(TAMANO_PAGINA_FAVORITOS) else {
+                    break;
+                };
+                desde = siguiente;
+            }
+            conteos
+        }
+
         /// Lógica interna para calcular el top de vendedores.
         ///
         /// # Optimización
@@ -386,7 +550,7 @@ mod reportes {
                 }
             }
 
-            ventas.sort_by(|a, b| b.1.cmp(&a.1));
+            ventas.sort_by_key(|v| core::cmp::Reverse(v.1));
 
             ventas
                 .iter()
@@ -398,7 +562,7 @@ mod reportes {
                         .map(|(_, producto)| ProductoVendido {
                             id_producto: *id_prod,
                             nombre: producto.nombre.clone(),
-                            categoria: producto.categoria.clone(),
+                            id_categoria: producto.id_categoria,
                             vendedor: producto.vendedor,
                             unidades_vendidas: *unidades,
                         })
@@ -406,27 +570,85 @@ mod reportes {
                 .collect()
         }
 
-        /// Lógica interna para estadísticas por categoría.
+        /// Lógica interna para productos más deseados.
         ///
-        /// Complejidad: O(p + o) donde p = cantidad de productos y o = cantidad de órdenes.
-        fn _estadisticas_por_categoria(&self) -> Vec<EstadisticasCategoria> {
+        /// Complejidad: O(p) donde p = cantidad de productos.
+        fn _productos_mas_deseados(&self, limite: u32) -> Vec<ProductoDeseado> {
             let marketplace = self.marketplace();
+            let conteos = self.listar_todos_conteos_favoritos();
             let productos = marketplace.listar_todos_productos();
-            let ordenes = marketplace.listar_todas_ordenes();
+            Self::_procesar_productos_mas_deseados(conteos, productos, limite)
+        }
 
-            let categorias_unicas: Vec<String> = Self::_procesar_listar_categorias(&productos);
+        /// Procesa los contadores de favoritos y los productos para calcular los más deseados.
+        ///
+        /// Función pura que puede ser testeada sin dependencias externas.
+        fn _procesar_productos_mas_deseados(
+            mut conteos: Vec<(u32, u32)>,
+            productos: Vec<(u32, Producto)>,
+            limite: u32,
+        ) -> Vec<ProductoDeseado> {
+            conteos.retain(|(_, cantidad)| *cantidad > 0);
+            conteos.sort_by_key(|c| core::cmp::Reverse(c.1));
 
-            let calificaciones: Vec<(String, (u32, u32))> = categorias_unicas
+            conteos
                 .iter()
-                .map(|cat| {
-                    let calif = marketplace
-                        .obtener_calificacion_categoria(cat.clone())
-                        .unwrap_or((0, 0));
-                    (cat.clone(), calif)
+                .filter_map(|(id_prod, cantidad)| {
+                    productos
+                        .iter()
+                        .find(|(pid, _)| pid == id_prod)
+                        .map(|(_, producto)| ProductoDeseado {
+                            id_producto: *id_prod,
+                            nombre: producto.nombre.clone(),
+                            id_categoria: producto.id_categoria,
+                            vendedor: producto.vendedor,
+                            cantidad_favoritos: *cantidad,
+                        })
+                })
+                .take(limite as usize)
+                .collect()
+        }
+
+        /// Convierte las estadísticas de los referidos en su rendimiento y los ordena por
+        /// ganancias.
+        ///
+        /// Función pura que puede ser testeada sin dependencias externas.
+        fn _procesar_ranking_referidos(
+            referidos: Vec<(AccountId, EstadisticasReferido)>,
+            limite: u32,
+        ) -> Vec<RendimientoReferido> {
+            let mut ranking: Vec<RendimientoReferido> = referidos
+                .into_iter()
+                .map(|(referido, estadisticas)| RendimientoReferido {
+                    referido,
+                    ordenes_referidas: estadisticas.ordenes_referidas,
+                    ordenes_completadas: estadisticas.ordenes_completadas,
+                    ganancias: estadisticas.ganancias,
                 })
                 .collect();
 
-            Self::_procesar_estadisticas_por_categoria(productos, ordenes, calificaciones)
+            ranking.sort_by_key(|r| core::cmp::Reverse(r.ganancias));
+            ranking.truncate(limite as usize);
+            ranking
+        }
+
+        /// Lógica interna para estadísticas por categoría.
+        ///
+        /// Complejidad: O(c + p + o) donde c = cantidad de categorías, p = cantidad de
+        /// productos y o = cantidad de órdenes.
+        fn _estadisticas_por_categoria(&self) -> Vec<EstadisticasCategoria> {
+            let marketplace = self.marketplace();
+            let categorias = marketplace.listar_categorias_registradas();
+            let productos = marketplace.listar_todos_productos();
+            let ordenes = marketplace.listar_todas_ordenes();
+            let calificaciones = marketplace.listar_calificaciones_categorias();
+
+            Self::_procesar_estadisticas_por_categoria(
+                &categorias,
+                productos,
+                ordenes,
+                calificaciones,
+            )
         }
 
         /// Procesa productos, órdenes y calificaciones para generar estadísticas por categoría.
@@ -434,12 +656,13 @@ mod reportes {
         /// Función pura que puede ser testeada sin dependencias externas.
         #[allow(clippy::arithmetic_side_effects)]
         fn _procesar_estadisticas_por_categoria(
+            categorias_registradas: &[(u32, Categoria)],
             productos: Vec<(u32, Producto)>,
             ordenes: Vec<(u32, Orden)>,
-            calificaciones: Vec<(String, (u32, u32))>,
+            calificaciones: Vec<(u32, (u32, u32))>,
         ) -> Vec<EstadisticasCategoria> {
             struct DatosCat {
-                categoria: String,
+                id_categoria: u32,
                 total_ventas: u32,
                 total_unidades: u32,
                 suma_calif: u32,
@@ -452,11 +675,11 @@ mod reportes {
             for (_pid, producto) in &productos {
                 let found = categorias
                     .iter_mut()
-                    .find(|c| c.categoria == producto.categoria);
+                    .find(|c| c.id_categoria == producto.id_categoria);
                 match found {
                     Some(cat) => cat.cant_productos = cat.cant_productos.saturating_add(1),
                     None => categorias.push(DatosCat {
-                        categoria: producto.categoria.clone(),
+                        id_categoria: producto.id_categoria,
                         total_ventas: 0,
                         total_unidades: 0,
                         suma_calif: 0,
@@ -475,7 +698,7 @@ mod reportes {
                     {
                         if let Some(cat) = categorias
                             .iter_mut()
-                            .find(|c| c.categoria == producto.categoria)
+                            .find(|c| c.id_categoria == producto.id_categoria)
                         {
                             cat.total_ventas = cat.total_ventas.saturating_add(1);
                             cat.total_unidades = cat.total_unidades.saturating_add(orden.cantidad);
@@ -487,7 +710,7 @@ mod reportes {
             for cat in categorias.iter_mut() {
                 if let Some((_, (suma, cant))) = calificaciones
                     .iter()
-                    .find(|(nombre, _)| nombre == &cat.categoria)
+                    .find(|(id, _)| *id == cat.id_categoria)
                 {
                     cat.suma_calif = *suma;
                     cat.cant_calif = *cant;
@@ -506,7 +729,11 @@ mod reportes {
                     };
 
                     EstadisticasCategoria {
-                        categoria: cat.categoria,
+                        categoria: Self::_nombre_categoria(
+                            categorias_registradas,
+                            cat.id_categoria,
+                        )
+                        .unwrap_or_default(),
                         total_ventas: cat.total_ventas,
                         total_unidades: cat.total_unidades,
                         calificacion_promedio_x100: promedio,
@@ -519,16 +746,23 @@ mod reportes {
         /// Lógica interna para estadísticas de una categoría específica.
         fn _estadisticas_categoria(
             &self,
-            categoria: String,
+            id_categoria: u32,
         ) -> Result<EstadisticasCategoria, Error> {
             let marketplace = self.marketplace();
+            let categorias = marketplace.listar_categorias_registradas();
             let productos = marketplace.listar_todos_productos();
             let ordenes = marketplace.listar_todas_ordenes();
             let calificacion = marketplace
-                .obtener_calificacion_categoria(categoria.clone())
+                .obtener_calificacion_categoria_por_id(id_categoria)
                 .unwrap_or((0, 0));
 
-            Self::_procesar_estadisticas_categoria(productos, ordenes, categoria, calificacion)
+            Self::_procesar_estadisticas_categoria(
+                &categorias,
+                productos,
+                ordenes,
+                id_categoria,
+                calificacion,
+            )
         }
 
         /// Procesa datos para obtener estadísticas de una categoría específica.
@@ -536,17 +770,20 @@ mod reportes {
         /// Función pura que puede ser testeada sin dependencias externas.
         #[allow(clippy::arithmetic_side_effects)]
         fn _procesar_estadisticas_categoria(
+            categorias: &[(u32, Categoria)],
             productos: Vec<(u32, Producto)>,
             ordenes: Vec<(u32, Orden)>,
-            categoria: String,
+            id_categoria: u32,
             calificacion: (u32, u32),
         ) -> Result<EstadisticasCategoria, Error> {
+            let categoria = Self::_nombre_categoria(categorias, id_categoria)
+                .ok_or(Error::CategoriaNoEncontrada)?;
             let mut cantidad_productos: u32 = 0;
             let mut total_ventas: u32 = 0;
             let mut total_unidades: u32 = 0;
 
             for (_pid, producto) in &productos {
-                if producto.categoria == categoria {
+                if producto.id_categoria == id_categoria {
                     cantidad_productos = cantidad_productos.saturating_add(1);
                 }
             }
@@ -562,7 +799,7 @@ mod reportes {
                         .find(|(pid, _)| *pid == orden.id_prod)
                         .map(|(_, p)| p)
                     {
-                        if producto.categoria == categoria {
+                        if producto.id_categoria == id_categoria {
                             total_ventas = total_ventas.saturating_add(1);
                             total_unidades = total_unidades.saturating_add(orden.cantidad);
                         }
@@ -587,6 +824,142 @@ mod reportes {
             })
         }
 
+        /// Obtiene el nombre canónico de una categoría del registro.
+        fn _nombre_categoria(categorias: &[(u32, Categoria)], id_categoria: u32) -> Option<String> {
+            categorias
+                .iter()
+                .find(|(id, _)| *id == id_categoria)
+                .map(|(_, cat)| cat.nombre.clone())
+        }
+
+        /// Lógica interna para estadísticas acumuladas de una categoría.
+        ///
+        /// Complejidad: O(c + p + o) llamadas de lectura, donde c = cantidad de categorías.
+        fn _estadisticas_categoria_acumuladas(
+            &self,
+            id_categoria: u32,
+        ) -> Result<EstadisticasCategoria, Error> {
+            let marketplace = self.marketplace();
+            let categorias = marketplace.listar_categorias_registradas();
+            let productos = marketplace.listar_todos_productos();
+            let ordenes = marketplace.listar_todas_ordenes();
+            let calificaciones = marketplace.listar_calificaciones_categorias();
+
+            Self::_procesar_estadisticas_acumuladas(
+                &categorias,
+                &productos,
+                &ordenes,
+                &calificaciones,
+                id_categoria,
+            )
+        }
+
+        /// Lógica interna para estadísticas acumuladas de todas las categorías.
+        fn _estadisticas_por_categoria_acumuladas(&self) -> Vec<EstadisticasCategoria> {
+            let marketplace = self.marketplace();
+            let categorias = marketplace.listar_categorias_registradas();
+            let productos = marketplace.listar_todos_productos();
+            let ordenes = marketplace.listar_todas_ordenes();
+            let calificaciones = marketplace.listar_calificaciones_categorias();
+
+            categorias
+                .iter()
+                .filter_map(|(id, _)| {
+                    Self::_procesar_estadisticas_acumuladas(
+                        &categorias,
+                        &productos,
+                        &ordenes,
+                        &calificaciones,
+                        *id,
+                    )
+                    .ok()
+                })
+                .collect()
+        }
+
+        /// Procesa los datos de una categoría y de todas sus descendientes.
+        ///
+        /// Función pura que puede ser testeada sin dependencias externas.
+        fn _procesar_estadisticas_acumuladas(
+            categorias: &[(u32, Categoria)],
+            productos: &[(u32, Producto)],
+            ordenes: &[(u32, Orden)],
+            calificaciones: &[(u32, (u32, u32))],
+            raiz: u32,
+        ) -> Result<EstadisticasCategoria, Error> {
+            let nombre =
+                Self::_nombre_categoria(categorias, raiz).ok_or(Error::CategoriaNoEncontrada)?;
+
+            let mut cantidad_productos: u32 = 0;
+            let mut total_ventas: u32 = 0;
+            let mut total_unidades: u32 = 0;
+            let mut suma_calif: u32 = 0;
+            let mut cant_calif: u32 = 0;
+
+            for (_pid, producto) in productos {
+                if Self::_es_descendiente(categorias, producto.id_categoria, raiz) {
+                    cantidad_productos = cantidad_productos.saturating_add(1);
+                }
+            }
+
+            for (_oid, orden) in ordenes {
+                if orden.estado == Estado::Recibido {
+                    if let Some((_, producto)) =
+                        productos.iter().find(|(pid, _)| *pid == orden.id_prod)
+                    {
+                        if Self::_es_descendiente(categorias, producto.id_categoria, raiz) {
+                            total_ventas = total_ventas.saturating_add(1);
+                            total_unidades = total_unidades.saturating_add(orden.cantidad);
+                        }
+                    }
+                }
+            }
+
+            for (id, _) in categorias {
+                if Self::_es_descendiente(categorias, *id, raiz) {
+                    if let Some((_, (suma, cant))) = calificaciones.iter().find(|(c, _)| c == id) {
+                        suma_calif = suma_calif.saturating_add(*suma);
+                        cant_calif = cant_calif.saturating_add(*cant);
+                    }
+                }
+            }
+
+            let calificacion_promedio_x100 = if cant_calif > 0 {
+                suma_calif.saturating_mul(100).saturating_div(cant_calif)
+            } else {
+                0
+            };
+
+            Ok(EstadisticasCategoria {
+                categoria: nombre,
+                total_ventas,
+                total_unidades,
+                calificacion_promedio_x100,
+                cantidad_productos,
+            })
+        }
+
+        /// Indica si la categoría `id` es `raiz` o una de sus descendientes.
+        ///
+        /// Recorre la cadena de padres; el número de pasos está acotado por la
+        /// cantidad de categorías.
+        fn _es_descendiente(categorias: &[(u32, Categoria)], id: u32, raiz: u32) -> bool {
+            let mut actual = Some(id);
+            for _ in 0..=categorias.len() {
+                match actual {
+                    Some(a) if a == raiz => return true,
+                    Some(a) => {
+                        actual = categorias
+                            .iter()
+                            .find(|(cid, _)| *cid == a)
+                            .and_then(|(_, cat)| cat.padre);
+                    }
+                    None => return false,
+                }
+            }
+            false
+        }
+
         /// Lógica interna para órdenes por usuario.
         ///
         /// Complejidad: O(o) donde o = cantidad de órdenes totales.
@@ -637,9 +1010,8 @@ mod reportes {
         ///
         /// Complejidad: O(u * o) donde u = cantidad de usuarios y o = cantidad de órdenes.
         fn _resumen_ordenes_todos_usuarios(&self) -> Vec<OrdenesUsuario> {
-            let marketplace = self.marketplace();
-            let usuarios = marketplace.listar_usuarios();
-            let ordenes = marketplace.listar_todas_ordenes();
+            let usuarios = self.listar_todos_usuarios();
+            let ordenes = self.marketplace().listar_todas_ordenes();
             Self::_procesar_resumen_ordenes_todos_usuarios(usuarios, ordenes)
         }
 
@@ -695,10 +1067,10 @@ mod reportes {
         /// Complejidad: O(o) donde o = cantidad de órdenes.
         fn _resumen_general(&self) -> (u32, u32, u32, u32) {
             let marketplace = self.marketplace();
-            let usuarios = marketplace.listar_usuarios();
+            let total_usuarios = marketplace.get_total_usuarios();
             let productos = marketplace.listar_todos_productos();
             let ordenes = marketplace.listar_todas_ordenes();
-            Self::_procesar_resumen_general(usuarios.len(), productos.len(), ordenes)
+            Self::_procesar_resumen_general(total_usuarios as usize, productos.len(), ordenes)
         }
 
         /// Procesa datos para generar resumen general del marketplace.
@@ -729,23 +1101,29 @@ mod reportes {
         /// Complejidad: O(p * c) donde p = cantidad de productos y c = categorías únicas.
         fn _listar_categorias(&self) -> Vec<String> {
             let marketplace = self.marketplace();
+            let categorias = marketplace.listar_categorias_registradas();
             let productos = marketplace.listar_todos_productos();
-            Self::_procesar_listar_categorias(&productos)
+            Self::_procesar_listar_categorias(&categorias, &productos)
         }
 
-        /// Procesa productos para extraer categorías únicas.
+        /// Procesa productos para extraer los nombres de sus categorías, sin repetir.
         ///
         /// Función pura que puede ser testeada sin dependencias externas.
-        fn _procesar_listar_categorias(productos: &[(u32, Producto)]) -> Vec<String> {
-            let mut categorias: Vec<String> = Vec::new();
+        fn _procesar_listar_categorias(
+            categorias: &[(u32, Categoria)],
+            productos: &[(u32, Producto)],
+        ) -> Vec<String> {
+            let mut ids: Vec<u32> = Vec::new();
 
             for (_pid, producto) in productos {
-                if !categorias.iter().any(|c| c == &producto.categoria) {
-                    categorias.push(producto.categoria.clone());
+                if !ids.contains(&producto.id_categoria) {
+                    ids.push(producto.id_categoria);
                 }
             }
 
-            categorias
+            ids.into_iter()
+                .filter_map(|id| Self::_nombre_categoria(categorias, id))
+                .collect()
         }
 
         /// Ordena usuarios por reputación descendente.
@@ -1,11 +1,32 @@
 mod tests {
     use super::*;
-    use market::{Orden, Producto, ReputacionUsuario};
+    use market::{
+        Categoria, EstadisticasReferido, Orden, Producto, ReputacionUsuario, TipoProducto,
+    };
 
     fn cuenta(n: u8) -> AccountId {
         AccountId::from([n; 32])
     }
 
+    /// Registro de categorías usado por los productos de prueba.
+    const CATEGORIAS: [&str; 5] = ["Electrónica", "Libros", "Cat", "NuevaCat", "Única"];
+
+    fn categorias_registradas() -> Vec<(u32, Categoria)> {
+        CATEGORIAS
+            .iter()
+            .zip(1u32..)
+            .map(|(nombre, id)| (id, crear_categoria(nombre, None)))
+            .collect()
+    }
+
+    fn id_de(categoria: &str) -> u32 {
+        categorias_registradas()
+            .into_iter()
+            .find(|(_, c)| c.nombre == categoria)
+            .map(|(id, _)| id)
+            .unwrap()
+    }
+
     fn crear_producto(vendedor: u8, nombre: &str, categoria: &str, precio: u128) -> Producto {
         Producto {
             vendedor: cuenta(vendedor),
@@ -13,10 +34,30 @@ mod tests {
             descripcion: String::from("Descripción"),
             precio,
             stock: 10,
-            categoria: String::from(categoria),
+            id_categoria: id_de(categoria),
+            requisito_reputacion: None,
+            activo: true,
+            pausas_vendedor: 0,
+            porcentaje_referido: 0,
+            tipo: TipoProducto::Fisico,
+            plan_suscripcion: None,
+            lanzamiento: None,
+            creado_en: 0,
+            actualizado_en: 0,
+        }
+    }
+
+    fn crear_producto_en(id_categoria: u32) -> Producto {
+        Producto {
+            id_categoria,
+            ..crear_producto(1, "Prod", "Cat", 100)
         }
     }
 
+    fn crear_categoria(nombre: &str, padre: Option<u32>) -> Categoria {
+        Categoria { nombre: String::from(nombre), padre }
+    }
+
     fn crear_orden(comprador: u8, vendedor: u8, id_prod: u32, cantidad: u32, estado: Estado) -> Orden {
         Orden {
             comprador: cuenta(comprador),
@@ -25,6 +66,7 @@ mod tests {
             cantidad,
             estado,
             monto_total: 1000,
+            destinatario: cuenta(comprador),
         }
     }
 
@@ -218,6 +260,56 @@ mod tests {
         assert!(Reportes::_procesar_productos_mas_vendidos(ordenes, productos, 0).is_empty());
     }
 
+    #[ink::test]
+    fn test_procesar_productos_mas_deseados() {
+        let productos = vec![
+            (1, crear_producto(1, "Laptop", "Electrónica", 1000)),
+            (2, crear_producto(2, "Mouse", "Electrónica", 50)),
+            (3, crear_producto(2, "Teclado", "Electrónica", 80)),
+        ];
+        let conteos = vec![(1, 2), (2, 7), (3, 0), (9, 4)];
+
+        let resultado = Reportes::_procesar_productos_mas_deseados(conteos.clone(), productos.clone(), 5);
+        assert_eq!(resultado.len(), 2);
+        assert_eq!(resultado[0].id_producto, 2);
+        assert_eq!(resultado[0].nombre, "Mouse");
+        assert_eq!(resultado[0].cantidad_favoritos, 7);
+        assert_eq!(resultado[1].id_producto, 1);
+
+        let resultado = Reportes::_procesar_productos_mas_deseados(conteos.clone(), productos.clone(), 2);
+        assert_eq!(resultado.len(), 2);
+
+        assert!(Reportes::_procesar_productos_mas_deseados(conteos, productos.clone(), 0).is_empty());
+        assert!(Reportes::_procesar_productos_mas_deseados(Vec::new(), productos, 5).is_empty());
+    }
+
+    #[ink::test]
+    fn test_procesar_ranking_referidos() {
+        let estadisticas = |referidas: u32, completadas: u32, ganancias: u128| EstadisticasReferido {
+            ordenes_referidas: referidas,
+            ordenes_completadas: completadas,
+            ganancias,
+        };
+        let referidos = vec![
+            (cuenta(7), estadisticas(3, 2, 25)),
+            (cuenta(8), estadisticas(1, 1, 50)),
+            (cuenta(9), estadisticas(4, 0, 0)),
+        ];
+
+        let resultado = Reportes::_procesar_ranking_referidos(referidos.clone(), 5);
+        assert_eq!(resultado.len(), 3);
+        assert_eq!(resultado[0].referido, cuenta(8));
+        assert_eq!(resultado[0].ganancias, 50);
+        assert_eq!(resultado[1].referido, cuenta(7));
+        assert_eq!(resultado[1].ordenes_referidas, 3);
+        assert_eq!(resultado[1].ordenes_completadas, 2);
+        assert_eq!(resultado[1].ganancias, 25);
+        assert_eq!(resultado[2].referido, cuenta(9));
+
+        assert_eq!(Reportes::_procesar_ranking_referidos(referidos, 1).len(), 1);
+        assert!(Reportes::_procesar_ranking_referidos(Vec::new(), 5).is_empty());
+    }
+
     #[ink::test]
     fn test_procesar_estadisticas_por_categoria() {
         let productos = vec![
@@ -231,11 +323,13 @@ mod tests {
             (3, crear_orden(12, 3, 3, 1, Estado::Recibido)),
         ];
         let calificaciones = vec![
-            (String::from("Electrónica"), (45, 10)),
-            (String::from("Libros"), (40, 10)),
+            (id_de("Electrónica"), (45, 10)),
+            (id_de("Libros"), (40, 10)),
         ];
 
-        let resultado = Reportes::_procesar_estadisticas_por_categoria(productos, ordenes, calificaciones);
+        let resultado = Reportes::_procesar_estadisticas_por_categoria(
+            &categorias_registradas(), productos, ordenes, calificaciones,
+        );
         assert_eq!(resultado.len(), 2);
         
         let electronica = resultado.iter().find(|s| s.categoria == "Electrónica").unwrap();
@@ -253,11 +347,11 @@ mod tests {
     fn test_procesar_estadisticas_por_categoria_casos_borde() {
         let productos = vec![(1, crear_producto(1, "Test", "NuevaCat", 100))];
         let ordenes = vec![(1, crear_orden(10, 1, 1, 3, Estado::Recibido))];
-        let resultado = Reportes::_procesar_estadisticas_por_categoria(productos, ordenes, Vec::new());
+        let resultado = Reportes::_procesar_estadisticas_por_categoria(&categorias_registradas(), productos, ordenes, Vec::new());
         assert_eq!(resultado[0].calificacion_promedio_x100, 0);
 
         let productos = vec![(1, crear_producto(1, "Test", "Cat", 100))];
-        let resultado = Reportes::_procesar_estadisticas_por_categoria(productos, Vec::new(), Vec::new());
+        let resultado = Reportes::_procesar_estadisticas_por_categoria(&categorias_registradas(), productos, Vec::new(), Vec::new());
         assert_eq!(resultado[0].total_ventas, 0);
         assert_eq!(resultado[0].cantidad_productos, 1);
     }
@@ -274,7 +368,7 @@ mod tests {
         ];
 
         let resultado = Reportes::_procesar_estadisticas_categoria(
-            productos.clone(), ordenes.clone(), String::from("Electrónica"), (45, 10),
+            &categorias_registradas(), productos.clone(), ordenes.clone(), id_de("Electrónica"), (45, 10),
         );
         assert!(resultado.is_ok());
         let stats = resultado.unwrap();
@@ -284,13 +378,18 @@ mod tests {
         assert_eq!(stats.calificacion_promedio_x100, 450);
 
         let resultado = Reportes::_procesar_estadisticas_categoria(
-            productos.clone(), Vec::new(), String::from("NoExiste"), (0, 0),
+            &categorias_registradas(), productos.clone(), Vec::new(), 99, (0, 0),
+        );
+        assert_eq!(resultado.unwrap_err(), Error::CategoriaNoEncontrada);
+
+        let resultado = Reportes::_procesar_estadisticas_categoria(
+            &categorias_registradas(), productos.clone(), Vec::new(), id_de("Libros"), (0, 0),
         );
         assert_eq!(resultado.unwrap_err(), Error::CategoriaNoEncontrada);
 
         let productos = vec![(1, crear_producto(1, "Test", "Cat", 100))];
         let ordenes = vec![(1, crear_orden(10, 1, 1, 5, Estado::Recibido))];
-        let resultado = Reportes::_procesar_estadisticas_categoria(productos, ordenes, String::from("Cat"), (0, 0));
+        let resultado = Reportes::_procesar_estadisticas_categoria(&categorias_registradas(), productos, ordenes, id_de("Cat"), (0, 0));
         assert_eq!(resultado.unwrap().calificacion_promedio_x100, 0);
 
         let productos = vec![(1u32, crear_producto(1, "Prod", "Cat", 100))];
@@ -298,12 +397,89 @@ mod tests {
             (1u32, crear_orden(2, 1, 1, 5, Estado::Cancelada)),
             (2u32, crear_orden(3, 1, 1, 3, Estado::Recibido)),
         ];
-        let resultado = Reportes::_procesar_estadisticas_categoria(productos, ordenes, String::from("Cat"), (20, 5));
+        let resultado = Reportes::_procesar_estadisticas_categoria(&categorias_registradas(), productos, ordenes, id_de("Cat"), (20, 5));
         let stats = resultado.unwrap();
         assert_eq!(stats.total_ventas, 1);
         assert_eq!(stats.total_unidades, 3);
     }
 
+    #[ink::test]
+    fn test_procesar_estadisticas_acumuladas() {
+        // 1 Hogar
+        // ├── 2 Cocina
+        // │   └── 4 Vajilla
+        // └── 3 Jardín
+        // 5 Libros
+        let categorias = vec![
+            (1, crear_categoria("Hogar", None)),
+            (2, crear_categoria("Cocina", Some(1))),
+            (3, crear_categoria("Jardín", Some(1))),
+            (4, crear_categoria("Vajilla", Some(2))),
+            (5, crear_categoria("Libros", None)),
+        ];
+        let productos = vec![
+            (1, crear_producto_en(1)),
+            (2, crear_producto_en(2)),
+            (3, crear_producto_en(4)),
+            (4, crear_producto_en(5)),
+        ];
+        let ordenes = vec![
+            (1, crear_orden(10, 1, 2, 2, Estado::Recibido)),
+            (2, crear_orden(10, 1, 3, 3, Estado::Recibido)),
+            (3, crear_orden(10, 1, 3, 1, Estado::Pendiente)),
+            (4, crear_orden(10, 1, 4, 5, Estado::Recibido)),
+        ];
+        let calificaciones = vec![
+            (2, (8, 2)),
+            (4, (5, 1)),
+            (5, (3, 1)),
+        ];
+
+        let hogar = Reportes::_procesar_estadisticas_acumuladas(
+            &categorias, &productos, &ordenes, &calificaciones, 1,
+        )
+        .unwrap();
+        assert_eq!(hogar.categoria, "Hogar");
+        assert_eq!(hogar.cantidad_productos, 3);
+        assert_eq!(hogar.total_ventas, 2);
+        assert_eq!(hogar.total_unidades, 5);
+        assert_eq!(hogar.calificacion_promedio_x100, 433);
+
+        let cocina = Reportes::_procesar_estadisticas_acumuladas(
+            &categorias, &productos, &ordenes, &calificaciones, 2,
+        )
+        .unwrap();
+        assert_eq!(cocina.cantidad_productos, 2);
+        assert_eq!(cocina.total_unidades, 5);
+
+        let jardin = Reportes::_procesar_estadisticas_acumuladas(
+            &categorias, &productos, &ordenes, &calificaciones, 3,
+        )
+        .unwrap();
+        assert_eq!(jardin.cantidad_productos, 0);
+        assert_eq!(jardin.calificacion_promedio_x100, 0);
+
+        assert_eq!(
+            Reportes::_procesar_estadisticas_acumuladas(
+                &categorias, &productos, &ordenes, &calificaciones, 99,
+            ),
+            Err(Error::CategoriaNoEncontrada)
+        );
+    }
+
+    #[ink::test]
+    fn test_es_descendiente() {
+        let categorias = vec![
+            (1, crear_categoria("A", None)),
+            (2, crear_categoria("B", Some(1))),
+            (3, crear_categoria("C", Some(2))),
+        ];
+        assert!(Reportes::_es_descendiente(&categorias, 3, 1));
+        assert!(Reportes::_es_descendiente(&categorias, 1, 1));
+        assert!(!Reportes::_es_descendiente(&categorias, 1, 3));
+        assert!(!Reportes::_es_descendiente(&categorias, 0, 1));
+    }
+
     #[ink::test]
     fn test_procesar_ordenes_por_usuario() {
         let ordenes = vec![
@@ -403,19 +579,19 @@ mod tests {
             (2, crear_producto(2, "B", "Libros", 50)),
             (3, crear_producto(3, "C", "Electrónica", 200)),
         ];
-        let resultado = Reportes::_procesar_listar_categorias(&productos);
+        let resultado = Reportes::_procesar_listar_categorias(&categorias_registradas(), &productos);
         assert_eq!(resultado.len(), 2);
         assert!(resultado.contains(&String::from("Electrónica")));
         assert!(resultado.contains(&String::from("Libros")));
 
         let vacio: Vec<(u32, Producto)> = Vec::new();
-        assert!(Reportes::_procesar_listar_categorias(&vacio).is_empty());
+        assert!(Reportes::_procesar_listar_categorias(&categorias_registradas(), &vacio).is_empty());
 
         let productos = vec![
             (1, crear_producto(1, "A", "Única", 100)),
             (2, crear_producto(2, "B", "Única", 200)),
         ];
-        let resultado = Reportes::_procesar_listar_categorias(&productos);
+        let resultado = Reportes::_procesar_listar_categorias(&categorias_registradas(), &productos);
         assert_eq!(resultado.len(), 1);
         assert_eq!(resultado[0], "Única");
     }
@@ -428,7 +604,7 @@ mod tests {
         let producto = ProductoVendido {
             id_producto: 1,
             nombre: String::from("Laptop"),
-            categoria: String::from("Electrónica"),
+            id_categoria: 1,
             vendedor: cuenta(5),
             unidades_vendidas: 100,
         };